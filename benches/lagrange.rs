@@ -0,0 +1,49 @@
+// recovering a batch of secrets split over the same x coordinates, as the
+// reconciliation job does: `lagrange_interpolate` rebuilds the basis for every
+// secret, `LagrangeBasis` builds it once and reuses it for the whole batch
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use k256::Scalar;
+use quorum::core::math::{lagrange_interpolate, random_scalar, LagrangeBasis};
+
+/// secrets recovered per iteration
+const BATCH: usize = 100;
+
+fn recover_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("recover_batch");
+    group.throughput(Throughput::Elements(BATCH as u64));
+
+    for threshold in [2usize, 10, 25, 50, 100] {
+        let xs: Vec<Scalar> = (1..=threshold as u64).map(Scalar::from).collect();
+        // the y values don't need to lie on a low-degree polynomial to be timed
+        let batch: Vec<Vec<Scalar>> = (0..BATCH)
+            .map(|_| (0..threshold).map(|_| random_scalar()).collect())
+            .collect();
+        let points: Vec<Vec<(Scalar, Scalar)>> = batch
+            .iter()
+            .map(|ys| xs.iter().copied().zip(ys.iter().copied()).collect())
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("lagrange_interpolate", threshold), &points, |b, points| {
+            b.iter(|| {
+                for shares in points {
+                    black_box(lagrange_interpolate(black_box(shares)).unwrap());
+                }
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("lagrange_basis", threshold), &batch, |b, batch| {
+            b.iter(|| {
+                let basis = LagrangeBasis::new(black_box(&xs), &Scalar::ZERO).unwrap();
+                for ys in batch {
+                    black_box(basis.interpolate(black_box(ys)).unwrap());
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, recover_batch);
+criterion_main!(benches);
@@ -1,12 +1,72 @@
 // client binary: coordinator that distributes shares to custodian nodes
-// connects to multiple nodes, splits a secret, distributes shares, then recovers
+// `client split` (the default) splits a secret, distributes shares, then recovers from all of them
+// `client split-weighted` does the same with custodians counting for different numbers of votes
+// `client split-packed` splits a batch of keys with one packed share per node
+// `client dkg` runs distributed key generation so no single party ever holds the key
+// `client sign` produces a frost (bip-340 schnorr) signature with a threshold of nodes,
+// optionally for a non-hardened bip-32 child of the key
+// `client sign-ecdsa` produces a recoverable ecdsa signature with a threshold of nodes
+// `client refresh` re-randomizes every node's share without changing the secret
+// `client reshare` moves a ceremony to a new threshold and set of nodes
+// `client repair` rebuilds a lost node's share from a threshold of the others
+// `client pubkey` derives the group public key and its addresses from the nodes' public shares
+// `client xpub` sets up a bip-32 chain code for a ceremony and prints xpubs for any path
+// `client encrypt` / `client decrypt` encrypt to the group key and decrypt with a threshold of nodes
+// `client mnemonic` exports the nodes' shares as slip-39 mnemonics and reads them back
 
-use k256::Scalar;
+use std::collections::BTreeSet;
+use std::env;
+use std::io::BufRead;
+
+use k256::sha2::{Digest, Sha256};
+use k256::{ProjectivePoint, Scalar};
 use rand::rngs::OsRng;
+use rand::RngCore;
 use tonic::transport::Channel;
 
-use quorum::{split_secret, recover_secret, Share, CustodianClient, ShareData, RetrieveRequest};
-use quorum::network::storage::{scalar_to_bytes, bytes_to_scalar};
+use quorum::{split_secret_verifiable, recover_secret_checked, recover_secret_robust, FragmentError, SecretShare, CustodianClient, ShareData, RetrieveRequest};
+use quorum::core::ecdsa::{Round5Output, SignerSet};
+use quorum::core::frost::{self, SignatureShare, SigningCommitments};
+use quorum::core::packed::{self, PackedShare};
+use quorum::core::math::random_scalar;
+use quorum::core::pubkey;
+use quorum::core::bip32::{self, ExtendedPublicKey};
+use quorum::core::decryption;
+use quorum::core::ecies;
+use quorum::core::scheme::{Curve, ShareMetadata};
+use quorum::core::slip39;
+use quorum::core::weighted::{self, WeightedShare};
+use quorum::core::vss::evaluate_commitments;
+use quorum::network::proto::{
+    ChainCodeRequest,
+    DealerCommitments, DealerMessage, DkgCommitRequest, DkgCompleteRequest, DkgDealRequest,
+    DkgFinalizeRequest, DkgReconstructRequest, DkgRespondRequest, DkgVerifyRequest,
+    EcdsaRound1Request, EcdsaRound2Request, EcdsaRound3Request, EcdsaRound4Request,
+    EcdsaRound5Request, EcdsaSetupRequest, FrostCommitRequest, FrostCommitment, FrostSignRequest,
+    PartialDecryptRequest,
+    JoinRequest, PeerKey, RefreshApplyRequest, RefreshCommitRequest, RefreshDealRequest,
+    RefreshStartRequest, RepairCombineRequest, RepairCompleteRequest, RepairSplitRequest,
+    RepairStartRequest, ReshareCommitRequest, SharePoint, WeightedShareData, ReshareCompleteRequest, ReshareDealRequest,
+    ReshareStartRequest,
+};
+use quorum::network::convert;
+use quorum::network::storage::{scalar_to_bytes, bytes_to_scalar, secret_to_bytes, bytes_to_secret, point_to_bytes, bytes_to_point};
+
+// node addresses (run 3 nodes on these ports first)
+const NODE_ADDRS: [&str; 3] = [
+    "http://127.0.0.1:50051",
+    "http://127.0.0.1:50052",
+    "http://127.0.0.1:50053",
+];
+
+// every node a ceremony can be reshared onto (run 5 nodes to grow a committee to 5)
+const NODE_POOL: [&str; 5] = [
+    "http://127.0.0.1:50051",
+    "http://127.0.0.1:50052",
+    "http://127.0.0.1:50053",
+    "http://127.0.0.1:50054",
+    "http://127.0.0.1:50055",
+];
 
 /// connects to a custodian node at the given address
 async fn connect_to_node(addr: &str) -> Result<CustodianClient<Channel>, Box<dyn std::error::Error>> {
@@ -15,15 +75,16 @@ async fn connect_to_node(addr: &str) -> Result<CustodianClient<Channel>, Box<dyn
 }
 
 /// stores a share on a remote custodian node
+/// the node verifies the share against the commitments before accepting it
 async fn store_share_on_node(
     client: &mut CustodianClient<Channel>,
     ceremony_id: &str,
-    share: &Share,
+    share: &SecretShare,
+    commitments: &[ProjectivePoint],
 ) -> Result<(), Box<dyn std::error::Error>> {
     let request = ShareData {
-        ceremony_id: ceremony_id.to_string(),
-        x: scalar_to_bytes(&share.x),
-        y: scalar_to_bytes(&share.y),
+        commitments: commitments.iter().map(point_to_bytes).collect(),
+        ..convert::share_to_proto(ceremony_id, share)
     };
 
     let response = client.store_share(request).await?;
@@ -31,11 +92,12 @@ async fn store_share_on_node(
     Ok(())
 }
 
-/// retrieves a share from a remote custodian node
+/// retrieves a share and its refresh epoch from a remote custodian node
+/// a share from a dealer split comes back with its metadata, so recovery checks it
 async fn retrieve_share_from_node(
     client: &mut CustodianClient<Channel>,
     ceremony_id: &str,
-) -> Result<Share, Box<dyn std::error::Error>> {
+) -> Result<(SecretShare, u64), Box<dyn std::error::Error>> {
     let request = RetrieveRequest {
         ceremony_id: ceremony_id.to_string(),
     };
@@ -43,39 +105,123 @@ async fn retrieve_share_from_node(
     let response = client.retrieve_share(request).await?;
     let data = response.into_inner();
 
-    let share = Share {
-        x: bytes_to_scalar(&data.x),
-        y: bytes_to_scalar(&data.y),
-    };
+    let share = convert::share_from_proto(&data)?;
+
+    Ok((share, data.epoch))
+}
 
-    Ok(share)
+/// decodes a share a node sent back, which may be malformed
+fn share_from_bytes(x: &[u8], y: &[u8]) -> Result<SecretShare, Box<dyn std::error::Error>> {
+    Ok(SecretShare {
+        x: bytes_to_scalar(x).ok_or("malformed share x")?,
+        y: bytes_to_secret(y).ok_or("malformed share y")?,
+        meta: None,
+    })
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("=== quorum client coordinator ===\n");
 
-    // node addresses (run 3 nodes on these ports first)
-    let node_addrs = vec![
-        "http://127.0.0.1:50051",
-        "http://127.0.0.1:50052",
-        "http://127.0.0.1:50053",
-    ];
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(String::as_str) {
+        None | Some("split") => run_split().await,
+        Some("split-weighted") => {
+            let usage = "usage: client split-weighted <threshold> <weight,weight,...>";
+            let threshold: usize = args.get(2).ok_or(usage)?.parse()?;
+            let weights = args
+                .get(3)
+                .ok_or(usage)?
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<Vec<usize>, _>>()?;
+            run_split_weighted(threshold, &weights).await
+        }
+        Some("split-packed") => {
+            let keys: usize = match args.get(2) {
+                Some(keys) => keys.parse()?,
+                None => 3,
+            };
+            run_split_packed(keys).await
+        }
+        Some("dkg") => run_dkg(args.get(2).map(String::as_str).unwrap_or("dkg-001")).await,
+        Some("sign") => {
+            let message = args.get(2).ok_or("usage: client sign <message> [ceremony-id] [path]")?;
+            run_sign(
+                message,
+                args.get(3).map(String::as_str).unwrap_or("dkg-001"),
+                args.get(4).map(String::as_str),
+            )
+            .await
+        }
+        Some("reshare") => {
+            let usage = "usage: client reshare <new-threshold> <new-total> [ceremony-id]";
+            let threshold: u32 = args.get(2).ok_or(usage)?.parse()?;
+            let total: u32 = args.get(3).ok_or(usage)?.parse()?;
+            run_reshare(threshold, total, args.get(4).map(String::as_str).unwrap_or("dkg-001")).await
+        }
+        Some("repair") => {
+            let lost: u32 = args.get(2).ok_or("usage: client repair <lost-index> [ceremony-id]")?.parse()?;
+            run_repair(lost, args.get(3).map(String::as_str).unwrap_or("dkg-001")).await
+        }
+        Some("mnemonic") => match args.get(2).map(String::as_str) {
+            Some("export") => run_mnemonic_export(args.get(3).map(String::as_str).unwrap_or("ceremony-001")).await,
+            Some("import") => run_mnemonic_import(),
+            _ => Err("usage: client mnemonic export [ceremony-id] | client mnemonic import".into()),
+        },
+        Some("encrypt") => {
+            let message = args.get(2).ok_or("usage: client encrypt <message> [ceremony-id]")?;
+            run_encrypt(message, args.get(3).map(String::as_str).unwrap_or("dkg-001")).await
+        }
+        Some("decrypt") => {
+            let ciphertext = args.get(2).ok_or("usage: client decrypt <ciphertext-hex> [ceremony-id]")?;
+            run_decrypt(ciphertext, args.get(3).map(String::as_str).unwrap_or("dkg-001")).await
+        }
+        Some("xpub") => match args.get(2).map(String::as_str) {
+            Some("init") => run_xpub_init(args.get(3).map(String::as_str).unwrap_or("dkg-001")).await,
+            path => run_xpub(path.unwrap_or("m"), args.get(3).map(String::as_str).unwrap_or("dkg-001")).await,
+        },
+        Some("pubkey") => run_pubkey(args.get(2).map(String::as_str).unwrap_or("dkg-001")).await,
+        Some("refresh") => run_refresh(args.get(2).map(String::as_str).unwrap_or("dkg-001")).await,
+        Some("sign-ecdsa") => {
+            let message = args
+                .get(2)
+                .ok_or("usage: client sign-ecdsa <message | 0x<32-byte hash>> [ceremony-id]")?;
+            run_sign_ecdsa(message, args.get(3).map(String::as_str).unwrap_or("dkg-001")).await
+        }
+        Some(other) => {
+            println!("unknown command '{}'", other);
+            println!(
+                "usage: client [split | split-weighted <threshold> <weight,weight,...> | split-packed [keys] \
+                 | dkg [ceremony-id] | sign <message> [ceremony-id] [path] \
+                 | sign-ecdsa <message | 0x<hash>> [ceremony-id] | refresh [ceremony-id] \
+                 | reshare <new-threshold> <new-total> [ceremony-id] \
+                 | repair <lost-index> [ceremony-id] | pubkey [ceremony-id] \
+                 | xpub init [ceremony-id] | xpub [path] [ceremony-id] \
+                 | encrypt <message> [ceremony-id] | decrypt <ciphertext-hex> [ceremony-id] \
+                 | mnemonic export [ceremony-id] | mnemonic import]"
+            );
+            std::process::exit(1);
+        }
+    }
+}
 
+/// dealer-based flow: the client generates a secret, splits it and recovers it again
+async fn run_split() -> Result<(), Box<dyn std::error::Error>> {
+    let node_addrs = NODE_ADDRS;
     let threshold = 2;
     let total_shares = 3;
     let ceremony_id = "ceremony-001";
 
     // step 1: generate a random secret
     println!("step 1: generating random secret...");
-    let mut rng = OsRng;
-    let secret = Scalar::generate_vartime(&mut rng);
+    let secret = random_scalar();
     println!("secret (hex): {}\n", hex::encode(secret.to_bytes()));
 
-    // step 2: split the secret into shares
+    // step 2: split the secret into shares with feldman commitments
     println!("step 2: splitting secret into {} shares (threshold: {})...", total_shares, threshold);
-    let shares = split_secret(&secret, threshold, total_shares)?;
-    println!("generated {} shares\n", shares.len());
+    let (shares, commitments) = split_secret_verifiable(&secret, threshold, total_shares)?;
+    println!("generated {} shares and {} commitments\n", shares.len(), commitments.len());
 
     // step 3: connect to nodes and distribute shares
     println!("step 3: distributing shares to custodian nodes...");
@@ -87,7 +233,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Ok(mut client) => {
                 println!("connected");
                 // storing the share on this node
-                store_share_on_node(&mut client, ceremony_id, &shares[i]).await?;
+                store_share_on_node(&mut client, ceremony_id, &shares[i], &commitments).await?;
                 clients.push(client);
             }
             Err(e) => {
@@ -98,26 +244,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     println!();
 
-    // step 4: retrieve shares from threshold number of nodes
-    println!("step 4: retrieving shares from {} nodes for recovery...", threshold);
-    let mut recovered_shares: Vec<Share> = Vec::new();
+    // step 4: retrieve shares from every node, the spares let recovery catch corrupted ones
+    println!("step 4: retrieving shares from all {} nodes for recovery...", clients.len());
+    let mut recovered_shares: Vec<SecretShare> = Vec::new();
+    let mut epochs = BTreeSet::new();
 
-    for (i, client) in clients.iter_mut().take(threshold).enumerate() {
+    for (i, client) in clients.iter_mut().enumerate() {
         print!("retrieving from node {}... ", i + 1);
-        let share = retrieve_share_from_node(client, ceremony_id).await?;
-        println!("got share");
+        let (share, epoch) = retrieve_share_from_node(client, ceremony_id).await?;
+        println!("got share (epoch {})", epoch);
         recovered_shares.push(share);
+        epochs.insert(epoch);
+    }
+
+    // shares from different refresh epochs lie on different polynomials
+    if epochs.len() > 1 {
+        println!("✗ error! shares come from different refresh epochs");
+        std::process::exit(1);
     }
     println!();
 
-    // step 5: recover the secret using lagrange interpolation
-    println!("step 5: recovering secret using lagrange interpolation...");
-    let recovered_secret = recover_secret(&recovered_shares)?;
-    println!("recovered (hex): {}\n", hex::encode(recovered_secret.to_bytes()));
+    // step 5: recover the secret, decoding past any corrupted shares
+    println!("step 5: recovering secret with error correction (berlekamp-welch)...");
+    let (recovered_secret, corrupted) = recover_secret_robust(&recovered_shares, threshold)?;
+    for x in &corrupted {
+        println!("  -> share at x = {} is corrupted, ignoring it", hex::encode(x.to_bytes()));
+    }
+    println!("recovered (hex): {}\n", hex::encode(recovered_secret.expose_secret().to_bytes()));
 
     // step 6: verify the recovery
     println!("step 6: verifying...");
-    if recovered_secret == secret {
+    if *recovered_secret.expose_secret() == secret {
         println!("✓ success! recovered secret matches the original");
     } else {
         println!("✗ error! secrets do not match");
@@ -126,3 +283,1470 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// weighted flow: like `split`, but node i carries weights[i] votes and receives that many
+/// shares; recovery asks nodes in order until their combined weight meets the threshold
+async fn run_split_weighted(threshold: usize, weights: &[usize]) -> Result<(), Box<dyn std::error::Error>> {
+    if weights.len() > NODE_POOL.len() {
+        println!("✗ error! at most {} custodians, got {}", NODE_POOL.len(), weights.len());
+        std::process::exit(1);
+    }
+    let ceremony_id = "weighted-001";
+
+    // step 1: generate a random secret
+    println!("step 1: generating random secret...");
+    let secret = random_scalar();
+    println!("secret (hex): {}\n", hex::encode(secret.to_bytes()));
+
+    // step 2: split it, every custodian gets one share per vote
+    println!("step 2: splitting secret for weights {:?} (threshold: {})...", weights, threshold);
+    let (custodians, commitments) = weighted::split_secret_weighted(&secret, threshold, weights)?;
+    println!("generated {} shares and {} commitments\n", weights.iter().sum::<usize>(), commitments.len());
+
+    // step 3: distribute each custodian's shares to its node
+    println!("step 3: distributing shares to custodian nodes...");
+    let mut clients = Vec::new();
+    for (custodian, addr) in custodians.iter().zip(NODE_POOL) {
+        let mut client = connect_to_node(addr).await?;
+        let response = client
+            .store_weighted_shares(WeightedShareData {
+                ceremony_id: ceremony_id.to_string(),
+                shares: custodian
+                    .shares
+                    .iter()
+                    .map(|share| SharePoint {
+                        x: scalar_to_bytes(&share.x),
+                        y: secret_to_bytes(&share.y),
+                    })
+                    .collect(),
+                commitments: commitments.iter().map(point_to_bytes).collect(),
+            })
+            .await?
+            .into_inner();
+        println!("  -> {} (weight {}): {}", addr, custodian.weight(), response.message);
+        clients.push((client, custodian.custodian));
+    }
+    println!();
+
+    // step 4: collect custodians until their combined weight meets the threshold
+    println!("step 4: retrieving shares until the combined weight reaches {}...", threshold);
+    let mut collected = Vec::new();
+    let mut weight = 0;
+    for (client, custodian) in clients.iter_mut() {
+        if weight >= threshold {
+            break;
+        }
+        let data = client
+            .retrieve_weighted_shares(RetrieveRequest {
+                ceremony_id: ceremony_id.to_string(),
+            })
+            .await?
+            .into_inner();
+        let shares: Vec<SecretShare> = data
+            .shares
+            .iter()
+            .map(|point| share_from_bytes(&point.x, &point.y))
+            .collect::<Result<_, _>>()?;
+        weight += shares.len();
+        println!("  -> custodian {} contributed weight {} (total {})", custodian, shares.len(), weight);
+        collected.push(WeightedShare {
+            custodian: *custodian,
+            shares,
+        });
+    }
+    println!();
+
+    // step 5: recover the secret
+    println!("step 5: recovering secret from {} custodians...", collected.len());
+    let recovered_secret = weighted::recover_secret_weighted(&collected, threshold)?;
+    println!("recovered (hex): {}\n", hex::encode(recovered_secret.expose_secret().to_bytes()));
+
+    // step 6: verify the recovery
+    println!("step 6: verifying...");
+    if *recovered_secret.expose_secret() == secret {
+        println!("✓ success! recovered secret matches the original");
+    } else {
+        println!("✗ error! secrets do not match");
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// packed flow: a batch of deposit keys goes into one polynomial, so every node
+/// stores a single share for the whole batch; recovery asks just enough nodes
+async fn run_split_packed(keys: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let privacy_threshold = 1;
+    let total_shares = NODE_POOL.len();
+    let ceremony_id = "packed-001";
+    if keys == 0 || keys + privacy_threshold > total_shares {
+        println!(
+            "✗ error! {} nodes fit between 1 and {} keys per batch, got {}",
+            total_shares,
+            total_shares - privacy_threshold,
+            keys
+        );
+        std::process::exit(1);
+    }
+
+    // step 1: generate a batch of random keys
+    println!("step 1: generating {} random keys...", keys);
+    let secrets: Vec<Scalar> = (0..keys).map(|_| random_scalar()).collect();
+    for (i, secret) in secrets.iter().enumerate() {
+        println!("key {} (hex): {}", i + 1, hex::encode(secret.to_bytes()));
+    }
+    println!();
+
+    // step 2: pack them into one polynomial
+    println!(
+        "step 2: packing {} keys into {} shares (privacy threshold: {})...",
+        keys, total_shares, privacy_threshold
+    );
+    let shares = packed::split_secrets_packed(&secrets, privacy_threshold, total_shares)?;
+    println!("any {} shares recover the whole batch\n", shares[0].threshold());
+
+    // step 3: one store call per node covers the batch
+    println!("step 3: distributing shares to custodian nodes...");
+    let mut clients = Vec::new();
+    for (share, addr) in shares.iter().zip(NODE_POOL) {
+        let mut client = connect_to_node(addr).await?;
+        let response = client
+            .store_share(ShareData {
+                packed_secrets: share.secrets,
+                privacy_threshold: share.privacy_threshold,
+                ..convert::share_to_proto(ceremony_id, &share.share)
+            })
+            .await?
+            .into_inner();
+        println!("  -> {}: {}", addr, response.message);
+        clients.push(client);
+    }
+    println!();
+
+    // step 4: collect just enough shares
+    let threshold = shares[0].threshold();
+    println!("step 4: retrieving shares from {} nodes...", threshold);
+    let mut collected = Vec::new();
+    for (i, client) in clients.iter_mut().take(threshold).enumerate() {
+        let data = client
+            .retrieve_share(RetrieveRequest {
+                ceremony_id: ceremony_id.to_string(),
+            })
+            .await?
+            .into_inner();
+        println!("  -> node {} holds a share of {} keys", i + 1, data.packed_secrets);
+        collected.push(PackedShare {
+            secrets: data.packed_secrets,
+            privacy_threshold: data.privacy_threshold,
+            share: convert::share_from_proto(&data)?,
+        });
+    }
+    println!();
+
+    // step 5: recover every key of the batch
+    println!("step 5: recovering the batch...");
+    let recovered = packed::recover_secrets_packed(&collected)?;
+    println!();
+
+    // step 6: verify the recovery
+    println!("step 6: verifying...");
+    if recovered == secrets {
+        println!("✓ success! all {} recovered keys match the originals", keys);
+    } else {
+        println!("✗ error! keys do not match");
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// dealerless flow: the nodes generate a shared key together
+/// the client only relays messages, sub-shares are encrypted between nodes
+async fn run_dkg(ceremony_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let threshold = 2u32;
+    let total = NODE_ADDRS.len() as u32;
+
+    // step 1: every node joins and publishes an ephemeral encryption key
+    println!("step 1: starting key generation '{}' ({}-of-{})...", ceremony_id, threshold, total);
+    let mut clients: Vec<CustodianClient<Channel>> = Vec::new();
+    let mut peers = Vec::new();
+
+    for (i, addr) in NODE_ADDRS.iter().enumerate() {
+        let index = i as u32 + 1;
+        let mut client = connect_to_node(addr).await?;
+        let response = client
+            .join_ceremony(JoinRequest {
+                node_id: format!("participant-{}", index),
+                ceremony_id: ceremony_id.to_string(),
+                threshold,
+                total,
+                index,
+            })
+            .await?
+            .into_inner();
+        println!("  -> {}: {}", addr, response.message);
+
+        // the node signs its key with its roster identity, peers refuse it otherwise
+        peers.push(PeerKey {
+            index,
+            encryption_key: response.encryption_key,
+            signature: response.key_signature,
+        });
+        clients.push(client);
+    }
+    println!();
+
+    // step 2: every node deals its polynomial
+    println!("step 2: collecting commitments and encrypted sub-shares...");
+    let mut deals = Vec::new();
+    for client in clients.iter_mut() {
+        let deal = client
+            .dkg_deal(DkgDealRequest {
+                ceremony_id: ceremony_id.to_string(),
+                peers: peers.clone(),
+            })
+            .await?
+            .into_inner();
+        println!("  -> dealer {} sent {} sub-shares", deal.dealer, deal.sub_shares.len());
+        deals.push(deal);
+    }
+    println!();
+
+    // step 3: every node verifies what it was dealt and echoes the commitments it received
+    println!("step 3: verifying sub-shares...");
+    let mut echoes = Vec::new();
+    for (client, peer) in clients.iter_mut().zip(&peers) {
+        let dealers = deals
+            .iter()
+            .filter(|deal| deal.dealer != peer.index)
+            .map(|deal| DealerMessage {
+                dealer: deal.dealer,
+                commitments: deal.commitments.clone(),
+                ciphertext: deal
+                    .sub_shares
+                    .iter()
+                    .find(|s| s.recipient == peer.index)
+                    .map(|s| s.ciphertext.clone())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        let response = client
+            .dkg_verify(DkgVerifyRequest {
+                ceremony_id: ceremony_id.to_string(),
+                dealers,
+            })
+            .await?
+            .into_inner();
+        echoes.push(response);
+    }
+    let complaints: usize = echoes.iter().map(|echo| echo.complaints.len()).sum();
+    println!("  -> {} complaints\n", complaints);
+
+    // step 4: everyone checks the echoes match, accused dealers answer,
+    // then everyone settles on the qualified set
+    println!("step 4: resolving complaints and publishing feldman commitments...");
+    let mut reveals = Vec::new();
+    for client in clients.iter_mut() {
+        let response = client
+            .dkg_respond(DkgRespondRequest {
+                ceremony_id: ceremony_id.to_string(),
+                echoes: echoes.clone(),
+            })
+            .await?
+            .into_inner();
+        reveals.extend(response.reveals);
+    }
+
+    let mut qualified_sets = BTreeSet::new();
+    let mut feldman = Vec::new();
+    for (client, peer) in clients.iter_mut().zip(&peers) {
+        let response = client
+            .dkg_commit(DkgCommitRequest {
+                ceremony_id: ceremony_id.to_string(),
+                reveals: reveals.clone(),
+            })
+            .await?
+            .into_inner();
+
+        if !response.commitments.is_empty() {
+            feldman.push(DealerCommitments {
+                dealer: peer.index,
+                commitments: response.commitments,
+            });
+        }
+        qualified_sets.insert(response.qualified);
+    }
+
+    if qualified_sets.len() != 1 {
+        println!("✗ error! nodes disagree on the qualified set");
+        std::process::exit(1);
+    }
+    println!("  -> qualified dealers: {:?}\n", qualified_sets.first().unwrap());
+
+    // step 5: everyone checks the feldman commitments, cheaters get reconstructed
+    println!("step 5: checking feldman commitments...");
+    let mut evidence = Vec::new();
+    for client in clients.iter_mut() {
+        let response = client
+            .dkg_finalize(DkgFinalizeRequest {
+                ceremony_id: ceremony_id.to_string(),
+                commitments: feldman.clone(),
+            })
+            .await?
+            .into_inner();
+        evidence.extend(response.reveals);
+    }
+
+    let accused: BTreeSet<u32> = evidence.iter().map(|e| e.dealer).collect();
+    let mut reconstruction = Vec::new();
+    if !accused.is_empty() {
+        println!("  -> reconstructing dealers {:?}", accused);
+        for client in clients.iter_mut() {
+            let response = client
+                .dkg_reconstruct(DkgReconstructRequest {
+                    ceremony_id: ceremony_id.to_string(),
+                    dealers: accused.iter().copied().collect(),
+                })
+                .await?
+                .into_inner();
+            reconstruction.extend(response.reveals);
+        }
+    }
+    println!();
+
+    // step 6: every node computes and stores its final share
+    println!("step 6: completing key generation...");
+    let mut group_keys = BTreeSet::new();
+    for client in clients.iter_mut() {
+        let result = client
+            .dkg_complete(DkgCompleteRequest {
+                ceremony_id: ceremony_id.to_string(),
+                evidence: evidence.clone(),
+                reveals: reconstruction.clone(),
+            })
+            .await?
+            .into_inner();
+        group_keys.insert(result.group_public_key);
+    }
+
+    if group_keys.len() != 1 {
+        println!("✗ error! nodes disagree on the group public key");
+        std::process::exit(1);
+    }
+
+    println!("group public key: {}", hex::encode(group_keys.first().unwrap()));
+    println!("✓ success! key generated without any party holding the secret");
+
+    Ok(())
+}
+
+/// threshold signing flow: a threshold of nodes sign with frost, the client aggregates
+/// the message is hashed with sha-256 and the digest is signed as a bip-340 message
+/// with a path, every node tweaks its share and the signature is for that child key
+async fn run_sign(message: &str, ceremony_id: &str, path: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let digest = Sha256::digest(message.as_bytes());
+    let path_indices = path.map(bip32::parse_path).transpose()?;
+
+    let mut session_bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut session_bytes);
+    let session_id = hex::encode(session_bytes);
+
+    // step 1: collect nonce commitments until we have a threshold of signers
+    println!("step 1: collecting nonce commitments for ceremony '{}'...", ceremony_id);
+    let mut signers: Vec<CustodianClient<Channel>> = Vec::new();
+    let mut commitments: Vec<FrostCommitment> = Vec::new();
+    let mut group_commitments: Vec<Vec<u8>> = Vec::new();
+    let mut chain_code = Vec::new();
+
+    for addr in NODE_ADDRS {
+        let mut client = match connect_to_node(addr).await {
+            Ok(client) => client,
+            Err(e) => {
+                println!("  -> {} unavailable: {}", addr, e);
+                continue;
+            }
+        };
+
+        let response = client
+            .frost_commit(FrostCommitRequest {
+                ceremony_id: ceremony_id.to_string(),
+                session_id: session_id.clone(),
+            })
+            .await?
+            .into_inner();
+
+        // every signer has to agree on the group commitments
+        if group_commitments.is_empty() {
+            group_commitments = response.group_commitments;
+            chain_code = response.chain_code;
+        } else if group_commitments != response.group_commitments || chain_code != response.chain_code {
+            println!("✗ error! {} holds different group commitments", addr);
+            std::process::exit(1);
+        }
+
+        let commitment = response.commitment.ok_or("node returned no commitment")?;
+        println!("  -> signer {} committed", commitment.index);
+        commitments.push(commitment);
+        signers.push(client);
+
+        if signers.len() == group_commitments.len() {
+            break;
+        }
+    }
+
+    let threshold = group_commitments.len();
+    if threshold == 0 || signers.len() < threshold {
+        println!("✗ error! need {} signers, only {} available", threshold, signers.len());
+        std::process::exit(1);
+    }
+    println!();
+
+    // step 2: every signer returns its signature share
+    println!("step 2: collecting signature shares from {} signers...", threshold);
+    let mut signature_shares = Vec::new();
+    for client in signers.iter_mut() {
+        let response = client
+            .frost_sign(FrostSignRequest {
+                ceremony_id: ceremony_id.to_string(),
+                session_id: session_id.clone(),
+                message: digest.to_vec(),
+                commitments: commitments.clone(),
+                derivation_path: path.unwrap_or_default().to_string(),
+            })
+            .await?
+            .into_inner();
+
+        println!("  -> got signature share from signer {}", response.index);
+        signature_shares.push(SignatureShare {
+            index: response.index,
+            z: bytes_to_scalar(&response.z).ok_or("malformed signature share")?,
+        });
+    }
+    println!();
+
+    // step 3: verify each share against its public share and aggregate
+    println!("step 3: aggregating signature...");
+    let group_commitments: Vec<ProjectivePoint> = group_commitments
+        .iter()
+        .map(|bytes| bytes_to_point(bytes))
+        .collect::<Option<Vec<_>>>()
+        .ok_or("malformed group commitment")?;
+
+    // a child key's commitments only differ from the root's in the constant term
+    let group_commitments = match &path_indices {
+        Some(indices) => {
+            let chain_code: [u8; 32] = chain_code
+                .try_into()
+                .map_err(|_| "ceremony has no chain code, run `client xpub init` first")?;
+            let (_, tweak) = ExtendedPublicKey::root(group_commitments[0], chain_code).derive_path(indices)?;
+            bip32::tweak_commitments(&group_commitments, &tweak)
+        }
+        None => group_commitments,
+    };
+    let group_public_key = group_commitments[0];
+
+    let signing_commitments = commitments
+        .iter()
+        .map(|c| {
+            Some(SigningCommitments {
+                index: c.index,
+                hiding: bytes_to_point(&c.hiding)?,
+                binding: bytes_to_point(&c.binding)?,
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or("malformed nonce commitment")?;
+    let public_shares: Vec<ProjectivePoint> = signature_shares
+        .iter()
+        .map(|s| evaluate_commitments(&group_commitments, &Scalar::from(s.index as u64)))
+        .collect();
+
+    let signature = frost::aggregate(
+        &group_public_key,
+        &digest,
+        &signing_commitments,
+        &signature_shares,
+        &public_shares,
+    )?;
+
+    println!("message sha-256:  {}", hex::encode(digest));
+    if let Some(path) = path {
+        println!("path:             {}", path);
+    }
+    println!("public key (x):   {}", hex::encode(frost::x_only(&group_public_key)));
+    println!("signature:        {}", hex::encode(signature));
+    println!("✓ success! bip-340 signature verifies against the group key");
+
+    Ok(())
+}
+
+/// threshold ecdsa flow: a threshold of nodes run the five signing rounds through the client,
+/// which checks every proof it relays and combines the signature shares
+/// a `0x`-prefixed 32-byte hex argument is signed as a hash, anything else is hashed with sha-256 first
+async fn run_sign_ecdsa(message: &str, ceremony_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let prehash: [u8; 32] = match message.strip_prefix("0x") {
+        Some(digest) => hex::decode(digest)?
+            .try_into()
+            .map_err(|_| "message hash must be 32 bytes")?,
+        None => Sha256::digest(message.as_bytes()).into(),
+    };
+
+    let mut session_bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut session_bytes);
+    let session_id = hex::encode(session_bytes);
+
+    // step 1: collect signer setups until we have a threshold of signers
+    println!("step 1: collecting signer setups for ceremony '{}'...", ceremony_id);
+    let mut signers: Vec<CustodianClient<Channel>> = Vec::new();
+    let mut setups = Vec::new();
+    let mut group_commitments: Vec<Vec<u8>> = Vec::new();
+
+    for addr in NODE_ADDRS {
+        let mut client = match connect_to_node(addr).await {
+            Ok(client) => client,
+            Err(e) => {
+                println!("  -> {} unavailable: {}", addr, e);
+                continue;
+            }
+        };
+
+        let response = client
+            .ecdsa_setup(EcdsaSetupRequest {
+                ceremony_id: ceremony_id.to_string(),
+                peers: Vec::new(),
+            })
+            .await?
+            .into_inner();
+
+        // every signer has to agree on the group commitments
+        if group_commitments.is_empty() {
+            group_commitments = response.group_commitments;
+        } else if group_commitments != response.group_commitments {
+            println!("✗ error! {} holds different group commitments", addr);
+            std::process::exit(1);
+        }
+
+        let setup = response.setup.ok_or("node returned no setup")?;
+        println!("  -> signer {} ready", setup.index);
+        setups.push(setup);
+        signers.push(client);
+
+        if signers.len() == group_commitments.len() {
+            break;
+        }
+    }
+
+    let threshold = group_commitments.len();
+    if threshold == 0 || signers.len() < threshold {
+        println!("✗ error! need {} signers, only {} available", threshold, signers.len());
+        std::process::exit(1);
+    }
+
+    // every signer proves its paillier modulus free of small factors to each of the others,
+    // against their ring-pedersen parameters
+    let announced = setups.clone();
+    for (client, setup) in signers.iter_mut().zip(setups.iter_mut()) {
+        let response = client
+            .ecdsa_setup(EcdsaSetupRequest {
+                ceremony_id: ceremony_id.to_string(),
+                peers: announced.clone(),
+            })
+            .await?
+            .into_inner();
+        *setup = response.setup.ok_or("node returned no setup")?;
+        println!("  -> signer {} proved its modulus to the others", setup.index);
+    }
+
+    let group_points: Vec<ProjectivePoint> = group_commitments
+        .iter()
+        .map(|bytes| bytes_to_point(bytes))
+        .collect::<Option<Vec<_>>>()
+        .ok_or("malformed group commitment")?;
+    let signer_setups = setups
+        .iter()
+        .map(convert::setup_from_proto)
+        .collect::<Result<Vec<_>, _>>()?;
+    let set = SignerSet::new(signer_setups, &group_points)?;
+    println!();
+
+    // step 2: encrypted nonces with range proofs
+    println!("step 2: round 1, encrypted nonces...");
+    let mut round1_proto = Vec::new();
+    for client in signers.iter_mut() {
+        let response = client
+            .ecdsa_round1(EcdsaRound1Request {
+                ceremony_id: ceremony_id.to_string(),
+                session_id: session_id.clone(),
+                setups: setups.clone(),
+            })
+            .await?
+            .into_inner();
+        println!("  -> signer {} sent its encrypted nonce", response.index);
+        round1_proto.push(response);
+    }
+    let round1 = round1_proto
+        .iter()
+        .map(convert::round1_from_proto)
+        .collect::<Result<Vec<_>, _>>()?;
+    set.verify_round1(&round1)?;
+    println!("  -> all range proofs verified\n");
+
+    // step 3: pairwise multiplicative-to-additive conversions
+    println!("step 3: round 2, mta exchanges...");
+    let mut mta_proto = Vec::new();
+    for client in signers.iter_mut() {
+        let response = client
+            .ecdsa_round2(EcdsaRound2Request {
+                ceremony_id: ceremony_id.to_string(),
+                session_id: session_id.clone(),
+                messages: round1_proto.clone(),
+            })
+            .await?
+            .into_inner();
+        mta_proto.extend(response.messages);
+    }
+    let mta = mta_proto
+        .iter()
+        .map(convert::mta_from_proto)
+        .collect::<Result<Vec<_>, _>>()?;
+    set.verify_mta(&round1, &mta)?;
+    println!("  -> {} mta responses verified\n", mta.len());
+
+    // step 4: delta shares and gamma openings, each node only gets the responses meant for it
+    println!("step 4: round 3, delta shares...");
+    let mut round3_proto = Vec::new();
+    for (client, setup) in signers.iter_mut().zip(&setups) {
+        let response = client
+            .ecdsa_round3(EcdsaRound3Request {
+                ceremony_id: ceremony_id.to_string(),
+                session_id: session_id.clone(),
+                messages: mta_proto.iter().filter(|m| m.to == setup.index).cloned().collect(),
+            })
+            .await?
+            .into_inner();
+        round3_proto.push(response);
+    }
+    let round3 = round3_proto
+        .iter()
+        .map(convert::round3_from_proto)
+        .collect::<Result<Vec<_>, _>>()?;
+    set.verify_round3(&round1, &round3)?;
+    println!("  -> gamma commitments opened\n");
+
+    // step 5: shares of the nonce point
+    println!("step 5: round 4, nonce point shares...");
+    let mut round4_proto = Vec::new();
+    for client in signers.iter_mut() {
+        let response = client
+            .ecdsa_round4(EcdsaRound4Request {
+                ceremony_id: ceremony_id.to_string(),
+                session_id: session_id.clone(),
+                messages: round3_proto.clone(),
+            })
+            .await?
+            .into_inner();
+        round4_proto.push(response);
+    }
+    let round4 = round4_proto
+        .iter()
+        .map(convert::round4_from_proto)
+        .collect::<Result<Vec<_>, _>>()?;
+    set.verify_round4(&round1, &round3, &round4)?;
+    println!("  -> nonce and sigma shares proven\n");
+
+    // step 6: signature shares, or the session secrets if somebody cheated
+    println!("step 6: round 5, signature shares...");
+    let mut signature_shares = Vec::new();
+    let mut reveals = Vec::new();
+    for client in signers.iter_mut() {
+        let response = client
+            .ecdsa_round5(EcdsaRound5Request {
+                ceremony_id: ceremony_id.to_string(),
+                session_id: session_id.clone(),
+                message_hash: prehash.to_vec(),
+                messages: round4_proto.clone(),
+            })
+            .await?
+            .into_inner();
+        match convert::round5_from_proto(&response)? {
+            Round5Output::Share(share) => {
+                println!("  -> got signature share from signer {}", share.index);
+                signature_shares.push(share);
+            }
+            Round5Output::Abort(reveal) => {
+                println!("  -> signer {} aborted and revealed its session", reveal.index);
+                reveals.push(reveal);
+            }
+        }
+    }
+    println!();
+
+    if !set.round4_consistent(&round4)? {
+        let culprit = set.identify_culprit(&round1, &mta, &round3, &round4, &reveals);
+        println!("✗ error! signing aborted: {}", culprit);
+        std::process::exit(1);
+    }
+
+    // step 7: check every share and combine
+    println!("step 7: combining signature...");
+    let signature = set.combine(&round3, &round4, &prehash, &signature_shares)?;
+
+    println!("message hash:     {}", hex::encode(prehash));
+    println!("public key:       {}", hex::encode(point_to_bytes(set.group_public_key())));
+    println!("r:                {}", hex::encode(signature.r.to_bytes()));
+    println!("s:                {}", hex::encode(signature.s.to_bytes()));
+    println!("v:                {}", signature.recovery_id);
+    println!("✓ success! ecdsa signature verifies and recovers the group key");
+
+    Ok(())
+}
+
+/// proactive refresh flow: every holder deals shares of zero to the others, then all of them
+/// switch to their refreshed shares together, moving the ceremony to the next epoch
+/// needs every holder online, a holder left behind would be stuck with a useless share
+async fn run_refresh(ceremony_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // step 1: every holder opens a refresh session and publishes an ephemeral key
+    println!("step 1: starting refresh of ceremony '{}'...", ceremony_id);
+    let mut clients: Vec<CustodianClient<Channel>> = Vec::new();
+    let mut peers = Vec::new();
+    let mut epochs = BTreeSet::new();
+
+    for addr in NODE_ADDRS {
+        let mut client = connect_to_node(addr).await?;
+        let response = client
+            .refresh_start(RefreshStartRequest {
+                ceremony_id: ceremony_id.to_string(),
+            })
+            .await?
+            .into_inner();
+        println!("  -> holder {} at epoch {}", response.index, response.epoch);
+
+        epochs.insert(response.epoch);
+        peers.push(PeerKey {
+            index: response.index,
+            encryption_key: response.encryption_key,
+            signature: response.key_signature,
+        });
+        clients.push(client);
+    }
+
+    if epochs.len() > 1 {
+        println!("✗ error! holders are at different epochs");
+        std::process::exit(1);
+    }
+    println!();
+
+    // step 2: every holder deals a polynomial with a zero constant term
+    println!("step 2: collecting refresh sub-shares...");
+    let mut deals = Vec::new();
+    for client in clients.iter_mut() {
+        let deal = client
+            .refresh_deal(RefreshDealRequest {
+                ceremony_id: ceremony_id.to_string(),
+                peers: peers.clone(),
+            })
+            .await?
+            .into_inner();
+        println!("  -> holder {} sent {} sub-shares", deal.dealer, deal.sub_shares.len());
+        deals.push(deal);
+    }
+    println!();
+
+    // step 3: every holder checks its sub-shares and stages its new share
+    println!("step 3: applying refresh...");
+    let mut staged_commitments: Vec<Vec<u8>> = Vec::new();
+    let mut next_epoch = 0;
+    for (client, peer) in clients.iter_mut().zip(&peers) {
+        let dealers = deals
+            .iter()
+            .filter(|deal| deal.dealer != peer.index)
+            .map(|deal| DealerMessage {
+                dealer: deal.dealer,
+                commitments: deal.commitments.clone(),
+                ciphertext: deal
+                    .sub_shares
+                    .iter()
+                    .find(|s| s.recipient == peer.index)
+                    .map(|s| s.ciphertext.clone())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        let response = client
+            .refresh_apply(RefreshApplyRequest {
+                ceremony_id: ceremony_id.to_string(),
+                dealers,
+            })
+            .await?
+            .into_inner();
+
+        // every holder must end up with the same group commitments
+        if staged_commitments.is_empty() {
+            staged_commitments = response.commitments;
+        } else if staged_commitments != response.commitments {
+            println!("✗ error! holder {} staged different commitments", response.index);
+            std::process::exit(1);
+        }
+        next_epoch = response.epoch;
+        println!("  -> holder {} staged its refreshed share", response.index);
+    }
+    println!();
+
+    // step 4: everyone switches over
+    println!("step 4: committing epoch {}...", next_epoch);
+    for client in clients.iter_mut() {
+        let response = client
+            .refresh_commit(RefreshCommitRequest {
+                ceremony_id: ceremony_id.to_string(),
+                epoch: next_epoch,
+            })
+            .await?
+            .into_inner();
+        println!("  -> {}", response.message);
+    }
+    println!();
+
+    println!("group public key: {}", hex::encode(staged_commitments.first().ok_or("no commitments")?));
+    println!("✓ success! shares refreshed, shares from earlier epochs are now useless");
+
+    Ok(())
+}
+
+/// resharing flow: the current holders deal their shares to a new committee made of the
+/// first `new_total` nodes of the pool, which ends up with a `new_threshold`-of-`new_total`
+/// sharing of the same key; holders outside the new committee delete their shares
+async fn run_reshare(new_threshold: u32, new_total: u32, ceremony_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if new_threshold < 2 || new_threshold > new_total || new_total as usize > NODE_POOL.len() {
+        println!(
+            "✗ error! need 2 <= threshold <= total <= {}, got {}-of-{}",
+            NODE_POOL.len(),
+            new_threshold,
+            new_total
+        );
+        std::process::exit(1);
+    }
+
+    // step 1: find the current holders and collect the new committee's encryption keys
+    println!("step 1: starting resharing of '{}' to {}-of-{}...", ceremony_id, new_threshold, new_total);
+    let mut participants: Vec<(CustodianClient<Channel>, u32)> = Vec::new();
+    let mut dealers: Vec<(usize, u32)> = Vec::new();
+    let mut recipients = Vec::new();
+    let mut old_commitments: Vec<Vec<u8>> = Vec::new();
+    let mut epoch = 0;
+
+    for (i, addr) in NODE_POOL.iter().enumerate() {
+        let new_index = if (i as u32) < new_total { i as u32 + 1 } else { 0 };
+        let mut client = match connect_to_node(addr).await {
+            Ok(client) => client,
+            Err(e) if new_index == 0 => {
+                println!("  -> {} unavailable: {}", addr, e);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let response = match client
+            .reshare_start(ReshareStartRequest {
+                ceremony_id: ceremony_id.to_string(),
+                new_index,
+            })
+            .await
+        {
+            Ok(response) => response.into_inner(),
+            // a node outside the new committee without a share has nothing to do
+            Err(_) if new_index == 0 => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        if response.old_index != 0 {
+            if old_commitments.is_empty() {
+                old_commitments = response.commitments;
+            } else if old_commitments != response.commitments {
+                println!("✗ error! {} holds different group commitments", addr);
+                std::process::exit(1);
+            }
+            epoch = epoch.max(response.epoch);
+            dealers.push((participants.len(), response.old_index));
+        }
+        if new_index != 0 {
+            recipients.push(PeerKey {
+                index: new_index,
+                encryption_key: response.encryption_key,
+                signature: response.key_signature,
+            });
+        }
+        println!("  -> {}: old holder {}, new holder {}", addr, response.old_index, new_index);
+        participants.push((client, new_index));
+    }
+
+    let old_threshold = old_commitments.len();
+    if old_threshold == 0 || dealers.len() < old_threshold {
+        println!("✗ error! need {} current holders, found {}", old_threshold, dealers.len());
+        std::process::exit(1);
+    }
+    let dealer_indices: Vec<u32> = dealers.iter().map(|(_, index)| *index).collect();
+    println!();
+
+    // step 2: every current holder deals its share to the new committee
+    println!("step 2: collecting sub-shares from {} current holders...", dealers.len());
+    let mut deals = Vec::new();
+    for (position, _) in &dealers {
+        let deal = participants[*position]
+            .0
+            .reshare_deal(ReshareDealRequest {
+                ceremony_id: ceremony_id.to_string(),
+                dealers: dealer_indices.clone(),
+                new_threshold,
+                recipients: recipients.clone(),
+            })
+            .await?
+            .into_inner();
+        println!("  -> holder {} sent {} sub-shares", deal.dealer, deal.sub_shares.len());
+        deals.push(deal);
+    }
+    println!();
+
+    // step 3: every new holder combines its sub-shares
+    println!("step 3: building the new shares...");
+    let mut new_commitments: Vec<Vec<u8>> = Vec::new();
+    for (client, new_index) in participants.iter_mut().filter(|(_, index)| *index != 0) {
+        let messages = deals
+            .iter()
+            .map(|deal| DealerMessage {
+                dealer: deal.dealer,
+                commitments: deal.commitments.clone(),
+                ciphertext: deal
+                    .sub_shares
+                    .iter()
+                    .find(|s| s.recipient == *new_index)
+                    .map(|s| s.ciphertext.clone())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        let response = client
+            .reshare_complete(ReshareCompleteRequest {
+                ceremony_id: ceremony_id.to_string(),
+                dealers: dealer_indices.clone(),
+                messages,
+                views: deals.iter().filter_map(|deal| deal.view.clone()).collect(),
+                new_threshold,
+                recipients: recipients.iter().map(|peer| peer.index).collect(),
+            })
+            .await?
+            .into_inner();
+
+        // every new holder must end up with the same commitments and the old group key
+        if new_commitments.is_empty() {
+            new_commitments = response.commitments;
+        } else if new_commitments != response.commitments {
+            println!("✗ error! holder {} staged different commitments", response.index);
+            std::process::exit(1);
+        }
+        println!("  -> holder {} staged its new share", response.index);
+    }
+    if new_commitments.first() != old_commitments.first() {
+        println!("✗ error! the new sharing has a different group key");
+        std::process::exit(1);
+    }
+    println!();
+
+    // step 4: new holders switch over, leaving holders delete their shares
+    println!("step 4: committing...");
+    for (client, _) in participants.iter_mut() {
+        let response = client
+            .reshare_commit(ReshareCommitRequest {
+                ceremony_id: ceremony_id.to_string(),
+                epoch: epoch + 1,
+            })
+            .await?
+            .into_inner();
+        println!("  -> {}", response.message);
+    }
+    println!();
+
+    println!("group public key: {}", hex::encode(&new_commitments[0]));
+    println!(
+        "✓ success! '{}' is now shared {}-of-{} under the same key",
+        ceremony_id, new_threshold, new_total
+    );
+
+    Ok(())
+}
+
+/// repair flow: the node at position `lost` of the pool lost its share (e.g. a wiped disk)
+/// a threshold of the remaining holders rebuild it there without reconstructing the secret;
+/// the client only relays encrypted pieces and never sees the share either
+async fn run_repair(lost: u32, ceremony_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if lost == 0 || lost as usize > NODE_POOL.len() {
+        println!("✗ error! lost index must be between 1 and {}", NODE_POOL.len());
+        std::process::exit(1);
+    }
+
+    // step 1: open a session on the node being repaired, then on holders until there are enough
+    println!("step 1: starting repair of share {} of '{}'...", lost, ceremony_id);
+    let mut recipient = connect_to_node(NODE_POOL[lost as usize - 1]).await?;
+    let recipient_start = recipient
+        .repair_start(RepairStartRequest {
+            ceremony_id: ceremony_id.to_string(),
+            lost_index: lost,
+        })
+        .await?
+        .into_inner();
+
+    let mut helpers: Vec<(CustodianClient<Channel>, u32)> = Vec::new();
+    let mut helper_keys = Vec::new();
+    let mut commitments: Vec<Vec<u8>> = Vec::new();
+    let mut epoch = 0;
+
+    for (i, addr) in NODE_POOL.iter().enumerate() {
+        if i as u32 + 1 == lost || (!commitments.is_empty() && helpers.len() >= commitments.len()) {
+            continue;
+        }
+        let Ok(mut client) = connect_to_node(addr).await else {
+            println!("  -> {} unavailable", addr);
+            continue;
+        };
+        let Ok(response) = client
+            .repair_start(RepairStartRequest {
+                ceremony_id: ceremony_id.to_string(),
+                lost_index: lost,
+            })
+            .await
+            .map(|response| response.into_inner())
+        else {
+            continue;
+        };
+        if response.index == 0 {
+            continue;
+        }
+
+        // every helper must hold a share of the same sharing
+        if commitments.is_empty() {
+            commitments = response.commitments;
+            epoch = response.epoch;
+        } else if commitments != response.commitments || epoch != response.epoch {
+            println!("✗ error! holder {} is on a different sharing", response.index);
+            std::process::exit(1);
+        }
+        println!("  -> holder {} will help", response.index);
+        helper_keys.push(PeerKey {
+            index: response.index,
+            encryption_key: response.encryption_key,
+            signature: response.key_signature,
+        });
+        helpers.push((client, response.index));
+    }
+
+    if commitments.is_empty() || helpers.len() < commitments.len() {
+        println!("✗ error! need {} holders to help, found {}", commitments.len(), helpers.len());
+        std::process::exit(1);
+    }
+    println!();
+
+    // step 2: every helper splits its term into pieces for the others
+    println!("step 2: collecting masked pieces from {} helpers...", helpers.len());
+    let mut pieces = Vec::new();
+    for (client, index) in helpers.iter_mut() {
+        let response = client
+            .repair_split(RepairSplitRequest {
+                ceremony_id: ceremony_id.to_string(),
+                helpers: helper_keys.clone(),
+            })
+            .await?
+            .into_inner();
+        println!("  -> helper {} sent {} pieces", index, response.pieces.len());
+        pieces.extend(response.pieces);
+    }
+    println!();
+
+    // step 3: every helper sums what it received and encrypts the sum to the lost node
+    println!("step 3: collecting sums...");
+    let mut sums = Vec::new();
+    for (client, index) in helpers.iter_mut() {
+        let sum = client
+            .repair_combine(RepairCombineRequest {
+                ceremony_id: ceremony_id.to_string(),
+                pieces: pieces.iter().filter(|piece| piece.to == *index).cloned().collect(),
+                recipient_key: recipient_start.encryption_key.clone(),
+                recipient_key_signature: recipient_start.key_signature.clone(),
+            })
+            .await?
+            .into_inner();
+        println!("  -> helper {} sent its sum", index);
+        sums.push(sum);
+    }
+    println!();
+
+    // step 4: the lost node rebuilds its share and checks it against the commitments
+    println!("step 4: rebuilding the share...");
+    let response = recipient
+        .repair_complete(RepairCompleteRequest {
+            ceremony_id: ceremony_id.to_string(),
+            sums,
+        })
+        .await?
+        .into_inner();
+    println!("  -> {}", response.message);
+    println!();
+
+    println!("group public key: {}", hex::encode(&commitments[0]));
+    println!("✓ success! share {} of '{}' repaired", lost, ceremony_id);
+
+    Ok(())
+}
+
+/// what a threshold of holders publish about a ceremony's key
+struct PublicShares {
+    /// the holders' connections with their (x, g^y)
+    holders: Vec<(CustodianClient<Channel>, Scalar, ProjectivePoint)>,
+    commitments: Vec<ProjectivePoint>,
+    chain_code: Option<[u8; 32]>,
+}
+
+/// asks holders for their public shares until there are a threshold of them, checking
+/// every one against the feldman commitments when the ceremony has them
+async fn collect_public_shares(ceremony_id: &str) -> Result<PublicShares, Box<dyn std::error::Error>> {
+    let mut holders = Vec::new();
+    let mut commitments: Vec<ProjectivePoint> = Vec::new();
+    let mut epochs = BTreeSet::new();
+    let mut chain_codes = BTreeSet::new();
+
+    for addr in NODE_POOL {
+        if !commitments.is_empty() && holders.len() >= commitments.len() {
+            break;
+        }
+        let Ok(mut client) = connect_to_node(addr).await else {
+            println!("  -> {} unavailable", addr);
+            continue;
+        };
+        let Ok(response) = client
+            .get_public_share(RetrieveRequest {
+                ceremony_id: ceremony_id.to_string(),
+            })
+            .await
+            .map(|response| response.into_inner())
+        else {
+            continue;
+        };
+
+        let x = bytes_to_scalar(&response.x).ok_or("malformed share index")?;
+        let point = bytes_to_point(&response.public_share).ok_or("malformed public share")?;
+        if commitments.is_empty() {
+            commitments = response
+                .commitments
+                .iter()
+                .map(|bytes| bytes_to_point(bytes))
+                .collect::<Option<Vec<_>>>()
+                .ok_or("malformed commitment point")?;
+        }
+
+        // with commitments, every public share can be checked on its own
+        if !commitments.is_empty() && evaluate_commitments(&commitments, &x) != point {
+            println!("  -> {} sent a public share that doesn't match the commitments, skipping", addr);
+            continue;
+        }
+        println!("  -> {}: {}", addr, hex::encode(pubkey::compressed_public_key(&point)));
+        holders.push((client, x, point));
+        epochs.insert(response.epoch);
+        chain_codes.insert(response.chain_code);
+    }
+
+    if holders.is_empty() || holders.len() < commitments.len() {
+        println!("✗ error! only {} holders of '{}' answered", holders.len(), ceremony_id);
+        std::process::exit(1);
+    }
+    if epochs.len() > 1 {
+        println!("✗ error! holders are at different epochs");
+        std::process::exit(1);
+    }
+    if chain_codes.len() > 1 {
+        println!("✗ error! holders disagree on the chain code");
+        std::process::exit(1);
+    }
+    let chain_code = chain_codes.into_iter().next().and_then(|bytes| bytes.try_into().ok());
+
+    Ok(PublicShares {
+        holders,
+        commitments,
+        chain_code,
+    })
+}
+
+/// combines the holders' public shares in the exponent and checks the result against
+/// the constant commitment when there is one
+fn group_public_key(shares: &PublicShares) -> Result<ProjectivePoint, Box<dyn std::error::Error>> {
+    let public_shares: Vec<(Scalar, ProjectivePoint)> =
+        shares.holders.iter().map(|(_, x, point)| (*x, *point)).collect();
+    let public_key = pubkey::combine_public_shares(&public_shares)?;
+    if shares.commitments.first().is_some_and(|constant| *constant != public_key) {
+        println!("✗ error! combined key doesn't match the published commitments");
+        std::process::exit(1);
+    }
+    Ok(public_key)
+}
+
+/// public key flow: collects the public points g^y of a threshold of holders and
+/// combines them in the exponent, no node ever sends its share
+async fn run_pubkey(ceremony_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // step 1: ask holders for their public shares until there are enough
+    println!("step 1: collecting public shares of '{}'...", ceremony_id);
+    let shares = collect_public_shares(ceremony_id).await?;
+    println!();
+
+    // step 2: lagrange interpolation in the exponent
+    println!("step 2: combining {} public shares...", shares.holders.len());
+    let public_key = group_public_key(&shares)?;
+    println!();
+
+    println!("public key:       {}", hex::encode(pubkey::compressed_public_key(&public_key)));
+    println!("ethereum address: {}", pubkey::ethereum_address(&public_key));
+    println!("bitcoin p2wpkh:   {}", pubkey::p2wpkh_address(&public_key));
+    println!("bitcoin p2tr:     {}", pubkey::p2tr_address(&public_key));
+
+    Ok(())
+}
+
+/// sets a fresh random chain code on every holder of a ceremony, turning its key into
+/// the root of a bip-32 tree
+async fn run_xpub_init(ceremony_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut chain_code = [0u8; 32];
+    OsRng.fill_bytes(&mut chain_code);
+
+    println!("step 1: setting a chain code on every holder of '{}'...", ceremony_id);
+    let mut holders = 0;
+    for addr in NODE_POOL {
+        let Ok(mut client) = connect_to_node(addr).await else {
+            println!("  -> {} unavailable", addr);
+            continue;
+        };
+        match client
+            .set_chain_code(ChainCodeRequest {
+                ceremony_id: ceremony_id.to_string(),
+                chain_code: chain_code.to_vec(),
+            })
+            .await
+        {
+            Ok(response) => {
+                println!("  -> {}: {}", addr, response.into_inner().message);
+                holders += 1;
+            }
+            Err(status) if status.code() == tonic::Code::NotFound => continue,
+            Err(status) => return Err(status.into()),
+        }
+    }
+    if holders == 0 {
+        println!("✗ error! no node holds a share of '{}'", ceremony_id);
+        std::process::exit(1);
+    }
+    println!();
+
+    run_xpub("m", ceremony_id).await
+}
+
+/// prints the xpub and addresses of a non-hardened path below a ceremony's key,
+/// derived from public shares only
+async fn run_xpub(path: &str, ceremony_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let path_indices = bip32::parse_path(path)?;
+
+    println!("collecting public shares of '{}'...", ceremony_id);
+    let shares = collect_public_shares(ceremony_id).await?;
+    let public_key = group_public_key(&shares)?;
+    let Some(chain_code) = shares.chain_code else {
+        println!("✗ error! '{}' has no chain code yet, run `client xpub init {}` first", ceremony_id, ceremony_id);
+        std::process::exit(1);
+    };
+    println!();
+
+    let (child, _) = ExtendedPublicKey::root(public_key, chain_code).derive_path(&path_indices)?;
+    println!("path:             {}", path);
+    println!("xpub:             {}", child.to_xpub());
+    println!("public key:       {}", hex::encode(pubkey::compressed_public_key(&child.public_key)));
+    println!("ethereum address: {}", pubkey::ethereum_address(&child.public_key));
+    println!("bitcoin p2wpkh:   {}", pubkey::p2wpkh_address(&child.public_key));
+    println!("bitcoin p2tr:     {}", pubkey::p2tr_address(&child.public_key));
+
+    Ok(())
+}
+
+/// the ecies context binding ciphertexts to the ceremony whose key they're encrypted to
+fn decryption_context(ceremony_id: &str) -> Vec<u8> {
+    format!("quorum/decrypt/{}", ceremony_id).into_bytes()
+}
+
+/// encryption flow: encrypts a message to the group key, which only a threshold of
+/// holders can decrypt together
+async fn run_encrypt(message: &str, ceremony_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    println!("step 1: deriving the group key of '{}'...", ceremony_id);
+    let public_key = group_public_key(&collect_public_shares(ceremony_id).await?)?;
+    println!("group public key: {}\n", hex::encode(pubkey::compressed_public_key(&public_key)));
+
+    println!("step 2: encrypting...");
+    let ciphertext = ecies::encrypt(&public_key, message.as_bytes(), &decryption_context(ceremony_id));
+    println!("ciphertext (hex): {}", hex::encode(ciphertext));
+
+    Ok(())
+}
+
+/// threshold decryption flow: a threshold of holders each return a proven partial
+/// decryption, the client combines them; the key is never reconstructed
+async fn run_decrypt(ciphertext_hex: &str, ceremony_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let ciphertext = hex::decode(ciphertext_hex)?;
+    let ephemeral = ecies::ephemeral_point(&ciphertext)?;
+
+    // step 1: find a threshold of holders and their public shares
+    println!("step 1: collecting public shares of '{}'...", ceremony_id);
+    let mut holders = collect_public_shares(ceremony_id).await?.holders;
+    println!();
+
+    // step 2: every holder decrypts with its share and proves it
+    println!("step 2: requesting partial decryptions...");
+    let mut partials = Vec::new();
+    let mut public_shares = Vec::new();
+    for (client, x, public_share) in holders.iter_mut() {
+        let response = client
+            .partial_decrypt(PartialDecryptRequest {
+                ceremony_id: ceremony_id.to_string(),
+                ephemeral_point: point_to_bytes(&ephemeral),
+            })
+            .await?
+            .into_inner();
+        let partial = convert::partial_decryption_from_proto(&response)?;
+
+        // the index drives the lagrange coefficient, so it has to be the share's x
+        if Scalar::from(partial.index as u64) != *x {
+            println!("✗ error! holder {} answered for the wrong index", partial.index);
+            std::process::exit(1);
+        }
+        println!("  -> holder {} sent a partial decryption", partial.index);
+        partials.push(partial);
+        public_shares.push(*public_share);
+    }
+    println!();
+
+    // step 3: check the proofs and combine
+    println!("step 3: checking proofs and combining {} partials...", partials.len());
+    let plaintext = decryption::decrypt_with_partials(
+        &ciphertext,
+        &decryption_context(ceremony_id),
+        &partials,
+        &public_shares,
+    )?;
+    println!("plaintext: {}", String::from_utf8_lossy(&plaintext));
+
+    Ok(())
+}
+
+/// the passphrase mnemonics are encrypted under, empty unless set in the environment
+fn mnemonic_passphrase() -> Vec<u8> {
+    env::var("QUORUM_MNEMONIC_PASSPHRASE").unwrap_or_default().into_bytes()
+}
+
+/// paper backup flow: retrieves every node's share of a ceremony and prints each one
+/// as a slip-39 mnemonic, to be written down by that node's recovery officer
+async fn run_mnemonic_export(ceremony_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    println!("exporting the shares of '{}' as slip-39 mnemonics...\n", ceremony_id);
+
+    let mut shares = Vec::new();
+    let mut threshold = 0;
+    for addr in NODE_POOL {
+        let Ok(mut client) = connect_to_node(addr).await else {
+            continue;
+        };
+        let Ok(response) = client
+            .retrieve_share(RetrieveRequest {
+                ceremony_id: ceremony_id.to_string(),
+            })
+            .await
+        else {
+            continue;
+        };
+        let data = response.into_inner();
+        let share = convert::share_from_proto(&data)?;
+        // a dealer split records its threshold, otherwise it is the number of commitments
+        let recorded = share.meta.as_ref().map(|meta| meta.threshold as usize);
+        threshold = threshold.max(recorded.unwrap_or(data.commitments.len()));
+        shares.push((share, data.epoch));
+    }
+    if shares.is_empty() {
+        println!("✗ error! no node hands out a share of '{}'", ceremony_id);
+        println!("  (shares from key generation never leave their nodes)");
+        std::process::exit(1);
+    }
+    if threshold < 2 {
+        println!("✗ error! the shares of '{}' record neither a threshold nor commitments", ceremony_id);
+        std::process::exit(1);
+    }
+
+    // dealer-split shares keep their own share-set id, the others get one derived from the
+    // ceremony id; either way the epoch goes into the mnemonic identifier, so mnemonics
+    // exported before and after a refresh can't be combined
+    let set_id: [u8; 16] = Sha256::digest(ceremony_id.as_bytes())[..16].try_into().unwrap();
+    let passphrase = mnemonic_passphrase();
+    let total = shares.len() as u32;
+    for (share, epoch) in shares {
+        let tagged = match share.meta {
+            Some(_) => share.clone(),
+            None => SecretShare::tagged(
+                share.x,
+                *share.y.expose_secret(),
+                ShareMetadata {
+                    threshold: threshold as u32,
+                    total,
+                    set_id,
+                    curve: Curve::Secp256k1,
+                    epoch,
+                    checksum: [0; 8],
+                },
+            ),
+        };
+        let index = u32::from_be_bytes(share.x.to_bytes()[28..].try_into().unwrap());
+        println!("share {} ({}-of-{}):", index, threshold, total);
+        println!("  {}\n", slip39::share_to_mnemonic(&tagged, &passphrase)?);
+    }
+
+    println!("✓ success! any {} of these mnemonics recover the key", threshold);
+    Ok(())
+}
+
+/// reads slip-39 share mnemonics from stdin, one per line, and recovers the key from them
+fn run_mnemonic_import() -> Result<(), Box<dyn std::error::Error>> {
+    println!("enter one mnemonic per line, end with an empty line or eof:");
+
+    let passphrase = mnemonic_passphrase();
+    let mut shares = Vec::new();
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            break;
+        }
+        let share = slip39::share_from_mnemonic(&line, &passphrase)?;
+        let threshold = share.meta.as_ref().map_or(0, |meta| meta.threshold);
+        println!("  -> read share {} (threshold {})", shares.len() + 1, threshold);
+        shares.push(share);
+    }
+    println!();
+
+    // mnemonics beyond the threshold are checked against the others, so a custodian
+    // handing over a well-formed mnemonic of the wrong share doesn't go unnoticed
+    let threshold = shares
+        .first()
+        .and_then(|share| share.meta.as_ref())
+        .map_or(2, |meta| meta.threshold as usize);
+    let secret = match recover_secret_checked(&shares, threshold) {
+        Err(FragmentError::InconsistentShares { disagreeing }) => {
+            for x in &disagreeing {
+                println!("✗ share at x = {} disagrees with the first {}", hex::encode(x.to_bytes()), threshold);
+            }
+            std::process::exit(1);
+        }
+        result => result?,
+    };
+    let public_key = ProjectivePoint::GENERATOR * secret.expose_secret();
+    println!("recovered (hex):  {}", hex::encode(secret.expose_secret().to_bytes()));
+    println!("public key:       {}", hex::encode(point_to_bytes(&public_key)));
+    println!("✓ success! recovered the key from {} mnemonics", shares.len());
+
+    Ok(())
+}
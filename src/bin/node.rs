@@ -2,9 +2,11 @@
 // run multiple instances on different ports to form a custodian network
 
 use std::env;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tonic::transport::Server;
 
+use quorum::network::{NodeIdentity, Roster};
 use quorum::{ShareStore, CustodianService, CustodianServer};
 
 #[tokio::main]
@@ -30,17 +32,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         50051 // default port
     }
 
+    // the value following a flag, e.g. `--index 2`
+    fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+        args.iter()
+            .position(|arg| arg == flag)
+            .and_then(|i| args.get(i + 1))
+            .map(String::as_str)
+    }
+
+    // the node's index in the roster, which is also the x coordinate of its shares.
+    // defaults to its position in the client's node pool, where port 50051 is node 1
+    let index: u32 = match flag_value(&args, "--index") {
+        Some(index) => index.parse()?,
+        None => u32::from(port.saturating_sub(50050)),
+    };
+    // the key directory holds this node's identity key and the roster of every node's
+    // public key; operators share the public keys out of band, never through the client
+    let key_dir = PathBuf::from(flag_value(&args, "--key-dir").unwrap_or("keys"));
+
     let addr = format!("0.0.0.0:{}", port).parse()?;
     let node_id = format!("node-{}", port);
 
     // creating the share store (thread-safe for concurrent requests)
     let store = Arc::new(ShareStore::new());
 
+    // loading the node identity, generated and published to the roster on first start
+    let identity = NodeIdentity::load_or_generate(&key_dir, index)?;
+    let roster = Roster::new(key_dir.clone());
+
     // creating the custodian service
-    let service = CustodianService::new(store, node_id.clone());
+    let service = CustodianService::new(store, node_id.clone(), identity, roster);
 
     println!("=== quorum custodian node ===");
-    println!("[{}] listening on {}", node_id, addr);
+    println!("[{}] listening on {} as node {} (keys in {})", node_id, addr, index, key_dir.display());
 
     // starting the grpc server
     Server::builder()
@@ -0,0 +1,222 @@
+// bip-32 non-hardened derivation on a threshold-shared key
+// a non-hardened child key is the parent key plus a public tweak, k_child = k + I_L with
+// I_L = HMAC-SHA512(chain code, parent public key || index). everything needed for the
+// tweak is public, so the group derives child public keys from the root public key and
+// chain code, and every custodian adds the same tweak to its share: since lagrange
+// coefficients sum to one, the tweaked shares are shares of the child private key.
+// hardened derivation hashes the private key itself and can't work without a ceremony
+
+use hmac::{Hmac, Mac};
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::PrimeField;
+use k256::sha2::{Digest, Sha256, Sha512};
+use k256::{ProjectivePoint, Scalar};
+use ripemd::Ripemd160;
+
+use crate::core::scheme::{FragmentError, SecretShare};
+
+/// child indices at or above this are hardened
+pub const HARDENED: u32 = 1 << 31;
+
+/// version bytes of a mainnet xpub
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+
+/// a public key with the chain code and position needed to derive its children
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtendedPublicKey {
+    pub public_key: ProjectivePoint,
+    pub chain_code: [u8; 32],
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: u32,
+}
+
+impl ExtendedPublicKey {
+    /// the root of a derivation tree, m
+    pub fn root(public_key: ProjectivePoint, chain_code: [u8; 32]) -> Self {
+        ExtendedPublicKey {
+            public_key,
+            chain_code,
+            depth: 0,
+            parent_fingerprint: [0; 4],
+            child_number: 0,
+        }
+    }
+
+    /// the first 4 bytes of hash160 of the compressed public key
+    pub fn fingerprint(&self) -> [u8; 4] {
+        let hash160 = Ripemd160::digest(Sha256::digest(self.public_key.to_bytes()));
+        hash160[..4].try_into().unwrap()
+    }
+
+    /// derives a non-hardened child, returning it with the tweak that was added to the key
+    pub fn derive_child(&self, index: u32) -> Result<(Self, Scalar), FragmentError> {
+        if index >= HARDENED {
+            return Err(FragmentError::DerivationFailed(format!(
+                "child {} is hardened and needs the private key",
+                index - HARDENED
+            )));
+        }
+
+        let mut mac = Hmac::<Sha512>::new_from_slice(&self.chain_code).expect("hmac accepts any key length");
+        mac.update(&self.public_key.to_bytes());
+        mac.update(&index.to_be_bytes());
+        let digest = mac.finalize().into_bytes();
+
+        // i_l >= n or a child at infinity make the index invalid, per bip-32
+        let tweak_bytes: [u8; 32] = digest[..32].try_into().unwrap();
+        let tweak = Option::<Scalar>::from(Scalar::from_repr(tweak_bytes.into()))
+            .ok_or_else(|| FragmentError::DerivationFailed(format!("child {} is invalid, skip it", index)))?;
+        let public_key = self.public_key + ProjectivePoint::GENERATOR * tweak;
+        if public_key == ProjectivePoint::IDENTITY {
+            return Err(FragmentError::DerivationFailed(format!("child {} is invalid, skip it", index)));
+        }
+
+        let child = ExtendedPublicKey {
+            public_key,
+            chain_code: digest[32..].try_into().unwrap(),
+            depth: self.depth.checked_add(1).ok_or_else(|| {
+                FragmentError::DerivationFailed("derivation path is deeper than 255".to_string())
+            })?,
+            parent_fingerprint: self.fingerprint(),
+            child_number: index,
+        };
+
+        Ok((child, tweak))
+    }
+
+    /// derives the key at a path below this one, returning it with the sum of the tweaks
+    /// along the way: the child private key is the parent's plus that sum
+    pub fn derive_path(&self, path: &[u32]) -> Result<(Self, Scalar), FragmentError> {
+        path.iter().try_fold((*self, Scalar::ZERO), |(key, tweak), index| {
+            let (child, step) = key.derive_child(*index)?;
+            Ok((child, tweak + step))
+        })
+    }
+
+    /// the base58check xpub serialization
+    pub fn to_xpub(&self) -> String {
+        let mut bytes = Vec::with_capacity(78);
+        bytes.extend_from_slice(&XPUB_VERSION);
+        bytes.push(self.depth);
+        bytes.extend_from_slice(&self.parent_fingerprint);
+        bytes.extend_from_slice(&self.child_number.to_be_bytes());
+        bytes.extend_from_slice(&self.chain_code);
+        bytes.extend_from_slice(&self.public_key.to_bytes());
+        bs58::encode(bytes).with_check().into_string()
+    }
+
+    /// parses an xpub produced by `to_xpub` or any bip-32 wallet
+    pub fn from_xpub(xpub: &str) -> Result<Self, FragmentError> {
+        let malformed = |reason: &str| FragmentError::DerivationFailed(format!("malformed xpub: {}", reason));
+
+        let bytes = bs58::decode(xpub)
+            .with_check(None)
+            .into_vec()
+            .map_err(|_| malformed("bad base58 or checksum"))?;
+        if bytes.len() != 78 || bytes[..4] != XPUB_VERSION {
+            return Err(malformed("not a mainnet xpub"));
+        }
+
+        let key: [u8; 33] = bytes[45..].try_into().unwrap();
+        let public_key =
+            Option::from(ProjectivePoint::from_bytes((&key).into())).ok_or_else(|| malformed("bad public key"))?;
+
+        Ok(ExtendedPublicKey {
+            public_key,
+            chain_code: bytes[13..45].try_into().unwrap(),
+            depth: bytes[4],
+            parent_fingerprint: bytes[5..9].try_into().unwrap(),
+            child_number: u32::from_be_bytes(bytes[9..13].try_into().unwrap()),
+        })
+    }
+}
+
+/// parses a path like "m/0/5" into child indices; hardened steps ("0'" or "0h") are
+/// rejected, since a threshold-shared key can only derive non-hardened children
+pub fn parse_path(path: &str) -> Result<Vec<u32>, FragmentError> {
+    let mut steps = path.split('/');
+    if steps.next() != Some("m") {
+        return Err(FragmentError::DerivationFailed(format!("path '{}' must start at m", path)));
+    }
+
+    steps
+        .map(|step| {
+            if step.ends_with('\'') || step.ends_with('h') || step.ends_with('H') {
+                return Err(FragmentError::DerivationFailed(format!(
+                    "hardened step '{}' needs the private key",
+                    step
+                )));
+            }
+            match step.parse::<u32>() {
+                Ok(index) if index < HARDENED => Ok(index),
+                _ => Err(FragmentError::DerivationFailed(format!("invalid path step '{}'", step))),
+            }
+        })
+        .collect()
+}
+
+/// a custodian's share of the derived key: the share plus the path's tweak
+pub fn tweak_share(share: &SecretShare, tweak: &Scalar) -> SecretShare {
+    SecretShare::new(share.x, *share.y.expose_secret() + tweak)
+}
+
+/// the feldman commitments of the derived key: only the constant term moves
+pub fn tweak_commitments(commitments: &[ProjectivePoint], tweak: &Scalar) -> Vec<ProjectivePoint> {
+    let mut tweaked = commitments.to_vec();
+    if let Some(constant) = tweaked.first_mut() {
+        *constant += ProjectivePoint::GENERATOR * tweak;
+    }
+    tweaked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::scheme::recover_secret;
+    use crate::core::vss::{split_secret_verifiable, verify_share};
+
+    #[test]
+    fn test_bip32_vector_1() {
+        // m/0h from the first bip-32 test vector, then its non-hardened child m/0h/1
+        let parent = ExtendedPublicKey::from_xpub(
+            "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+        )
+        .unwrap();
+        let (child, _) = parent.derive_child(1).unwrap();
+        assert_eq!(
+            child.to_xpub(),
+            "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ"
+        );
+
+        assert!(parent.derive_child(HARDENED).is_err());
+        assert!(ExtendedPublicKey::from_xpub("xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJc").is_err());
+    }
+
+    #[test]
+    fn test_tweaked_shares_hold_the_child_key() {
+        let secret = Scalar::from(123456789u64);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 3).unwrap();
+        let root = ExtendedPublicKey::root(commitments[0], [7u8; 32]);
+
+        let (child, tweak) = root.derive_path(&parse_path("m/0/42").unwrap()).unwrap();
+        assert_eq!(child.depth, 2);
+
+        let tweaked: Vec<SecretShare> = shares.iter().map(|share| tweak_share(share, &tweak)).collect();
+        let child_commitments = tweak_commitments(&commitments, &tweak);
+        assert!(tweaked.iter().all(|share| verify_share(share, &child_commitments)));
+
+        let child_secret = recover_secret(&tweaked[1..]).unwrap();
+        assert_eq!(ProjectivePoint::GENERATOR * child_secret.expose_secret(), child.public_key);
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(parse_path("m").unwrap(), Vec::<u32>::new());
+        assert_eq!(parse_path("m/0/7").unwrap(), vec![0, 7]);
+        assert!(parse_path("m/44'/0").is_err());
+        assert!(parse_path("m/0h").is_err());
+        assert!(parse_path("0/1").is_err());
+        assert!(parse_path("m/2147483648").is_err());
+    }
+}
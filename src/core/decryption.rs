@@ -0,0 +1,203 @@
+// threshold decryption with the shared key
+// a sender encrypts to the group public key with `ecies::encrypt`; decrypting needs the
+// ecdh point secret * R for the ciphertext's ephemeral point R. every custodian returns
+// its partial y_i * R with a chaum-pedersen proof that it used the same y_i as its public
+// share g^y_i, and the partials combine with lagrange coefficients into secret * R,
+// without the key ever being reconstructed
+
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::ops::Reduce;
+use k256::sha2::{Digest, Sha256};
+use k256::{ProjectivePoint, Scalar, U256};
+
+use crate::core::ecies;
+use crate::core::math::{lagrange_coefficient, random_scalar, validate_indices};
+use crate::core::scheme::{FragmentError, SecretShare};
+
+/// a chaum-pedersen proof that log_g(public) == log_R(partial)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DleqProof {
+    /// g^k
+    pub commitment_g: ProjectivePoint,
+    /// R^k
+    pub commitment_r: ProjectivePoint,
+    /// k + c * y
+    pub response: Scalar,
+}
+
+/// one custodian's share of a decryption: y_i * R, with its proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialDecryption {
+    pub index: u32,
+    pub point: ProjectivePoint,
+    pub proof: DleqProof,
+}
+
+fn dleq_challenge(
+    index: u32,
+    public_share: &ProjectivePoint,
+    ephemeral: &ProjectivePoint,
+    partial: &ProjectivePoint,
+    commitment_g: &ProjectivePoint,
+    commitment_r: &ProjectivePoint,
+) -> Scalar {
+    let digest = Sha256::new()
+        .chain_update(b"quorum/decrypt/dleq")
+        .chain_update(index.to_be_bytes())
+        .chain_update(public_share.to_bytes())
+        .chain_update(ephemeral.to_bytes())
+        .chain_update(partial.to_bytes())
+        .chain_update(commitment_g.to_bytes())
+        .chain_update(commitment_r.to_bytes())
+        .finalize();
+    <Scalar as Reduce<U256>>::reduce_bytes(&digest)
+}
+
+/// computes a custodian's partial decryption for the ephemeral point of a ciphertext
+/// index is the custodian's participant index, the x coordinate of its share
+pub fn partial_decrypt(index: u32, share: &SecretShare, ephemeral: &ProjectivePoint) -> PartialDecryption {
+    let public_share = ProjectivePoint::GENERATOR * *share.y.expose_secret();
+    let point = *ephemeral * *share.y.expose_secret();
+
+    let nonce = random_scalar();
+    let commitment_g = ProjectivePoint::GENERATOR * nonce;
+    let commitment_r = *ephemeral * nonce;
+    let challenge = dleq_challenge(index, &public_share, ephemeral, &point, &commitment_g, &commitment_r);
+
+    PartialDecryption {
+        index,
+        point,
+        proof: DleqProof {
+            commitment_g,
+            commitment_r,
+            response: nonce + challenge * *share.y.expose_secret(),
+        },
+    }
+}
+
+/// checks a partial decryption against the custodian's public share g^y_i
+pub fn verify_partial_decryption(
+    partial: &PartialDecryption,
+    public_share: &ProjectivePoint,
+    ephemeral: &ProjectivePoint,
+) -> bool {
+    let proof = &partial.proof;
+    let challenge = dleq_challenge(
+        partial.index,
+        public_share,
+        ephemeral,
+        &partial.point,
+        &proof.commitment_g,
+        &proof.commitment_r,
+    );
+
+    ProjectivePoint::GENERATOR * proof.response == proof.commitment_g + *public_share * challenge
+        && *ephemeral * proof.response == proof.commitment_r + partial.point * challenge
+}
+
+/// combines a threshold of partial decryptions into the ecdh point secret * R
+///
+/// - public_shares: g^y_i of every custodian, in the same order as `partials`
+///
+/// every partial is checked first, a bad one fails with DecryptionShareInvalid naming
+/// the custodian. with fewer than a threshold of partials the result is just wrong, so
+/// callers pass exactly the threshold the commitments call for
+pub fn combine_partial_decryptions(
+    ephemeral: &ProjectivePoint,
+    partials: &[PartialDecryption],
+    public_shares: &[ProjectivePoint],
+) -> Result<ProjectivePoint, FragmentError> {
+    if partials.is_empty() {
+        return Err(FragmentError::InsufficientShares);
+    }
+    if partials.len() != public_shares.len() {
+        return Err(FragmentError::MalformedShare(
+            "every partial decryption needs its custodian's public share".to_string(),
+        ));
+    }
+
+    let indices: Vec<Scalar> = partials.iter().map(|partial| Scalar::from(partial.index as u64)).collect();
+    validate_indices(&indices)?;
+    for (partial, public_share) in partials.iter().zip(public_shares) {
+        if !verify_partial_decryption(partial, public_share, ephemeral) {
+            return Err(FragmentError::DecryptionShareInvalid { index: partial.index });
+        }
+    }
+
+    partials
+        .iter()
+        .zip(&indices)
+        .try_fold(ProjectivePoint::IDENTITY, |acc, (partial, x)| {
+            Ok(acc + partial.point * lagrange_coefficient(x, &indices)?)
+        })
+}
+
+/// decrypts an `ecies::encrypt` ciphertext from a threshold of partial decryptions
+pub fn decrypt_with_partials(
+    ciphertext: &[u8],
+    context: &[u8],
+    partials: &[PartialDecryption],
+    public_shares: &[ProjectivePoint],
+) -> Result<Vec<u8>, FragmentError> {
+    let ephemeral = ecies::ephemeral_point(ciphertext)?;
+    let shared = combine_partial_decryptions(&ephemeral, partials, public_shares)?;
+    ecies::open(&shared, ciphertext, context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+    use crate::core::vss::{evaluate_commitments, split_secret_verifiable};
+
+    #[test]
+    fn test_threshold_decryption() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 3).unwrap();
+        let group_key = commitments[0];
+
+        let message = b"released only when two custodians agree";
+        let ciphertext = ecies::encrypt(&group_key, message, b"ceremony-001");
+        let ephemeral = ecies::ephemeral_point(&ciphertext).unwrap();
+
+        let partials: Vec<PartialDecryption> = [1u32, 3]
+            .iter()
+            .map(|index| partial_decrypt(*index, &shares[*index as usize - 1], &ephemeral))
+            .collect();
+        let public_shares: Vec<ProjectivePoint> = [1u32, 3]
+            .iter()
+            .map(|index| evaluate_commitments(&commitments, &Scalar::from(*index as u64)))
+            .collect();
+
+        let plaintext = decrypt_with_partials(&ciphertext, b"ceremony-001", &partials, &public_shares).unwrap();
+        assert_eq!(plaintext, message);
+
+        // one partial is not enough
+        assert!(decrypt_with_partials(&ciphertext, b"ceremony-001", &partials[..1], &public_shares[..1]).is_err());
+    }
+
+    #[test]
+    fn test_bad_partial_named() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 3).unwrap();
+        let ephemeral = ProjectivePoint::GENERATOR * Scalar::generate_vartime(&mut OsRng);
+
+        let good = partial_decrypt(1, &shares[0], &ephemeral);
+        let mut bad = partial_decrypt(2, &shares[1], &ephemeral);
+        bad.point += ProjectivePoint::GENERATOR;
+        let public_shares = vec![
+            evaluate_commitments(&commitments, &Scalar::from(1u64)),
+            evaluate_commitments(&commitments, &Scalar::from(2u64)),
+        ];
+
+        assert!(verify_partial_decryption(&good, &public_shares[0], &ephemeral));
+        assert!(matches!(
+            combine_partial_decryptions(&ephemeral, &[good, bad], &public_shares),
+            Err(FragmentError::DecryptionShareInvalid { index: 2 })
+        ));
+
+        // a valid partial under the wrong public share fails too
+        let swapped = vec![public_shares[1], public_shares[0]];
+        assert!(combine_partial_decryptions(&ephemeral, &[good, good], &swapped).is_err());
+    }
+}
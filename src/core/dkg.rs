@@ -0,0 +1,694 @@
+// dealerless distributed key generation (pedersen / gjkr dkg)
+// every custodian deals its own random polynomial, so the group key is never assembled anywhere
+//
+// gjkr assumes a broadcast channel, but here the coordinator relays every broadcast and
+// could show participants different commitments to provoke complaints, each of which
+// makes a dealer reveal a sub-share. so participants echo a digest of what they received
+// and abort before anyone answers a complaint unless every echo matches
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::sha2::{Digest, Sha256};
+use k256::{ProjectivePoint, Scalar};
+
+use crate::core::math::{evaluate_polynomial, interpolate_polynomial, random_scalar};
+use crate::core::scheme::{random_polynomial, FragmentError, SecretShare};
+use crate::core::vss::{
+    commit_polynomial, commit_polynomial_pedersen, verify_pedersen_share, verify_share,
+    PedersenShare,
+};
+
+/// everything a dealer sends out in the first round
+/// the commitments are broadcast, each sub-share goes privately to its recipient
+#[derive(Debug, Clone)]
+pub struct DealerPackage {
+    pub dealer: u32,
+    pub commitments: Vec<ProjectivePoint>,
+    pub sub_shares: BTreeMap<u32, PedersenShare>,
+}
+
+/// a complaint raised by `accuser` because `accused` dealt it an invalid sub-share
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Complaint {
+    pub accuser: u32,
+    pub accused: u32,
+}
+
+/// what a participant echoes after round 2: a digest of every dealer's commitments as it
+/// received them, and the dealers it complains about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Echo {
+    pub participant: u32,
+    pub digest: [u8; 32],
+    pub accused: Vec<u32>,
+}
+
+/// domain separation tag for the digest of the received commitments
+const ECHO_TAG: &[u8] = b"quorum/dkg/echo";
+
+/// a sub-share made public, either by a dealer answering a complaint,
+/// as evidence against a dealer, or to reconstruct a misbehaving dealer's polynomial
+#[derive(Debug, Clone)]
+pub struct RevealedShare {
+    pub dealer: u32,
+    pub recipient: u32,
+    pub share: Scalar,
+    pub blinding: Scalar,
+}
+
+impl RevealedShare {
+    fn to_pedersen(&self) -> PedersenShare {
+        PedersenShare {
+            share: SecretShare::new(Scalar::from(self.recipient as u64), self.share),
+            blinding: self.blinding,
+        }
+    }
+}
+
+/// the result of a successful key generation for one participant
+#[derive(Debug, Clone)]
+pub struct DkgOutput {
+    /// this participant's share of the group key
+    pub share: SecretShare,
+    /// the group public key, g^secret
+    pub group_public_key: ProjectivePoint,
+    /// feldman commitments to the (never assembled) group polynomial
+    pub commitments: Vec<ProjectivePoint>,
+    /// dealers whose contributions make up the key
+    pub qualified: Vec<u32>,
+}
+
+/// one custodian's state in the key generation protocol
+///
+/// the protocol runs in these steps, with all broadcasts relayed by the coordinator:
+/// 1. `deal`: publish pedersen commitments, send sub-shares privately
+/// 2. `receive_deal` + `echo`: verify sub-shares, accuse bad dealers and echo what was received
+/// 3. `check_echoes` + `respond`: abort unless everyone received the same commitments,
+///    then accused dealers reveal the disputed sub-shares
+/// 4. `resolve_complaints`: everyone agrees on the qualified set
+/// 5. `feldman_commitments` + `verify_feldman`: qualified dealers expose g^a_ik
+/// 6. `reveal_shares` + `complete`: reconstruct dealers whose feldman commitments
+///    were inconsistent, then sum the sub-shares into the final share
+pub struct DkgParticipant {
+    index: u32,
+    threshold: usize,
+    total: usize,
+    coefficients: Vec<Scalar>,
+    blinding_coefficients: Vec<Scalar>,
+    pedersen_commitments: BTreeMap<u32, Vec<ProjectivePoint>>,
+    received: BTreeMap<u32, PedersenShare>,
+    qualified: BTreeSet<u32>,
+    feldman_commitments: BTreeMap<u32, Vec<ProjectivePoint>>,
+    // the complaints every participant echoed, none until the echoes are checked
+    agreed_complaints: Option<Vec<Complaint>>,
+}
+
+impl DkgParticipant {
+    /// creates a participant with index (its x coordinate) in 1..=total
+    /// and samples its secret and blinding polynomials
+    pub fn new(index: u32, threshold: usize, total: usize) -> Result<Self, FragmentError> {
+        if threshold < 2 || threshold > total {
+            return Err(FragmentError::InvalidThreshold);
+        }
+        if index == 0 || index as usize > total {
+            return Err(FragmentError::KeyGenerationAborted(format!(
+                "participant index {} is outside 1..={}",
+                index, total
+            )));
+        }
+
+        let contribution = random_scalar();
+        let blinding = random_scalar();
+
+        Ok(Self {
+            index,
+            threshold,
+            total,
+            coefficients: random_polynomial(&contribution, threshold),
+            blinding_coefficients: random_polynomial(&blinding, threshold),
+            pedersen_commitments: BTreeMap::new(),
+            received: BTreeMap::new(),
+            qualified: BTreeSet::new(),
+            feldman_commitments: BTreeMap::new(),
+            agreed_complaints: None,
+        })
+    }
+
+    /// this participant's index, which is also the x coordinate of its final share
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// round 1: commits to both polynomials and evaluates them for every participant
+    /// the participant's own sub-share is recorded directly
+    pub fn deal(&mut self) -> DealerPackage {
+        let commitments =
+            commit_polynomial_pedersen(&self.coefficients, &self.blinding_coefficients);
+
+        let mut sub_shares = BTreeMap::new();
+        for recipient in 1..=self.total as u32 {
+            sub_shares.insert(recipient, self.sub_share_for(recipient));
+        }
+
+        self.pedersen_commitments
+            .insert(self.index, commitments.clone());
+        self.received
+            .insert(self.index, self.sub_share_for(self.index));
+
+        DealerPackage {
+            dealer: self.index,
+            commitments,
+            sub_shares,
+        }
+    }
+
+    /// round 2: records a dealer's broadcast commitments and the sub-share it sent us
+    /// a missing sub-share (e.g. one that failed to decrypt) is passed as none
+    pub fn receive_deal(
+        &mut self,
+        dealer: u32,
+        commitments: Vec<ProjectivePoint>,
+        sub_share: Option<PedersenShare>,
+    ) {
+        if dealer == 0 || dealer as usize > self.total || dealer == self.index {
+            return;
+        }
+
+        self.pedersen_commitments.insert(dealer, commitments);
+        match sub_share {
+            Some(share) => {
+                self.received.insert(dealer, share);
+            }
+            None => {
+                self.received.remove(&dealer);
+            }
+        }
+    }
+
+    /// round 2: lists the dealers whose sub-share does not match their commitments
+    pub fn complaints(&self) -> Vec<u32> {
+        (1..=self.total as u32)
+            .filter(|dealer| !self.holds_valid_share_from(*dealer))
+            .collect()
+    }
+
+    /// round 2: this participant's echo of the commitments it received, with its complaints
+    pub fn echo(&self) -> Echo {
+        Echo {
+            participant: self.index,
+            digest: self.received_digest(),
+            accused: self.complaints(),
+        }
+    }
+
+    /// round 3: checks that every participant echoed the commitments this one received
+    /// the complaints in the echoes are the only ones `respond` and `resolve_complaints`
+    /// act on, so a complaint nobody raised can't make a dealer reveal a sub-share
+    pub fn check_echoes(&mut self, echoes: &[Echo]) -> Result<Vec<Complaint>, FragmentError> {
+        let participants: BTreeSet<u32> = echoes.iter().map(|echo| echo.participant).collect();
+        if participants.len() != echoes.len() || participants != (1..=self.total as u32).collect() {
+            return Err(FragmentError::KeyGenerationAborted(
+                "expected one echo from every participant".to_string(),
+            ));
+        }
+
+        let digest = self.received_digest();
+        if let Some(echo) = echoes.iter().find(|echo| echo.digest != digest) {
+            return Err(FragmentError::KeyGenerationAborted(format!(
+                "participant {} received different commitments than participant {}",
+                echo.participant, self.index
+            )));
+        }
+
+        let complaints: Vec<Complaint> = echoes
+            .iter()
+            .flat_map(|echo| {
+                echo.accused.iter().map(|accused| Complaint {
+                    accuser: echo.participant,
+                    accused: *accused,
+                })
+            })
+            .collect();
+        self.agreed_complaints = Some(complaints.clone());
+        Ok(complaints)
+    }
+
+    /// round 3: answers complaints against this participant by revealing the disputed sub-shares
+    /// refuses until the echoes are checked, the complaints are the ones they carried
+    pub fn respond(&self) -> Result<Vec<RevealedShare>, FragmentError> {
+        Ok(self
+            .agreed()?
+            .iter()
+            .filter(|c| c.accused == self.index)
+            .map(|c| {
+                let sub_share = self.sub_share_for(c.accuser);
+                RevealedShare {
+                    dealer: self.index,
+                    recipient: c.accuser,
+                    share: *sub_share.share.y.expose_secret(),
+                    blinding: sub_share.blinding,
+                }
+            })
+            .collect())
+    }
+
+    /// round 4: decides which dealers are qualified given the echoed complaints and answers
+    ///
+    /// a dealer is disqualified if it has more than threshold - 1 complaints,
+    /// or if any complaint is left without a revealed sub-share that verifies.
+    /// the echoes showed every participant the same broadcasts, so they all compute the same set
+    pub fn resolve_complaints(&mut self, reveals: &[RevealedShare]) -> Result<Vec<u32>, FragmentError> {
+        let complaints = self.agreed()?.to_vec();
+        let mut qualified = BTreeSet::new();
+
+        for dealer in 1..=self.total as u32 {
+            let Some(commitments) = self.pedersen_commitments.get(&dealer) else {
+                continue;
+            };
+            if commitments.len() != self.threshold {
+                continue;
+            }
+
+            let accusers: BTreeSet<u32> = complaints
+                .iter()
+                .filter(|c| c.accused == dealer)
+                .map(|c| c.accuser)
+                .collect();
+            if accusers.len() > self.threshold - 1 {
+                continue;
+            }
+
+            // every accuser must have received a valid public answer
+            let mut answered = BTreeMap::new();
+            for reveal in reveals.iter().filter(|r| r.dealer == dealer) {
+                let pedersen = reveal.to_pedersen();
+                if accusers.contains(&reveal.recipient)
+                    && verify_pedersen_share(&pedersen, commitments)
+                {
+                    answered.insert(reveal.recipient, pedersen);
+                }
+            }
+            if answered.len() != accusers.len() {
+                continue;
+            }
+
+            // our own disputed sub-share is replaced by the publicly revealed one
+            if let Some(share) = answered.remove(&self.index) {
+                self.received.insert(dealer, share);
+            }
+
+            qualified.insert(dealer);
+        }
+
+        if qualified.is_empty() {
+            return Err(FragmentError::KeyGenerationAborted(
+                "no dealer qualified".to_string(),
+            ));
+        }
+
+        self.qualified = qualified;
+        Ok(self.qualified.iter().copied().collect())
+    }
+
+    /// round 5: this participant's feldman commitments g^a_ik
+    pub fn feldman_commitments(&self) -> Vec<ProjectivePoint> {
+        commit_polynomial(&self.coefficients)
+    }
+
+    /// round 5: checks every qualified dealer's feldman commitments against our sub-share
+    /// returns our sub-share as public evidence for every dealer that fails the check
+    pub fn verify_feldman(
+        &mut self,
+        commitments: BTreeMap<u32, Vec<ProjectivePoint>>,
+    ) -> Vec<RevealedShare> {
+        // our own entry is whatever was broadcast, so every participant judges the same data
+        self.feldman_commitments = commitments;
+        let own = self.feldman_commitments();
+        self.feldman_commitments.entry(self.index).or_insert(own);
+
+        let failing: Vec<u32> = self
+            .qualified
+            .iter()
+            .copied()
+            .filter(|dealer| !self.feldman_consistent(*dealer))
+            .collect();
+
+        self.reveal_shares(&failing)
+    }
+
+    /// round 6: reveals our sub-shares from the given dealers
+    /// used to publicly reconstruct the polynomial of a dealer that cheated in round 5
+    pub fn reveal_shares(&self, dealers: &[u32]) -> Vec<RevealedShare> {
+        dealers
+            .iter()
+            .filter_map(|dealer| {
+                self.received.get(dealer).map(|sub_share| RevealedShare {
+                    dealer: *dealer,
+                    recipient: self.index,
+                    share: *sub_share.share.y.expose_secret(),
+                    blinding: sub_share.blinding,
+                })
+            })
+            .collect()
+    }
+
+    /// dealers that are proven to have published inconsistent feldman commitments
+    /// evidence only counts if it verifies against the dealer's pedersen commitments
+    pub fn accused_in_extraction(&self, evidence: &[RevealedShare]) -> Vec<u32> {
+        let accused: BTreeSet<u32> = evidence
+            .iter()
+            .filter(|e| self.qualified.contains(&e.dealer))
+            .filter(|e| {
+                let pedersen = e.to_pedersen();
+                let pedersen_ok = self
+                    .pedersen_commitments
+                    .get(&e.dealer)
+                    .is_some_and(|c| verify_pedersen_share(&pedersen, c));
+                let feldman_ok = self
+                    .feldman_commitments
+                    .get(&e.dealer)
+                    .is_some_and(|c| c.len() == self.threshold && verify_share(&pedersen.share, c));
+                pedersen_ok && !feldman_ok
+            })
+            .map(|e| e.dealer)
+            .collect();
+
+        accused.into_iter().collect()
+    }
+
+    /// round 6: finishes key generation
+    ///
+    /// - evidence: sub-shares published in round 5 against inconsistent dealers
+    /// - reveals: sub-shares of the accused dealers, used to reconstruct their polynomials
+    pub fn complete(
+        &mut self,
+        evidence: &[RevealedShare],
+        reveals: &[RevealedShare],
+    ) -> Result<DkgOutput, FragmentError> {
+        // reconstructing the real commitments of every proven cheater
+        for dealer in self.accused_in_extraction(evidence) {
+            let commitments = &self.pedersen_commitments[&dealer];
+
+            let mut points = BTreeMap::new();
+            for reveal in reveals.iter().filter(|r| r.dealer == dealer) {
+                let pedersen = reveal.to_pedersen();
+                if verify_pedersen_share(&pedersen, commitments) {
+                    points.insert(reveal.recipient, *pedersen.share.y.expose_secret());
+                }
+            }
+
+            if points.len() < self.threshold {
+                return Err(FragmentError::KeyGenerationAborted(format!(
+                    "not enough valid shares to reconstruct dealer {}",
+                    dealer
+                )));
+            }
+
+            let points: Vec<(Scalar, Scalar)> = points
+                .into_iter()
+                .take(self.threshold)
+                .map(|(x, y)| (Scalar::from(x as u64), y))
+                .collect();
+            let coefficients = interpolate_polynomial(&points)?;
+            self.feldman_commitments
+                .insert(dealer, commit_polynomial(&coefficients));
+        }
+
+        // summing the qualified sub-shares and commitments
+        let mut y = Scalar::ZERO;
+        let mut commitments = vec![ProjectivePoint::IDENTITY; self.threshold];
+
+        for dealer in &self.qualified {
+            let sub_share = self.received.get(dealer).ok_or_else(|| {
+                FragmentError::KeyGenerationAborted(format!("missing sub-share from dealer {}", dealer))
+            })?;
+            let dealer_commitments = self.feldman_commitments.get(dealer).ok_or_else(|| {
+                FragmentError::KeyGenerationAborted(format!("missing commitments from dealer {}", dealer))
+            })?;
+
+            y += *sub_share.share.y.expose_secret();
+            for (acc, commitment) in commitments.iter_mut().zip(dealer_commitments) {
+                *acc += commitment;
+            }
+        }
+
+        let share = SecretShare::new(Scalar::from(self.index as u64), y);
+
+        // the final share has to be consistent with the group commitments
+        if !verify_share(&share, &commitments) {
+            return Err(FragmentError::KeyGenerationAborted(
+                "final share does not match the group commitments".to_string(),
+            ));
+        }
+
+        Ok(DkgOutput {
+            share,
+            group_public_key: commitments[0],
+            commitments,
+            qualified: self.qualified.iter().copied().collect(),
+        })
+    }
+
+    /// the complaints agreed on through the echoes
+    fn agreed(&self) -> Result<&[Complaint], FragmentError> {
+        self.agreed_complaints.as_deref().ok_or_else(|| {
+            FragmentError::KeyGenerationAborted("the echoes have not been checked yet".to_string())
+        })
+    }
+
+    /// hashes every dealer's pedersen commitments as this participant received them
+    fn received_digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new().chain_update(ECHO_TAG);
+        for dealer in 1..=self.total as u32 {
+            let commitments = self.pedersen_commitments.get(&dealer).map_or(&[][..], Vec::as_slice);
+            hasher.update(dealer.to_be_bytes());
+            hasher.update((commitments.len() as u32).to_be_bytes());
+            for commitment in commitments {
+                hasher.update(commitment.to_bytes());
+            }
+        }
+        hasher.finalize().into()
+    }
+
+    /// evaluates both of our polynomials at the recipient's index
+    fn sub_share_for(&self, recipient: u32) -> PedersenShare {
+        let x = Scalar::from(recipient as u64);
+        PedersenShare {
+            share: SecretShare::new(x, evaluate_polynomial(&self.coefficients, &x)),
+            blinding: evaluate_polynomial(&self.blinding_coefficients, &x),
+        }
+    }
+
+    /// checks the sub-share we hold from a dealer against its pedersen commitments
+    fn holds_valid_share_from(&self, dealer: u32) -> bool {
+        match (
+            self.received.get(&dealer),
+            self.pedersen_commitments.get(&dealer),
+        ) {
+            (Some(share), Some(commitments)) => {
+                commitments.len() == self.threshold
+                    && share.share.x == Scalar::from(self.index as u64)
+                    && verify_pedersen_share(share, commitments)
+            }
+            _ => false,
+        }
+    }
+
+    /// checks the sub-share we hold from a dealer against its feldman commitments
+    fn feldman_consistent(&self, dealer: u32) -> bool {
+        match (
+            self.received.get(&dealer),
+            self.feldman_commitments.get(&dealer),
+        ) {
+            (Some(share), Some(commitments)) => {
+                commitments.len() == self.threshold && verify_share(&share.share, commitments)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::scheme::recover_secret;
+
+    /// relays round 1 between all participants, letting the test tamper with sub-shares,
+    /// then relays everyone's echo and returns the complaints they agreed on
+    fn run_deal(
+        participants: &mut [DkgParticipant],
+        tamper: impl Fn(u32, u32, &mut PedersenShare),
+    ) -> Vec<Complaint> {
+        let packages: Vec<DealerPackage> = participants.iter_mut().map(|p| p.deal()).collect();
+
+        for participant in participants.iter_mut() {
+            for package in &packages {
+                let mut sub_share = package.sub_shares[&participant.index()].clone();
+                tamper(package.dealer, participant.index(), &mut sub_share);
+                participant.receive_deal(package.dealer, package.commitments.clone(), Some(sub_share));
+            }
+        }
+
+        let echoes: Vec<Echo> = participants.iter().map(|p| p.echo()).collect();
+        let mut agreed = Vec::new();
+        for participant in participants.iter_mut() {
+            agreed = participant.check_echoes(&echoes).unwrap();
+        }
+        agreed
+    }
+
+    /// relays the remaining rounds, letting the test replace feldman commitments
+    fn run_rest(
+        participants: &mut [DkgParticipant],
+        withhold_reveals: bool,
+        tamper_feldman: impl Fn(u32, &mut Vec<ProjectivePoint>),
+    ) -> Vec<DkgOutput> {
+        let reveals: Vec<RevealedShare> = if withhold_reveals {
+            Vec::new()
+        } else {
+            participants.iter().flat_map(|p| p.respond().unwrap()).collect()
+        };
+
+        for participant in participants.iter_mut() {
+            participant.resolve_complaints(&reveals).unwrap();
+        }
+
+        let mut feldman = BTreeMap::new();
+        for participant in participants.iter() {
+            let mut commitments = participant.feldman_commitments();
+            tamper_feldman(participant.index(), &mut commitments);
+            feldman.insert(participant.index(), commitments);
+        }
+
+        let evidence: Vec<RevealedShare> = participants
+            .iter_mut()
+            .flat_map(|p| p.verify_feldman(feldman.clone()))
+            .collect();
+
+        let accused = participants[0].accused_in_extraction(&evidence);
+        let reconstruction: Vec<RevealedShare> = participants
+            .iter()
+            .flat_map(|p| p.reveal_shares(&accused))
+            .collect();
+
+        participants
+            .iter_mut()
+            .map(|p| p.complete(&evidence, &reconstruction).unwrap())
+            .collect()
+    }
+
+    fn new_participants(threshold: usize, total: usize) -> Vec<DkgParticipant> {
+        (1..=total as u32)
+            .map(|i| DkgParticipant::new(i, threshold, total).unwrap())
+            .collect()
+    }
+
+    fn assert_consistent(outputs: &[DkgOutput], threshold: usize) {
+        let group_key = outputs[0].group_public_key;
+        assert!(outputs.iter().all(|o| o.group_public_key == group_key));
+
+        // any threshold shares recover the secret behind the group key
+        let shares: Vec<SecretShare> = outputs.iter().map(|o| o.share.clone()).collect();
+        let secret = recover_secret(&shares[..threshold]).unwrap();
+        assert_eq!(ProjectivePoint::GENERATOR * secret.expose_secret(), group_key);
+        let secret = recover_secret(&shares[shares.len() - threshold..]).unwrap();
+        assert_eq!(ProjectivePoint::GENERATOR * secret.expose_secret(), group_key);
+    }
+
+    #[test]
+    fn test_honest_dkg() {
+        let mut participants = new_participants(3, 5);
+        let complaints = run_deal(&mut participants, |_, _, _| {});
+        assert!(complaints.is_empty());
+
+        let outputs = run_rest(&mut participants, false, |_, _| {});
+        assert_eq!(outputs[0].qualified, vec![1, 2, 3, 4, 5]);
+        assert_consistent(&outputs, 3);
+    }
+
+    #[test]
+    fn test_answered_complaint_keeps_dealer() {
+        let mut participants = new_participants(2, 3);
+
+        // dealer 1 sends participant 3 a bad sub-share
+        let complaints = run_deal(&mut participants, |dealer, recipient, share| {
+            if dealer == 1 && recipient == 3 {
+                *share.share.y.expose_secret_mut() += Scalar::ONE;
+            }
+        });
+        assert_eq!(complaints, vec![Complaint { accuser: 3, accused: 1 }]);
+
+        // dealer 1 answers publicly, so it stays qualified
+        let outputs = run_rest(&mut participants, false, |_, _| {});
+        assert_eq!(outputs[0].qualified, vec![1, 2, 3]);
+        assert_consistent(&outputs, 2);
+    }
+
+    #[test]
+    fn test_unanswered_complaint_disqualifies_dealer() {
+        let mut participants = new_participants(2, 3);
+
+        let complaints = run_deal(&mut participants, |dealer, recipient, share| {
+            if dealer == 2 && recipient == 1 {
+                share.blinding += Scalar::ONE;
+            }
+        });
+        assert_eq!(complaints, vec![Complaint { accuser: 1, accused: 2 }]);
+
+        let outputs = run_rest(&mut participants, true, |_, _| {});
+        assert_eq!(outputs[0].qualified, vec![1, 3]);
+        assert_consistent(&outputs, 2);
+    }
+
+    #[test]
+    fn test_bad_feldman_commitments_are_reconstructed() {
+        let mut participants = new_participants(3, 4);
+        assert!(run_deal(&mut participants, |_, _, _| {}).is_empty());
+
+        // dealer 4 publishes commitments that don't match its polynomial
+        let outputs = run_rest(&mut participants, false, |dealer, commitments| {
+            if dealer == 4 {
+                commitments[1] += ProjectivePoint::GENERATOR;
+            }
+        });
+
+        // the dealer's contribution is kept, with commitments rebuilt from the revealed shares
+        assert_eq!(outputs[0].qualified, vec![1, 2, 3, 4]);
+        assert_consistent(&outputs, 3);
+    }
+
+    #[test]
+    fn test_equivocated_commitments_abort_before_reveals() {
+        let mut participants = new_participants(2, 3);
+        let packages: Vec<DealerPackage> = participants.iter_mut().map(|p| p.deal()).collect();
+
+        // the coordinator shows participant 3 other commitments for dealer 1, so that
+        // participant 3 complains and dealer 1 would reveal its sub-share
+        for participant in participants.iter_mut() {
+            for package in &packages {
+                let mut commitments = package.commitments.clone();
+                if package.dealer == 1 && participant.index() == 3 {
+                    commitments[1] += ProjectivePoint::GENERATOR;
+                }
+                let sub_share = package.sub_shares[&participant.index()].clone();
+                participant.receive_deal(package.dealer, commitments, Some(sub_share));
+            }
+        }
+        assert_eq!(participants[2].echo().accused, vec![1]);
+
+        // nobody answers a complaint before the echoes are checked, and they don't match
+        assert!(participants[0].respond().is_err());
+        let echoes: Vec<Echo> = participants.iter().map(|p| p.echo()).collect();
+        for participant in participants.iter_mut() {
+            assert!(matches!(
+                participant.check_echoes(&echoes),
+                Err(FragmentError::KeyGenerationAborted(_))
+            ));
+        }
+        assert!(participants[0].respond().is_err());
+
+        // a missing echo is no better than a mismatched one
+        assert!(participants[0].check_echoes(&echoes[..2]).is_err());
+    }
+}
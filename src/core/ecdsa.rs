@@ -0,0 +1,1322 @@
+// threshold ecdsa over secp256k1 (gg18 with identifiable abort)
+// signers turn their shamir shares into additive ones and multiply their secrets pairwise
+// with paillier mta, so neither the key nor the nonce is ever reconstructed
+//
+// every message goes through the coordinator, who checks all proofs as they pass;
+// if the nonce checks fail at the end, every signer reveals its session secrets
+// and the coordinator recomputes each contribution to find the signer that cheated
+//
+// signers prove their paillier moduli well formed before a session (cggmp21), and prove
+// their round 4 shares against what they committed to earlier (gg20), so no signer can
+// pick its shares after seeing the others'
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::point::AffineCoordinates;
+use k256::elliptic_curve::scalar::IsHigh;
+use k256::sha2::{Digest, Sha256};
+use k256::{ProjectivePoint, Scalar, U256};
+use num_bigint::BigUint;
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::core::math::{lagrange_coefficient, random_scalar};
+use crate::core::mta::{
+    biguint_to_scalar, mta_finish, mta_respond, scalar_to_biguint, verify_decryption, LogProof, RangeProof,
+    RespondentProof,
+};
+use crate::core::paillier::{
+    FactorProof, ModulusProof, PaillierPublicKey, PaillierSecretKey, RingPedersenParams, RingPedersenProof,
+    MIN_PAILLIER_BITS,
+};
+use crate::core::scheme::{FragmentError, SecretShare};
+use crate::core::vss::evaluate_commitments;
+
+/// a signer's long-term paillier key and ring-pedersen parameters
+/// generated once per node and reused across signing sessions
+pub struct SignerKeys {
+    paillier: PaillierSecretKey,
+    ring_pedersen: RingPedersenParams,
+    proof: RingPedersenProof,
+    modulus_proof: ModulusProof,
+}
+
+/// the public half of a signer's keys, shared with the other signers before a session
+///
+/// the paillier modulus comes with a proof that it is a product of two blum primes and,
+/// for every other signer, a proof against that signer's ring-pedersen parameters that
+/// neither prime is small
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerSetup {
+    pub index: u32,
+    pub paillier: PaillierPublicKey,
+    pub ring_pedersen: RingPedersenParams,
+    pub proof: RingPedersenProof,
+    pub modulus_proof: ModulusProof,
+    /// no-small-factor proofs keyed by the signer they are made for
+    pub factor_proofs: BTreeMap<u32, FactorProof>,
+}
+
+/// round 1: the encrypted nonce share k_i, with a range proof for every other signer,
+/// and a hash commitment to Gamma_i = g^gamma_i
+#[derive(Debug, Clone)]
+pub struct Round1Message {
+    pub index: u32,
+    pub k_ciphertext: BigUint,
+    pub gamma_commitment: [u8; 32],
+    /// range proofs keyed by the signer they are made for
+    pub range_proofs: BTreeMap<u32, RangeProof>,
+}
+
+/// round 2: bob's two mta responses to alice's encrypted k
+/// one multiplies by gamma, the other by bob's additive key share w (tied to W = g^w)
+#[derive(Debug, Clone)]
+pub struct MtaMessage {
+    pub from: u32,
+    pub to: u32,
+    pub gamma_ciphertext: BigUint,
+    pub gamma_proof: RespondentProof,
+    pub w_ciphertext: BigUint,
+    pub w_proof: RespondentProof,
+}
+
+/// round 3: the signer's share of delta = k * gamma, the opening of its gamma commitment
+/// and a pedersen commitment T_i = sigma_i * g + l_i * h that round 4 is checked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Round3Message {
+    pub index: u32,
+    pub delta: Scalar,
+    pub gamma_point: ProjectivePoint,
+    pub gamma_blinding: [u8; 32],
+    pub gamma_proof: SchnorrProof,
+    pub sigma_commitment: ProjectivePoint,
+}
+
+/// a schnorr proof of knowledge of the discrete log of a point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchnorrProof {
+    pub commitment: ProjectivePoint,
+    pub response: Scalar,
+}
+
+/// round 4: k_i * R and sigma_i * R, which have to sum to g and the group key
+/// k_i is proven to be the nonce encrypted in round 1, with a proof for every other signer,
+/// and sigma_i the value committed to in round 3
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Round4Message {
+    pub index: u32,
+    pub nonce_share: ProjectivePoint,
+    pub sigma_share: ProjectivePoint,
+    /// log proofs keyed by the signer they are made for
+    pub nonce_proofs: BTreeMap<u32, LogProof>,
+    pub sigma_proof: SigmaProof,
+}
+
+/// proof that S = sigma * R and T = sigma * g + l * h for the same sigma
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigmaProof {
+    /// a * R
+    pub point_commitment: ProjectivePoint,
+    /// a * g + b * h
+    pub commitment: ProjectivePoint,
+    pub response: Scalar,
+    pub blinding_response: Scalar,
+}
+
+/// a signer's contribution s_i to the final signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignatureShare {
+    pub index: u32,
+    pub s: Scalar,
+}
+
+/// the result of round 5: a signature share, or the session secrets if the checks failed
+#[derive(Debug, Clone)]
+pub enum Round5Output {
+    Share(SignatureShare),
+    Abort(AuditReveal),
+}
+
+/// everything a signer used in an aborted session, so the coordinator can replay it
+#[derive(Debug, Clone)]
+pub struct AuditReveal {
+    pub index: u32,
+    pub k: Scalar,
+    pub k_randomness: BigUint,
+    pub gamma: Scalar,
+    pub decryptions: Vec<Decryption>,
+}
+
+/// the decryption of the mta responses a signer received from one peer
+/// the paillier randomness lets anyone check the plaintexts against the ciphertexts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decryption {
+    pub from: u32,
+    pub gamma_plaintext: BigUint,
+    pub gamma_randomness: BigUint,
+    pub w_plaintext: BigUint,
+    pub w_randomness: BigUint,
+}
+
+/// a low-s ecdsa signature with its recovery id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoverableSignature {
+    pub r: Scalar,
+    pub s: Scalar,
+    pub recovery_id: u8,
+}
+
+/// the public view of a signing session: who signs, their keys and their additive public shares
+#[derive(Debug, Clone)]
+pub struct SignerSet {
+    setups: BTreeMap<u32, SignerSetup>,
+    // W_j = lambda_j * g^x_j, the public side of each signer's additive key share
+    public_shares: BTreeMap<u32, ProjectivePoint>,
+    group_public_key: ProjectivePoint,
+}
+
+/// one signer's state across the five rounds
+pub struct EcdsaSigner {
+    index: u32,
+    keys: Arc<SignerKeys>,
+    set: SignerSet,
+    // lambda_i * x_i, this signer's additive share of the key
+    w: Scalar,
+    k: Scalar,
+    gamma: Scalar,
+    k_ciphertext: BigUint,
+    k_randomness: BigUint,
+    gamma_blinding: [u8; 32],
+    // l_i, the blinding of the round 3 commitment to sigma_i
+    sigma_blinding: Scalar,
+    round1: Option<BTreeMap<u32, Round1Message>>,
+    round3: Option<Vec<Round3Message>>,
+    // bob's shares of the mta products, keyed by alice
+    betas: BTreeMap<u32, (Scalar, Scalar)>,
+    decryptions: Vec<Decryption>,
+    sigma: Option<Scalar>,
+    nonce_point: Option<ProjectivePoint>,
+}
+
+impl SignerKeys {
+    /// generates a paillier key and ring-pedersen parameters with moduli of the given size
+    pub fn generate(bits: u64) -> Self {
+        let paillier = PaillierSecretKey::generate(bits);
+        let (ring_pedersen, proof) = RingPedersenParams::generate(bits);
+        let modulus_proof = paillier.prove_modulus();
+        Self {
+            paillier,
+            ring_pedersen,
+            proof,
+            modulus_proof,
+        }
+    }
+
+    /// the public setup this node announces when signing as `index`, with a no-small-factor
+    /// proof for every peer; the proofs are made against the peers' ring-pedersen parameters,
+    /// so they are checked first, and peers can be empty to announce the parameters themselves
+    pub fn setup(&self, index: u32, peers: &[SignerSetup]) -> Result<SignerSetup, FragmentError> {
+        let mut factor_proofs = BTreeMap::new();
+        for peer in peers.iter().filter(|peer| peer.index != index) {
+            if peer.ring_pedersen.n_tilde.bits() < MIN_PAILLIER_BITS || !peer.ring_pedersen.verify(&peer.proof) {
+                return Err(FragmentError::SignerMisbehaved {
+                    index: peer.index,
+                    reason: "invalid ring-pedersen parameters".to_string(),
+                });
+            }
+            factor_proofs.insert(peer.index, self.paillier.prove_factors(&peer.ring_pedersen));
+        }
+
+        Ok(SignerSetup {
+            index,
+            paillier: self.paillier.public_key().clone(),
+            ring_pedersen: self.ring_pedersen.clone(),
+            proof: self.proof.clone(),
+            modulus_proof: self.modulus_proof.clone(),
+            factor_proofs,
+        })
+    }
+}
+
+impl SignerSetup {
+    /// checks the modulus sizes, the ring-pedersen proof and the modulus proof
+    /// the factor proofs need the other signers' parameters and are checked by the signer set
+    pub fn verify(&self) -> bool {
+        self.paillier.n.bits() >= MIN_PAILLIER_BITS
+            && self.ring_pedersen.n_tilde.bits() >= MIN_PAILLIER_BITS
+            && self.ring_pedersen.verify(&self.proof)
+            && self.modulus_proof.verify(&self.paillier.n)
+    }
+}
+
+impl RecoverableSignature {
+    /// the 64-byte r || s encoding
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.r.to_bytes());
+        bytes[32..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+}
+
+impl SignerSet {
+    /// builds the session view from every signer's setup and the group's feldman commitments
+    /// needs at least as many signers as the threshold (the number of commitments)
+    pub fn new(setups: Vec<SignerSetup>, group_commitments: &[ProjectivePoint]) -> Result<Self, FragmentError> {
+        if group_commitments.is_empty() || setups.len() < group_commitments.len() {
+            return Err(FragmentError::InsufficientShares);
+        }
+
+        let mut by_index = BTreeMap::new();
+        for setup in setups {
+            if setup.index == 0 {
+                return Err(FragmentError::SigningAborted("signer index 0 is not allowed".to_string()));
+            }
+            if !setup.verify() {
+                return Err(FragmentError::SignerMisbehaved {
+                    index: setup.index,
+                    reason: "invalid paillier or ring-pedersen setup".to_string(),
+                });
+            }
+            if by_index.insert(setup.index, setup).is_some() {
+                return Err(FragmentError::SigningAborted("duplicate signer index".to_string()));
+            }
+        }
+
+        // every modulus has to be proven free of small factors to every other signer
+        for (index, setup) in &by_index {
+            for (verifier, peer) in by_index.iter().filter(|(j, _)| *j != index) {
+                let valid = setup
+                    .factor_proofs
+                    .get(verifier)
+                    .is_some_and(|proof| proof.verify(&setup.paillier.n, &peer.ring_pedersen));
+                if !valid {
+                    return Err(FragmentError::SignerMisbehaved {
+                        index: *index,
+                        reason: format!("missing or invalid no-small-factor proof for signer {}", verifier),
+                    });
+                }
+            }
+        }
+
+        let xs: Vec<Scalar> = by_index.keys().map(|i| Scalar::from(*i as u64)).collect();
+        let public_shares = xs
+            .iter()
+            .zip(by_index.keys())
+            .map(|(x, i)| Ok((*i, evaluate_commitments(group_commitments, x) * lagrange_coefficient(x, &xs)?)))
+            .collect::<Result<_, FragmentError>>()?;
+
+        Ok(Self {
+            setups: by_index,
+            public_shares,
+            group_public_key: group_commitments[0],
+        })
+    }
+
+    pub fn group_public_key(&self) -> &ProjectivePoint {
+        &self.group_public_key
+    }
+
+    /// the signer indices in ascending order
+    pub fn indices(&self) -> Vec<u32> {
+        self.setups.keys().copied().collect()
+    }
+
+    /// checks every round 1 message and all of its range proofs
+    pub fn verify_round1(&self, messages: &[Round1Message]) -> Result<(), FragmentError> {
+        let round1 = self.collect(messages, |m| m.index, "1")?;
+        for (index, message) in round1 {
+            for verifier in self.setups.keys().filter(|j| **j != index) {
+                self.verify_range_proof(message, *verifier)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// checks that there is exactly one mta message per ordered pair of signers, with valid proofs
+    pub fn verify_mta(&self, round1: &[Round1Message], messages: &[MtaMessage]) -> Result<(), FragmentError> {
+        let round1 = self.collect(round1, |m| m.index, "1")?;
+        for alice in self.setups.keys() {
+            let received: Vec<MtaMessage> = messages.iter().filter(|m| m.to == *alice).cloned().collect();
+            let received = self.collect_peers(&received, *alice, |m| m.from, "2")?;
+            for message in received.values() {
+                self.verify_mta_message(round1[alice], message)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// checks every gamma decommitment and proof of knowledge
+    pub fn verify_round3(&self, round1: &[Round1Message], messages: &[Round3Message]) -> Result<(), FragmentError> {
+        let round1 = self.collect(round1, |m| m.index, "1")?;
+        let round3 = self.collect(messages, |m| m.index, "3")?;
+        for (index, message) in round3 {
+            self.verify_gamma_opening(&round1[&index].gamma_commitment, message)?;
+        }
+        Ok(())
+    }
+
+    /// the nonce point R = delta^-1 * Gamma and its x coordinate r
+    pub fn nonce_point(&self, round3: &[Round3Message]) -> Result<(ProjectivePoint, Scalar), FragmentError> {
+        let round3 = self.collect(round3, |m| m.index, "3")?;
+        let delta = round3.values().fold(Scalar::ZERO, |acc, m| acc + m.delta);
+        let gamma = round3
+            .values()
+            .fold(ProjectivePoint::IDENTITY, |acc, m| acc + m.gamma_point);
+
+        let delta_inv = Option::<Scalar>::from(delta.invert())
+            .ok_or_else(|| FragmentError::SigningAborted("delta is zero".to_string()))?;
+        let point = gamma * delta_inv;
+        if point == ProjectivePoint::IDENTITY {
+            return Err(FragmentError::SigningAborted("nonce point is the identity".to_string()));
+        }
+
+        Ok((point, x_coordinate(&point)))
+    }
+
+    /// checks every round 4 share against what its signer committed to earlier: k_i * R
+    /// against the nonce encrypted in round 1, sigma_i * R against the round 3 commitment
+    pub fn verify_round4(
+        &self,
+        round1: &[Round1Message],
+        round3: &[Round3Message],
+        round4: &[Round4Message],
+    ) -> Result<(), FragmentError> {
+        self.check_round4(round1, round3, round4, None)
+    }
+
+    /// like `verify_round4`, but only checks the nonce proofs made for `verifier` if given
+    fn check_round4(
+        &self,
+        round1: &[Round1Message],
+        round3: &[Round3Message],
+        round4: &[Round4Message],
+        verifier: Option<u32>,
+    ) -> Result<(), FragmentError> {
+        let (point, _) = self.nonce_point(round3)?;
+        let round1 = self.collect(round1, |m| m.index, "1")?;
+        let round3 = self.collect(round3, |m| m.index, "3")?;
+        let round4 = self.collect(round4, |m| m.index, "4")?;
+        let blame = |index: u32, reason: String| FragmentError::SignerMisbehaved { index, reason };
+
+        for (index, message) in round4 {
+            let setup = &self.setups[&index];
+            let verifiers = self
+                .setups
+                .iter()
+                .filter(|(j, _)| **j != index && verifier.is_none_or(|v| v == **j));
+            for (j, peer) in verifiers {
+                let valid = message.nonce_proofs.get(j).is_some_and(|proof| {
+                    proof.verify(
+                        &setup.paillier,
+                        &peer.ring_pedersen,
+                        &round1[&index].k_ciphertext,
+                        &point,
+                        &message.nonce_share,
+                    )
+                });
+                if !valid {
+                    return Err(blame(index, format!("invalid nonce share proof for signer {}", j)));
+                }
+            }
+
+            let sigma_commitment = &round3[&index].sigma_commitment;
+            if !verify_sigma(index, &point, &message.sigma_share, sigma_commitment, &message.sigma_proof) {
+                return Err(blame(index, "sigma share does not match its commitment".to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// checks that the round 4 shares sum to g and to the group key
+    /// if not, somebody cheated earlier and the session has to be audited
+    pub fn round4_consistent(&self, round4: &[Round4Message]) -> Result<bool, FragmentError> {
+        let round4 = self.collect(round4, |m| m.index, "4")?;
+        let nonce_sum = round4
+            .values()
+            .fold(ProjectivePoint::IDENTITY, |acc, m| acc + m.nonce_share);
+        let sigma_sum = round4
+            .values()
+            .fold(ProjectivePoint::IDENTITY, |acc, m| acc + m.sigma_share);
+
+        Ok(nonce_sum == ProjectivePoint::GENERATOR && sigma_sum == self.group_public_key)
+    }
+
+    /// checks every signature share and combines them into a recoverable low-s signature
+    ///
+    /// a share is valid when s_i * R = m * (k_i * R) + r * (sigma_i * R),
+    /// so a bad share names its signer without an audit
+    pub fn combine(
+        &self,
+        round3: &[Round3Message],
+        round4: &[Round4Message],
+        prehash: &[u8; 32],
+        shares: &[SignatureShare],
+    ) -> Result<RecoverableSignature, FragmentError> {
+        let (point, r) = self.nonce_point(round3)?;
+        let m = message_scalar(prehash);
+        let round4 = self.collect(round4, |m| m.index, "4")?;
+        let shares = self.collect(shares, |s| s.index, "5")?;
+
+        let mut s = Scalar::ZERO;
+        for (index, share) in shares {
+            let expected = round4[&index].nonce_share * m + round4[&index].sigma_share * r;
+            if point * share.s != expected {
+                return Err(FragmentError::SignerMisbehaved {
+                    index,
+                    reason: "invalid signature share".to_string(),
+                });
+            }
+            s += share.s;
+        }
+
+        let affine = point.to_affine();
+        let mut y_odd = bool::from(affine.y_is_odd());
+        if bool::from(s.is_high()) {
+            s = -s;
+            y_odd = !y_odd;
+        }
+        // r was reduced, which only happens when the x coordinate is at least the group order
+        let x_reduced = r.to_bytes() != affine.x();
+        let recovery_id = RecoveryId::new(y_odd, x_reduced);
+
+        let signature = Signature::from_scalars(r, s)
+            .map_err(|_| FragmentError::SigningAborted("signature is zero".to_string()))?;
+        let verifying_key = VerifyingKey::from_affine(self.group_public_key.to_affine())
+            .map_err(|_| FragmentError::SigningAborted("invalid group public key".to_string()))?;
+        let recovered = VerifyingKey::recover_from_prehash(prehash, &signature, recovery_id);
+        if verifying_key.verify_prehash(prehash, &signature).is_err() || recovered.ok() != Some(verifying_key) {
+            return Err(FragmentError::SigningAborted(
+                "combined signature does not verify".to_string(),
+            ));
+        }
+
+        Ok(RecoverableSignature {
+            r,
+            s,
+            recovery_id: recovery_id.to_byte(),
+        })
+    }
+
+    /// replays an aborted session from the revealed secrets and names the signer that cheated
+    ///
+    /// all messages passed in must already have passed the round 1 to 3 checks
+    pub fn identify_culprit(
+        &self,
+        round1: &[Round1Message],
+        mta: &[MtaMessage],
+        round3: &[Round3Message],
+        round4: &[Round4Message],
+        reveals: &[AuditReveal],
+    ) -> FragmentError {
+        match self.audit(round1, mta, round3, round4, reveals) {
+            Ok(()) => FragmentError::SigningAborted("abort could not be attributed".to_string()),
+            Err(err) => err,
+        }
+    }
+
+    fn audit(
+        &self,
+        round1: &[Round1Message],
+        mta: &[MtaMessage],
+        round3: &[Round3Message],
+        round4: &[Round4Message],
+        reveals: &[AuditReveal],
+    ) -> Result<(), FragmentError> {
+        let round1 = self.collect(round1, |m| m.index, "1")?;
+        let round3 = self.collect(round3, |m| m.index, "3")?;
+        let round4 = self.collect(round4, |m| m.index, "4")?;
+        let reveals = self.collect(reveals, |r| r.index, "5")?;
+        let blame = |index: u32, reason: &str| FragmentError::SignerMisbehaved {
+            index,
+            reason: reason.to_string(),
+        };
+
+        // alpha and mu as seen by each alice, keyed by (alice, bob)
+        let mut alphas = BTreeMap::new();
+        let mut mus = BTreeMap::new();
+
+        for (index, reveal) in &reveals {
+            let setup = &self.setups[index];
+            let k = scalar_to_biguint(&reveal.k);
+            if setup.paillier.encrypt_with(&k, &reveal.k_randomness) != round1[index].k_ciphertext {
+                return Err(blame(*index, "revealed nonce does not match its ciphertext"));
+            }
+            if ProjectivePoint::GENERATOR * reveal.gamma != round3[index].gamma_point {
+                return Err(blame(*index, "revealed gamma does not match its commitment"));
+            }
+
+            let decryptions: BTreeMap<u32, &Decryption> = reveal.decryptions.iter().map(|d| (d.from, d)).collect();
+            for bob in self.setups.keys().filter(|j| *j != index) {
+                let message = mta
+                    .iter()
+                    .find(|m| m.from == *bob && m.to == *index)
+                    .ok_or_else(|| FragmentError::SigningAborted("missing mta message".to_string()))?;
+                let Some(decryption) = decryptions.get(bob) else {
+                    return Err(blame(*index, "missing decryption in audit"));
+                };
+                let valid = verify_decryption(
+                    &setup.paillier,
+                    &message.gamma_ciphertext,
+                    &decryption.gamma_plaintext,
+                    &decryption.gamma_randomness,
+                ) && verify_decryption(
+                    &setup.paillier,
+                    &message.w_ciphertext,
+                    &decryption.w_plaintext,
+                    &decryption.w_randomness,
+                );
+                if !valid {
+                    return Err(blame(*index, "false decryption of an mta response"));
+                }
+                alphas.insert((*index, *bob), biguint_to_scalar(&decryption.gamma_plaintext));
+                mus.insert((*index, *bob), biguint_to_scalar(&decryption.w_plaintext));
+            }
+        }
+
+        // delta_i = k_i * gamma_i + sum of alpha_(i, j) + sum of beta_(i, j),
+        // and bob's beta_(i, j) = k_j * gamma_i - alpha_(j, i)
+        for (index, reveal) in &reveals {
+            let mut delta = reveal.k * reveal.gamma;
+            for (peer, other) in reveals.iter().filter(|(j, _)| *j != index) {
+                delta += alphas[&(*index, *peer)] + other.k * reveal.gamma - alphas[&(*peer, *index)];
+            }
+            if delta != round3[index].delta {
+                return Err(blame(*index, "delta share does not match the replayed session"));
+            }
+        }
+
+        let (point, _) = self.nonce_point(&round3.values().map(|m| **m).collect::<Vec<_>>())?;
+        let k_total = reveals.values().fold(Scalar::ZERO, |acc, r| acc + r.k);
+        let gamma_total = reveals.values().fold(Scalar::ZERO, |acc, r| acc + r.gamma);
+        let delta_total = round3.values().fold(Scalar::ZERO, |acc, m| acc + m.delta);
+        // R = (gamma / delta) * g, so sigma_i * R = (gamma / delta) * (sigma_i * g)
+        let scale = gamma_total * Option::<Scalar>::from(delta_total.invert()).unwrap_or(Scalar::ZERO);
+
+        for (index, reveal) in &reveals {
+            if round4[index].nonce_share != point * reveal.k {
+                return Err(blame(*index, "nonce share does not match the revealed nonce"));
+            }
+
+            // sigma_i * g = k * W_i + (sum of mu_(i, j) - sum of mu_(j, i)) * g
+            let mut masks = Scalar::ZERO;
+            for peer in reveals.keys().filter(|j| *j != index) {
+                masks += mus[&(*index, *peer)] - mus[&(*peer, *index)];
+            }
+            let sigma_point = self.public_shares[index] * k_total + ProjectivePoint::GENERATOR * masks;
+            if round4[index].sigma_share != sigma_point * scale {
+                return Err(blame(*index, "sigma share does not match the replayed session"));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn verify_range_proof(&self, message: &Round1Message, verifier: u32) -> Result<(), FragmentError> {
+        let valid = message.range_proofs.get(&verifier).is_some_and(|proof| {
+            proof.verify(
+                &self.setups[&message.index].paillier,
+                &self.setups[&verifier].ring_pedersen,
+                &message.k_ciphertext,
+            )
+        });
+        if !valid {
+            return Err(FragmentError::SignerMisbehaved {
+                index: message.index,
+                reason: format!("invalid range proof for signer {}", verifier),
+            });
+        }
+        Ok(())
+    }
+
+    fn verify_mta_message(&self, alice_round1: &Round1Message, message: &MtaMessage) -> Result<(), FragmentError> {
+        let alice = &self.setups[&message.to];
+        let c1 = &alice_round1.k_ciphertext;
+        let valid = alice.paillier.is_valid_ciphertext(&message.gamma_ciphertext)
+            && alice.paillier.is_valid_ciphertext(&message.w_ciphertext)
+            && message
+                .gamma_proof
+                .verify(&alice.paillier, &alice.ring_pedersen, c1, &message.gamma_ciphertext, None)
+            && message.w_proof.verify(
+                &alice.paillier,
+                &alice.ring_pedersen,
+                c1,
+                &message.w_ciphertext,
+                Some(&self.public_shares[&message.from]),
+            );
+        if !valid {
+            return Err(FragmentError::SignerMisbehaved {
+                index: message.from,
+                reason: format!("invalid mta response for signer {}", message.to),
+            });
+        }
+        Ok(())
+    }
+
+    fn verify_gamma_opening(&self, commitment: &[u8; 32], message: &Round3Message) -> Result<(), FragmentError> {
+        let opens = gamma_commitment(message.index, &message.gamma_point, &message.gamma_blinding) == *commitment;
+        if !opens || !verify_schnorr(message.index, &message.gamma_point, &message.gamma_proof) {
+            return Err(FragmentError::SignerMisbehaved {
+                index: message.index,
+                reason: "invalid gamma decommitment".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// maps messages to their signers, requiring exactly one message per signer
+    fn collect<'a, T>(
+        &self,
+        messages: &'a [T],
+        index: impl Fn(&T) -> u32,
+        round: &str,
+    ) -> Result<BTreeMap<u32, &'a T>, FragmentError> {
+        let mut by_index = BTreeMap::new();
+        for message in messages {
+            let i = index(message);
+            if !self.setups.contains_key(&i) || by_index.insert(i, message).is_some() {
+                return Err(FragmentError::SigningAborted(format!(
+                    "unexpected round {} message from signer {}",
+                    round, i
+                )));
+            }
+        }
+        if by_index.len() != self.setups.len() {
+            return Err(FragmentError::SigningAborted(format!("missing round {} messages", round)));
+        }
+        Ok(by_index)
+    }
+
+    /// like `collect`, for messages addressed to `recipient` by every other signer
+    fn collect_peers<'a, T>(
+        &self,
+        messages: &'a [T],
+        recipient: u32,
+        index: impl Fn(&T) -> u32,
+        round: &str,
+    ) -> Result<BTreeMap<u32, &'a T>, FragmentError> {
+        let mut by_index = BTreeMap::new();
+        for message in messages {
+            let i = index(message);
+            if i == recipient || !self.setups.contains_key(&i) || by_index.insert(i, message).is_some() {
+                return Err(FragmentError::SigningAborted(format!(
+                    "unexpected round {} message from signer {}",
+                    round, i
+                )));
+            }
+        }
+        if by_index.len() + 1 != self.setups.len() {
+            return Err(FragmentError::SigningAborted(format!("missing round {} messages", round)));
+        }
+        Ok(by_index)
+    }
+}
+
+impl EcdsaSigner {
+    /// starts a session for the signer holding `share`, sampling its nonce shares
+    pub fn new(share: &SecretShare, keys: Arc<SignerKeys>, set: SignerSet) -> Result<Self, FragmentError> {
+        let index = set
+            .setups
+            .keys()
+            .copied()
+            .find(|i| Scalar::from(*i as u64) == share.x)
+            .ok_or_else(|| FragmentError::SigningAborted("share is not part of the signer set".to_string()))?;
+        if set.setups[&index].paillier != *keys.paillier.public_key() {
+            return Err(FragmentError::SigningAborted("setup does not match this signer's keys".to_string()));
+        }
+
+        let xs: Vec<Scalar> = set.setups.keys().map(|i| Scalar::from(*i as u64)).collect();
+        let w = *share.y.expose_secret() * lagrange_coefficient(&share.x, &xs)?;
+
+        let k = random_scalar();
+        let gamma = random_scalar();
+        let (k_ciphertext, k_randomness) = keys.paillier.public_key().encrypt(&scalar_to_biguint(&k));
+        let mut gamma_blinding = [0u8; 32];
+        OsRng.fill_bytes(&mut gamma_blinding);
+
+        Ok(Self {
+            index,
+            keys,
+            set,
+            w,
+            k,
+            gamma,
+            k_ciphertext,
+            k_randomness,
+            gamma_blinding,
+            sigma_blinding: random_scalar(),
+            round1: None,
+            round3: None,
+            betas: BTreeMap::new(),
+            decryptions: Vec::new(),
+            sigma: None,
+            nonce_point: None,
+        })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// round 1: publishes Enc(k_i), a range proof for each peer and a commitment to g^gamma_i
+    pub fn round1(&self) -> Round1Message {
+        let pk = self.keys.paillier.public_key();
+        let k = scalar_to_biguint(&self.k);
+        let range_proofs = self
+            .set
+            .setups
+            .iter()
+            .filter(|(j, _)| **j != self.index)
+            .map(|(j, setup)| {
+                let proof = RangeProof::prove(pk, &setup.ring_pedersen, &self.k_ciphertext, &k, &self.k_randomness);
+                (*j, proof)
+            })
+            .collect();
+
+        Round1Message {
+            index: self.index,
+            k_ciphertext: self.k_ciphertext.clone(),
+            gamma_commitment: gamma_commitment(
+                self.index,
+                &(ProjectivePoint::GENERATOR * self.gamma),
+                &self.gamma_blinding,
+            ),
+            range_proofs,
+        }
+    }
+
+    /// round 2: checks the peers' range proofs addressed to us and answers each peer's
+    /// encrypted k with mta on gamma_i and mta-with-check on w_i
+    pub fn round2(&mut self, round1: &[Round1Message]) -> Result<Vec<MtaMessage>, FragmentError> {
+        if self.round1.is_some() {
+            return Err(out_of_order(2));
+        }
+        let by_index = self.set.collect(round1, |m| m.index, "1")?;
+        for (index, message) in &by_index {
+            if *index != self.index {
+                self.set.verify_range_proof(message, self.index)?;
+            }
+        }
+
+        let w_public = self.set.public_shares[&self.index];
+        let mut messages = Vec::new();
+        for (alice, message) in by_index.iter().filter(|(j, _)| **j != self.index) {
+            let setup = &self.set.setups[alice];
+            let gamma = mta_respond(&setup.paillier, &setup.ring_pedersen, &message.k_ciphertext, &self.gamma, None);
+            let w = mta_respond(
+                &setup.paillier,
+                &setup.ring_pedersen,
+                &message.k_ciphertext,
+                &self.w,
+                Some(&w_public),
+            );
+            self.betas.insert(*alice, (gamma.beta, w.beta));
+            messages.push(MtaMessage {
+                from: self.index,
+                to: *alice,
+                gamma_ciphertext: gamma.ciphertext,
+                gamma_proof: gamma.proof,
+                w_ciphertext: w.ciphertext,
+                w_proof: w.proof,
+            });
+        }
+
+        self.round1 = Some(by_index.into_iter().map(|(i, m)| (i, m.clone())).collect());
+        Ok(messages)
+    }
+
+    /// round 3: checks and decrypts the mta responses addressed to us, then publishes
+    /// delta_i, opens the gamma commitment and commits to sigma_i
+    /// messages for other signers are ignored, so the full round 2 output can be passed in
+    pub fn round3(&mut self, mta: &[MtaMessage]) -> Result<Round3Message, FragmentError> {
+        let Some(round1) = &self.round1 else {
+            return Err(out_of_order(3));
+        };
+        if self.sigma.is_some() {
+            return Err(out_of_order(3));
+        }
+
+        let received: Vec<MtaMessage> = mta.iter().filter(|m| m.to == self.index).cloned().collect();
+        let received = self.set.collect_peers(&received, self.index, |m| m.from, "2")?;
+
+        let mut delta = self.k * self.gamma;
+        let mut sigma = self.k * self.w;
+        for (bob, message) in received {
+            self.set.verify_mta_message(&round1[&self.index], message)?;
+
+            let (alpha, gamma_plaintext) = mta_finish(&self.keys.paillier, &message.gamma_ciphertext);
+            let (mu, w_plaintext) = mta_finish(&self.keys.paillier, &message.w_ciphertext);
+            let (beta, nu) = self.betas[&bob];
+            delta += alpha + beta;
+            sigma += mu + nu;
+
+            self.decryptions.push(Decryption {
+                from: bob,
+                gamma_randomness: self
+                    .keys
+                    .paillier
+                    .recover_randomness(&message.gamma_ciphertext, &gamma_plaintext),
+                gamma_plaintext,
+                w_randomness: self.keys.paillier.recover_randomness(&message.w_ciphertext, &w_plaintext),
+                w_plaintext,
+            });
+        }
+        self.sigma = Some(sigma);
+
+        let gamma_point = ProjectivePoint::GENERATOR * self.gamma;
+        Ok(Round3Message {
+            index: self.index,
+            delta,
+            gamma_point,
+            gamma_blinding: self.gamma_blinding,
+            gamma_proof: prove_schnorr(self.index, &self.gamma),
+            sigma_commitment: ProjectivePoint::GENERATOR * sigma + pedersen_generator() * self.sigma_blinding,
+        })
+    }
+
+    /// round 4: checks the gamma openings, computes R and publishes k_i * R and sigma_i * R,
+    /// proving k_i to every peer against its encryption and sigma_i against its commitment
+    pub fn round4(&mut self, round3: &[Round3Message]) -> Result<Round4Message, FragmentError> {
+        let (Some(round1), Some(sigma)) = (&self.round1, self.sigma) else {
+            return Err(out_of_order(4));
+        };
+        if self.nonce_point.is_some() {
+            return Err(out_of_order(4));
+        }
+
+        let round1: Vec<Round1Message> = round1.values().cloned().collect();
+        self.set.verify_round3(&round1, round3)?;
+        let (point, _) = self.set.nonce_point(round3)?;
+        self.nonce_point = Some(point);
+        self.round3 = Some(round3.to_vec());
+
+        let pk = self.keys.paillier.public_key();
+        let k = scalar_to_biguint(&self.k);
+        let nonce_share = point * self.k;
+        let nonce_proofs = self
+            .set
+            .setups
+            .iter()
+            .filter(|(j, _)| **j != self.index)
+            .map(|(j, setup)| {
+                let proof = LogProof::prove(
+                    pk,
+                    &setup.ring_pedersen,
+                    &self.k_ciphertext,
+                    &point,
+                    &nonce_share,
+                    &k,
+                    &self.k_randomness,
+                );
+                (*j, proof)
+            })
+            .collect();
+
+        Ok(Round4Message {
+            index: self.index,
+            nonce_share,
+            sigma_share: point * sigma,
+            nonce_proofs,
+            sigma_proof: prove_sigma(self.index, &point, &sigma, &self.sigma_blinding),
+        })
+    }
+
+    /// round 5: checks the round 4 proofs made for us; if the shares are consistent, returns
+    /// s_i = m * k_i + r * sigma_i, otherwise reveals this session's secrets for the audit
+    /// consumes the signer, a session can't be reused after either outcome
+    pub fn round5(self, prehash: &[u8; 32], round4: &[Round4Message]) -> Result<Round5Output, FragmentError> {
+        let (Some(point), Some(sigma), Some(round1), Some(round3)) =
+            (self.nonce_point, self.sigma, &self.round1, &self.round3)
+        else {
+            return Err(out_of_order(5));
+        };
+
+        let round1: Vec<Round1Message> = round1.values().cloned().collect();
+        self.set.check_round4(&round1, round3, round4, Some(self.index))?;
+
+        if !self.set.round4_consistent(round4)? {
+            return Ok(Round5Output::Abort(AuditReveal {
+                index: self.index,
+                k: self.k,
+                k_randomness: self.k_randomness,
+                gamma: self.gamma,
+                decryptions: self.decryptions,
+            }));
+        }
+
+        let s = message_scalar(prehash) * self.k + x_coordinate(&point) * sigma;
+        Ok(Round5Output::Share(SignatureShare { index: self.index, s }))
+    }
+}
+
+fn out_of_order(round: u32) -> FragmentError {
+    FragmentError::SigningAborted(format!("round {} called out of order", round))
+}
+
+/// the message hash as a scalar, as ecdsa uses it
+fn message_scalar(prehash: &[u8; 32]) -> Scalar {
+    <Scalar as Reduce<U256>>::reduce_bytes(prehash.into())
+}
+
+/// r = x(R) mod q
+fn x_coordinate(point: &ProjectivePoint) -> Scalar {
+    <Scalar as Reduce<U256>>::reduce_bytes(&point.to_affine().x())
+}
+
+fn gamma_commitment(index: u32, gamma_point: &ProjectivePoint, blinding: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(b"quorum/ecdsa/gamma-commitment")
+        .chain_update(index.to_be_bytes())
+        .chain_update(gamma_point.to_bytes())
+        .chain_update(blinding)
+        .finalize()
+        .into()
+}
+
+fn schnorr_challenge(index: u32, public: &ProjectivePoint, commitment: &ProjectivePoint) -> Scalar {
+    let digest = Sha256::new()
+        .chain_update(b"quorum/ecdsa/schnorr")
+        .chain_update(index.to_be_bytes())
+        .chain_update(public.to_bytes())
+        .chain_update(commitment.to_bytes())
+        .finalize();
+    <Scalar as Reduce<U256>>::reduce_bytes(&digest)
+}
+
+fn prove_schnorr(index: u32, secret: &Scalar) -> SchnorrProof {
+    let nonce = random_scalar();
+    let commitment = ProjectivePoint::GENERATOR * nonce;
+    let challenge = schnorr_challenge(index, &(ProjectivePoint::GENERATOR * secret), &commitment);
+    SchnorrProof {
+        commitment,
+        response: nonce + challenge * secret,
+    }
+}
+
+fn verify_schnorr(index: u32, public: &ProjectivePoint, proof: &SchnorrProof) -> bool {
+    let challenge = schnorr_challenge(index, public, &proof.commitment);
+    ProjectivePoint::GENERATOR * proof.response == proof.commitment + *public * challenge
+}
+
+/// the second generator h of the sigma commitments, hashed to the curve by try-and-increment
+/// so that nobody knows log_g(h) and a commitment can't be opened to another sigma
+fn pedersen_generator() -> ProjectivePoint {
+    static GENERATOR: OnceLock<ProjectivePoint> = OnceLock::new();
+    *GENERATOR.get_or_init(|| {
+        (0u32..)
+            .find_map(|counter| {
+                let digest = Sha256::new()
+                    .chain_update(b"quorum/ecdsa/pedersen-generator")
+                    .chain_update(counter.to_be_bytes())
+                    .finalize();
+                let mut encoded = [2u8; 33];
+                encoded[1..].copy_from_slice(&digest);
+                Option::from(ProjectivePoint::from_bytes((&encoded).into()))
+            })
+            .expect("about half of all x coordinates are on the curve")
+    })
+}
+
+fn sigma_challenge(
+    index: u32,
+    point: &ProjectivePoint,
+    share: &ProjectivePoint,
+    sigma_commitment: &ProjectivePoint,
+    proof: &SigmaProof,
+) -> Scalar {
+    let digest = Sha256::new()
+        .chain_update(b"quorum/ecdsa/sigma")
+        .chain_update(index.to_be_bytes())
+        .chain_update(point.to_bytes())
+        .chain_update(share.to_bytes())
+        .chain_update(sigma_commitment.to_bytes())
+        .chain_update(proof.point_commitment.to_bytes())
+        .chain_update(proof.commitment.to_bytes())
+        .finalize();
+    <Scalar as Reduce<U256>>::reduce_bytes(&digest)
+}
+
+fn prove_sigma(index: u32, point: &ProjectivePoint, sigma: &Scalar, blinding: &Scalar) -> SigmaProof {
+    let a = random_scalar();
+    let b = random_scalar();
+    let mut proof = SigmaProof {
+        point_commitment: *point * a,
+        commitment: ProjectivePoint::GENERATOR * a + pedersen_generator() * b,
+        response: Scalar::ZERO,
+        blinding_response: Scalar::ZERO,
+    };
+
+    let sigma_commitment = ProjectivePoint::GENERATOR * sigma + pedersen_generator() * blinding;
+    let challenge = sigma_challenge(index, point, &(*point * sigma), &sigma_commitment, &proof);
+    proof.response = a + challenge * sigma;
+    proof.blinding_response = b + challenge * blinding;
+    proof
+}
+
+/// checks z * R == a * R + c * S and z * g + z' * h == (a * g + b * h) + c * T
+fn verify_sigma(
+    index: u32,
+    point: &ProjectivePoint,
+    share: &ProjectivePoint,
+    sigma_commitment: &ProjectivePoint,
+    proof: &SigmaProof,
+) -> bool {
+    let challenge = sigma_challenge(index, point, share, sigma_commitment, proof);
+    *point * proof.response == proof.point_commitment + *share * challenge
+        && ProjectivePoint::GENERATOR * proof.response + pedersen_generator() * proof.blinding_response
+            == proof.commitment + *sigma_commitment * challenge
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::vss::split_secret_verifiable;
+
+    // the smallest modulus the signers accept, keeps key generation fast
+    const TEST_BITS: u64 = MIN_PAILLIER_BITS;
+
+    struct Session {
+        set: SignerSet,
+        signers: Vec<EcdsaSigner>,
+        round1: Vec<Round1Message>,
+        mta: Vec<MtaMessage>,
+        round3: Vec<Round3Message>,
+        round4: Vec<Round4Message>,
+    }
+
+    /// sets up signers for the given share positions and runs rounds 1 to 4
+    /// `tamper` can rewrite the round 3 messages before they are broadcast
+    fn run_to_round4(
+        shares: &[SecretShare],
+        commitments: &[ProjectivePoint],
+        keys: &[Arc<SignerKeys>],
+        tamper: impl Fn(&mut Vec<Round3Message>),
+    ) -> Session {
+        let set = SignerSet::new(setups(&keys[..shares.len()]), commitments).unwrap();
+
+        let mut signers: Vec<EcdsaSigner> = shares
+            .iter()
+            .zip(keys)
+            .map(|(share, k)| EcdsaSigner::new(share, k.clone(), set.clone()).unwrap())
+            .collect();
+
+        let round1: Vec<Round1Message> = signers.iter().map(|s| s.round1()).collect();
+        set.verify_round1(&round1).unwrap();
+
+        let mut mta = Vec::new();
+        for signer in signers.iter_mut() {
+            mta.extend(signer.round2(&round1).unwrap());
+        }
+        set.verify_mta(&round1, &mta).unwrap();
+
+        let mut round3: Vec<Round3Message> = signers.iter_mut().map(|s| s.round3(&mta).unwrap()).collect();
+        tamper(&mut round3);
+        set.verify_round3(&round1, &round3).unwrap();
+
+        let round4 = signers.iter_mut().map(|s| s.round4(&round3).unwrap()).collect();
+
+        Session {
+            set,
+            signers,
+            round1,
+            mta,
+            round3,
+            round4,
+        }
+    }
+
+    /// signer keys take seconds to generate, so the tests share three of them
+    fn keys(count: usize) -> Vec<Arc<SignerKeys>> {
+        static KEYS: OnceLock<Vec<Arc<SignerKeys>>> = OnceLock::new();
+        KEYS.get_or_init(|| (0..3).map(|_| Arc::new(SignerKeys::generate(TEST_BITS))).collect())[..count].to_vec()
+    }
+
+    /// the setups of signers 1..=n, each with its factor proofs for the others
+    fn setups(keys: &[Arc<SignerKeys>]) -> Vec<SignerSetup> {
+        let announced: Vec<SignerSetup> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, k)| k.setup(i as u32 + 1, &[]).unwrap())
+            .collect();
+        keys.iter()
+            .enumerate()
+            .map(|(i, k)| k.setup(i as u32 + 1, &announced).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_threshold_ecdsa_signature_verifies() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 3).unwrap();
+        let keys = keys(3);
+        let prehash: [u8; 32] = Sha256::digest(b"withdraw 5 eth").into();
+
+        // any two signers, then all three
+        for signers in [&shares[..2], &shares[..]] {
+            let session = run_to_round4(signers, &commitments, &keys[..signers.len()], |_| {});
+
+            let mut signature_shares = Vec::new();
+            for signer in session.signers {
+                match signer.round5(&prehash, &session.round4).unwrap() {
+                    Round5Output::Share(share) => signature_shares.push(share),
+                    Round5Output::Abort(_) => panic!("honest session aborted"),
+                }
+            }
+
+            let signature = session
+                .set
+                .combine(&session.round3, &session.round4, &prehash, &signature_shares)
+                .unwrap();
+            assert!(!bool::from(signature.s.is_high()));
+
+            let expected = VerifyingKey::from_affine((ProjectivePoint::GENERATOR * secret).to_affine()).unwrap();
+            let recovered = VerifyingKey::recover_from_prehash(
+                &prehash,
+                &Signature::from_scalars(signature.r, signature.s).unwrap(),
+                RecoveryId::from_byte(signature.recovery_id).unwrap(),
+            )
+            .unwrap();
+            assert_eq!(recovered, expected);
+        }
+    }
+
+    #[test]
+    fn test_bad_range_proof_identified() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 2).unwrap();
+        let keys = keys(2);
+        let set = SignerSet::new(setups(&keys), &commitments).unwrap();
+
+        let first = EcdsaSigner::new(&shares[0], keys[0].clone(), set.clone()).unwrap();
+        let second = EcdsaSigner::new(&shares[1], keys[1].clone(), set.clone()).unwrap();
+
+        // signer 2 swaps in a ciphertext its range proof wasn't made for
+        let round1_first = first.round1();
+        let mut round1_second = second.round1();
+        round1_second.k_ciphertext = keys[1].paillier.public_key().encrypt(&BigUint::from(7u32)).0;
+
+        let err = set.verify_round1(&[round1_first, round1_second]).unwrap_err();
+        assert!(matches!(err, FragmentError::SignerMisbehaved { index: 2, .. }));
+    }
+
+    #[test]
+    fn test_cheating_delta_identified_by_audit() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 3, 3).unwrap();
+        let keys = keys(3);
+        let prehash: [u8; 32] = Sha256::digest(b"rotate cold wallet").into();
+
+        // signer 2 lies about its delta share, which skews R for everyone
+        let session = run_to_round4(&shares, &commitments, &keys, |round3| {
+            round3[1].delta += Scalar::ONE;
+        });
+        assert!(!session.set.round4_consistent(&session.round4).unwrap());
+
+        let mut reveals = Vec::new();
+        for signer in session.signers {
+            match signer.round5(&prehash, &session.round4).unwrap() {
+                Round5Output::Abort(reveal) => reveals.push(reveal),
+                Round5Output::Share(_) => panic!("inconsistent session produced a share"),
+            }
+        }
+
+        let err = session.set.identify_culprit(
+            &session.round1,
+            &session.mta,
+            &session.round3,
+            &session.round4,
+            &reveals,
+        );
+        assert!(matches!(err, FragmentError::SignerMisbehaved { index: 2, .. }));
+    }
+
+    #[test]
+    fn test_bad_signature_share_identified() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 2).unwrap();
+        let keys = keys(2);
+        let prehash: [u8; 32] = Sha256::digest(b"approve payroll").into();
+
+        let session = run_to_round4(&shares, &commitments, &keys, |_| {});
+        let mut signature_shares: Vec<SignatureShare> = session
+            .signers
+            .into_iter()
+            .map(|s| match s.round5(&prehash, &session.round4).unwrap() {
+                Round5Output::Share(share) => share,
+                Round5Output::Abort(_) => panic!("honest session aborted"),
+            })
+            .collect();
+        signature_shares[0].s += Scalar::ONE;
+
+        let err = session
+            .set
+            .combine(&session.round3, &session.round4, &prehash, &signature_shares)
+            .unwrap_err();
+        assert!(matches!(err, FragmentError::SignerMisbehaved { index: 1, .. }));
+    }
+
+    #[test]
+    fn test_unproven_setup_rejected() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (_, commitments) = split_secret_verifiable(&secret, 2, 2).unwrap();
+        let keys = keys(2);
+
+        // setups announced without the factor proofs for the other signer
+        let announced = vec![keys[0].setup(1, &[]).unwrap(), keys[1].setup(2, &[]).unwrap()];
+        let err = SignerSet::new(announced, &commitments).unwrap_err();
+        assert!(matches!(err, FragmentError::SignerMisbehaved { index: 1, .. }));
+
+        // a modulus proof made for another modulus
+        let mut swapped = setups(&keys);
+        swapped[1].modulus_proof = swapped[0].modulus_proof.clone();
+        let err = SignerSet::new(swapped, &commitments).unwrap_err();
+        assert!(matches!(err, FragmentError::SignerMisbehaved { index: 2, .. }));
+
+        // a factor proof made against other ring-pedersen parameters
+        let mut swapped = setups(&keys);
+        let other = keys[0].setup(1, &[keys[1].setup(2, &[]).unwrap(), keys[0].setup(3, &[]).unwrap()]).unwrap();
+        swapped[0].factor_proofs.insert(2, other.factor_proofs[&3].clone());
+        let err = SignerSet::new(swapped, &commitments).unwrap_err();
+        assert!(matches!(err, FragmentError::SignerMisbehaved { index: 1, .. }));
+    }
+
+    #[test]
+    fn test_adaptive_round4_shares_rejected() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 2).unwrap();
+        let keys = keys(2);
+
+        // signer 2 lies about its delta share, so the honest round 4 shares don't add up
+        let session = run_to_round4(&shares, &commitments, &keys, |round3| {
+            round3[1].delta += Scalar::ONE;
+        });
+        assert!(!session.set.round4_consistent(&session.round4).unwrap());
+        session
+            .set
+            .verify_round4(&session.round1, &session.round3, &session.round4)
+            .unwrap();
+
+        // to avoid the audit, it picks its shares after seeing signer 1's so both sums come out right
+        let mut forged = session.round4.clone();
+        forged[1].nonce_share = ProjectivePoint::GENERATOR - forged[0].nonce_share;
+        forged[1].sigma_share = *session.set.group_public_key() - forged[0].sigma_share;
+        assert!(session.set.round4_consistent(&forged).unwrap());
+
+        let err = session
+            .set
+            .verify_round4(&session.round1, &session.round3, &forged)
+            .unwrap_err();
+        assert!(matches!(err, FragmentError::SignerMisbehaved { index: 2, .. }));
+
+        // the honest signer refuses to produce a share
+        let prehash: [u8; 32] = Sha256::digest(b"sweep hot wallet").into();
+        let honest = session.signers.into_iter().next().unwrap();
+        let err = honest.round5(&prehash, &forged).unwrap_err();
+        assert!(matches!(err, FragmentError::SignerMisbehaved { index: 2, .. }));
+    }
+}
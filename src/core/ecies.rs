@@ -0,0 +1,169 @@
+// elliptic curve integrated encryption (ecies) over secp256k1
+// used to send sub-shares between custodians through the coordinator without exposing them
+
+use hmac::{Hmac, Mac};
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::sha2::{Digest, Sha256};
+use k256::{ProjectivePoint, Scalar};
+
+use crate::core::math::random_scalar;
+use crate::core::scheme::FragmentError;
+
+// ciphertext layout: ephemeral point (33) || encrypted payload || tag (32)
+const POINT_LEN: usize = 33;
+const TAG_LEN: usize = 32;
+
+/// encrypts a message to the holder of the secret key behind `recipient`
+///
+/// - recipient: the recipient's public key
+/// - plaintext: the message to encrypt
+/// - context: extra data bound to the ciphertext (e.g. ceremony id and indices)
+///
+/// returns the ephemeral public key, the encrypted payload and an authentication tag
+pub fn encrypt(recipient: &ProjectivePoint, plaintext: &[u8], context: &[u8]) -> Vec<u8> {
+    // fresh ephemeral key for every message
+    let ephemeral = random_scalar();
+    let ephemeral_point = ProjectivePoint::GENERATOR * ephemeral;
+    let shared = *recipient * ephemeral;
+
+    seal(&ephemeral_point, &shared, plaintext, context)
+}
+
+/// decrypts a message produced by `encrypt` using the recipient's secret key
+/// fails if the ciphertext is malformed or was tampered with
+pub fn decrypt(secret: &Scalar, ciphertext: &[u8], context: &[u8]) -> Result<Vec<u8>, FragmentError> {
+    let ephemeral_point = ephemeral_point(ciphertext)?;
+    let shared = ephemeral_point * secret;
+
+    open(&shared, ciphertext, context)
+}
+
+/// extracts the ephemeral public key from a ciphertext
+pub fn ephemeral_point(ciphertext: &[u8]) -> Result<ProjectivePoint, FragmentError> {
+    if ciphertext.len() < POINT_LEN + TAG_LEN {
+        return Err(FragmentError::DecryptionFailed);
+    }
+
+    let encoded: [u8; POINT_LEN] = ciphertext[..POINT_LEN]
+        .try_into()
+        .map_err(|_| FragmentError::DecryptionFailed)?;
+    Option::from(ProjectivePoint::from_bytes((&encoded).into())).ok_or(FragmentError::DecryptionFailed)
+}
+
+/// decrypts a ciphertext given the ecdh shared point directly
+/// the shared point is the recipient's secret times the ephemeral point
+pub fn open(shared: &ProjectivePoint, ciphertext: &[u8], context: &[u8]) -> Result<Vec<u8>, FragmentError> {
+    let ephemeral_point = ephemeral_point(ciphertext)?;
+    let (enc_key, mac_key) = derive_keys(&ephemeral_point, shared, context);
+
+    let body = &ciphertext[POINT_LEN..ciphertext.len() - TAG_LEN];
+    let tag = &ciphertext[ciphertext.len() - TAG_LEN..];
+
+    // checking the tag before touching the payload (constant-time comparison)
+    let mut mac = Hmac::<Sha256>::new_from_slice(&mac_key).expect("hmac accepts any key length");
+    mac.update(&ciphertext[..POINT_LEN]);
+    mac.update(body);
+    mac.verify_slice(tag).map_err(|_| FragmentError::DecryptionFailed)?;
+
+    Ok(apply_keystream(&enc_key, body))
+}
+
+/// encrypts and authenticates a payload under keys derived from the shared point
+fn seal(
+    ephemeral_point: &ProjectivePoint,
+    shared: &ProjectivePoint,
+    plaintext: &[u8],
+    context: &[u8],
+) -> Vec<u8> {
+    let (enc_key, mac_key) = derive_keys(ephemeral_point, shared, context);
+    let body = apply_keystream(&enc_key, plaintext);
+
+    let mut ciphertext = Vec::with_capacity(POINT_LEN + body.len() + TAG_LEN);
+    ciphertext.extend_from_slice(&ephemeral_point.to_bytes());
+    ciphertext.extend_from_slice(&body);
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(&mac_key).expect("hmac accepts any key length");
+    mac.update(&ciphertext);
+    ciphertext.extend_from_slice(&mac.finalize().into_bytes());
+
+    ciphertext
+}
+
+/// derives independent encryption and mac keys from the ecdh shared point
+fn derive_keys(
+    ephemeral_point: &ProjectivePoint,
+    shared: &ProjectivePoint,
+    context: &[u8],
+) -> ([u8; 32], [u8; 32]) {
+    let shared_x = shared.to_affine().to_encoded_point(true);
+
+    let derive = |label: &[u8]| -> [u8; 32] {
+        Sha256::new()
+            .chain_update(label)
+            .chain_update(ephemeral_point.to_bytes())
+            .chain_update(shared_x.as_bytes())
+            .chain_update(context)
+            .finalize()
+            .into()
+    };
+
+    (derive(b"quorum/ecies/enc"), derive(b"quorum/ecies/mac"))
+}
+
+/// xors data with a sha-256 keystream (counter mode)
+/// the same function encrypts and decrypts
+fn apply_keystream(key: &[u8; 32], data: &[u8]) -> Vec<u8> {
+    data.chunks(32)
+        .enumerate()
+        .flat_map(|(counter, chunk)| {
+            let block = Sha256::new()
+                .chain_update(key)
+                .chain_update((counter as u32).to_be_bytes())
+                .finalize();
+            chunk
+                .iter()
+                .zip(block)
+                .map(|(byte, pad)| byte ^ pad)
+                .collect::<Vec<u8>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let public = ProjectivePoint::GENERATOR * secret;
+
+        let message = b"a sub-share that only the recipient may read, longer than one block";
+        let ciphertext = encrypt(&public, message, b"ceremony-001/1/2");
+        assert_eq!(decrypt(&secret, &ciphertext, b"ceremony-001/1/2").unwrap(), message);
+    }
+
+    #[test]
+    fn test_tampering_detected() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let public = ProjectivePoint::GENERATOR * secret;
+        let ciphertext = encrypt(&public, b"payload", b"context");
+
+        // wrong context
+        assert!(decrypt(&secret, &ciphertext, b"other").is_err());
+
+        // wrong key
+        let other = Scalar::generate_vartime(&mut OsRng);
+        assert!(decrypt(&other, &ciphertext, b"context").is_err());
+
+        // flipped payload bit
+        let mut flipped = ciphertext.clone();
+        flipped[POINT_LEN] ^= 1;
+        assert!(decrypt(&secret, &flipped, b"context").is_err());
+
+        // truncated ciphertext
+        assert!(decrypt(&secret, &ciphertext[..40], b"context").is_err());
+    }
+}
@@ -0,0 +1,361 @@
+// frost threshold schnorr signatures (two rounds, bip-340 compatible)
+// custodians sign with their shares, the coordinator aggregates; the key is never reconstructed
+
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::point::AffineCoordinates;
+use k256::schnorr::{Signature, VerifyingKey};
+use k256::sha2::{Digest, Sha256};
+use k256::{FieldBytes, ProjectivePoint, Scalar, U256};
+
+use crate::core::math::{lagrange_coefficient, random_scalar};
+use crate::core::scheme::{FragmentError, SecretShare};
+
+/// the secret nonces a signer keeps between the two rounds
+/// deliberately not `Clone`: a nonce pair must never be used for two signatures
+pub struct SigningNonces {
+    hiding: Scalar,
+    binding: Scalar,
+}
+
+/// the public commitments to a signer's nonces, sent in round 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigningCommitments {
+    pub index: u32,
+    pub hiding: ProjectivePoint,
+    pub binding: ProjectivePoint,
+}
+
+/// a signer's contribution to the final signature, sent in round 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignatureShare {
+    pub index: u32,
+    pub z: Scalar,
+}
+
+/// round 1: samples fresh nonces and commits to them
+pub fn commit(index: u32) -> (SigningNonces, SigningCommitments) {
+    let nonces = SigningNonces {
+        hiding: random_scalar(),
+        binding: random_scalar(),
+    };
+    let commitments = SigningCommitments {
+        index,
+        hiding: ProjectivePoint::GENERATOR * nonces.hiding,
+        binding: ProjectivePoint::GENERATOR * nonces.binding,
+    };
+
+    (nonces, commitments)
+}
+
+/// round 2: produces this signer's signature share
+///
+/// - share: the signer's share of the group key (x must match one of the commitments)
+/// - nonces: the nonces from round 1, consumed so they can't be reused
+/// - group_public_key: the full group key, used for bip-340 parity handling
+/// - message: the message to sign
+/// - commitments: round 1 commitments of every signer taking part
+pub fn sign(
+    share: &SecretShare,
+    nonces: SigningNonces,
+    group_public_key: &ProjectivePoint,
+    message: &[u8],
+    commitments: &[SigningCommitments],
+) -> Result<SignatureShare, FragmentError> {
+    let own = commitments
+        .iter()
+        .find(|c| Scalar::from(c.index as u64) == share.x)
+        .ok_or_else(|| FragmentError::SigningAborted("signer is not part of the signing set".to_string()))?;
+
+    // refusing to sign with nonces that don't match what we committed to
+    if own.hiding != ProjectivePoint::GENERATOR * nonces.hiding
+        || own.binding != ProjectivePoint::GENERATOR * nonces.binding
+    {
+        return Err(FragmentError::SigningAborted(
+            "commitments do not match this signer's nonces".to_string(),
+        ));
+    }
+
+    let context = SigningContext::new(group_public_key, message, commitments)?;
+
+    let rho = context.binding_factor(own.index);
+    let nonce = context.nonce_sign * (nonces.hiding + nonces.binding * rho);
+    let lambda = context.lagrange(own.index)?;
+    let secret = context.key_sign * *share.y.expose_secret();
+
+    Ok(SignatureShare {
+        index: own.index,
+        z: nonce + lambda * context.challenge * secret,
+    })
+}
+
+/// checks a single signature share against the signer's public share g^s_i
+/// lets the aggregator name the signer responsible for a bad share
+pub fn verify_signature_share(
+    signature_share: &SignatureShare,
+    public_share: &ProjectivePoint,
+    group_public_key: &ProjectivePoint,
+    message: &[u8],
+    commitments: &[SigningCommitments],
+) -> bool {
+    let Ok(context) = SigningContext::new(group_public_key, message, commitments) else {
+        return false;
+    };
+    let Some(own) = commitments.iter().find(|c| c.index == signature_share.index) else {
+        return false;
+    };
+
+    let rho = context.binding_factor(own.index);
+    let nonce_point = (own.hiding + own.binding * rho) * context.nonce_sign;
+    let Ok(lambda) = context.lagrange(own.index) else {
+        return false;
+    };
+    let key_point = *public_share * context.key_sign;
+
+    ProjectivePoint::GENERATOR * signature_share.z == nonce_point + key_point * (lambda * context.challenge)
+}
+
+/// combines signature shares into a 64-byte bip-340 signature and verifies it
+///
+/// - public_shares: g^s_i for every signer, in the same order as `signature_shares`
+///
+/// fails naming the first signer whose share is invalid
+pub fn aggregate(
+    group_public_key: &ProjectivePoint,
+    message: &[u8],
+    commitments: &[SigningCommitments],
+    signature_shares: &[SignatureShare],
+    public_shares: &[ProjectivePoint],
+) -> Result<[u8; 64], FragmentError> {
+    if signature_shares.len() != commitments.len() || public_shares.len() != signature_shares.len() {
+        return Err(FragmentError::SigningAborted(
+            "expected one signature share and public share per commitment".to_string(),
+        ));
+    }
+
+    for (signature_share, public_share) in signature_shares.iter().zip(public_shares) {
+        if !verify_signature_share(signature_share, public_share, group_public_key, message, commitments) {
+            return Err(FragmentError::SigningAborted(format!(
+                "invalid signature share from signer {}",
+                signature_share.index
+            )));
+        }
+    }
+
+    let context = SigningContext::new(group_public_key, message, commitments)?;
+    let z = signature_shares
+        .iter()
+        .fold(Scalar::ZERO, |acc, share| acc + share.z);
+
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(&context.group_commitment.to_affine().x());
+    signature[32..].copy_from_slice(&z.to_bytes());
+
+    if !verify_signature(group_public_key, message, &signature) {
+        return Err(FragmentError::SigningAborted(
+            "aggregated signature does not verify".to_string(),
+        ));
+    }
+
+    Ok(signature)
+}
+
+/// verifies a bip-340 signature against the x-only form of the group key
+pub fn verify_signature(group_public_key: &ProjectivePoint, message: &[u8], signature: &[u8; 64]) -> bool {
+    let Ok(verifying_key) = VerifyingKey::from_bytes(&x_only(group_public_key)) else {
+        return false;
+    };
+    let Ok(signature) = Signature::try_from(signature.as_slice()) else {
+        return false;
+    };
+
+    verifying_key.verify_raw(message, &signature).is_ok()
+}
+
+/// the 32-byte x-only encoding of a point used by bip-340
+pub fn x_only(point: &ProjectivePoint) -> FieldBytes {
+    point.to_affine().x()
+}
+
+/// everything derived from the public inputs of a signing session
+struct SigningContext {
+    // hash of the message, the group key and all commitments, binds each rho_i to the session
+    binding_prefix: [u8; 32],
+    indices: Vec<Scalar>,
+    commitments: Vec<SigningCommitments>,
+    group_commitment: ProjectivePoint,
+    challenge: Scalar,
+    // bip-340 only uses points with even y, these are -1 when a point has to be negated
+    nonce_sign: Scalar,
+    key_sign: Scalar,
+}
+
+impl SigningContext {
+    fn new(
+        group_public_key: &ProjectivePoint,
+        message: &[u8],
+        commitments: &[SigningCommitments],
+    ) -> Result<Self, FragmentError> {
+        let mut sorted = commitments.to_vec();
+        sorted.sort_by_key(|c| c.index);
+        sorted.dedup_by_key(|c| c.index);
+        if sorted.len() != commitments.len() || sorted.iter().any(|c| c.index == 0) {
+            return Err(FragmentError::SigningAborted(
+                "signer indices must be distinct and non-zero".to_string(),
+            ));
+        }
+
+        let mut encoded = Sha256::new();
+        for c in &sorted {
+            encoded.update(c.index.to_be_bytes());
+            encoded.update(c.hiding.to_bytes());
+            encoded.update(c.binding.to_bytes());
+        }
+        let binding_prefix: [u8; 32] = tagged_hash(b"quorum/frost/bip340/rho")
+            .chain_update(group_public_key.to_bytes())
+            .chain_update(Sha256::digest(message))
+            .chain_update(encoded.finalize())
+            .finalize()
+            .into();
+
+        let mut context = Self {
+            binding_prefix,
+            indices: sorted.iter().map(|c| Scalar::from(c.index as u64)).collect(),
+            commitments: sorted,
+            group_commitment: ProjectivePoint::IDENTITY,
+            challenge: Scalar::ZERO,
+            nonce_sign: Scalar::ONE,
+            key_sign: Scalar::ONE,
+        };
+
+        // R = sum of D_i + rho_i * E_i
+        let group_commitment = context
+            .commitments
+            .iter()
+            .fold(ProjectivePoint::IDENTITY, |acc, c| {
+                acc + c.hiding + c.binding * context.binding_factor(c.index)
+            });
+        if group_commitment == ProjectivePoint::IDENTITY {
+            return Err(FragmentError::SigningAborted(
+                "group commitment is the identity".to_string(),
+            ));
+        }
+
+        if bool::from(group_commitment.to_affine().y_is_odd()) {
+            context.nonce_sign = -Scalar::ONE;
+        }
+        if bool::from(group_public_key.to_affine().y_is_odd()) {
+            context.key_sign = -Scalar::ONE;
+        }
+
+        // c = H_bip340/challenge(R.x || P.x || m)
+        let challenge = tagged_hash(b"BIP0340/challenge")
+            .chain_update(x_only(&group_commitment))
+            .chain_update(x_only(group_public_key))
+            .chain_update(message)
+            .finalize();
+
+        context.group_commitment = group_commitment;
+        context.challenge = <Scalar as Reduce<U256>>::reduce_bytes(&challenge);
+
+        Ok(context)
+    }
+
+    /// rho_i, which binds each signer's binding nonce to this exact session
+    fn binding_factor(&self, index: u32) -> Scalar {
+        let digest = Sha256::new()
+            .chain_update(self.binding_prefix)
+            .chain_update(index.to_be_bytes())
+            .finalize();
+        <Scalar as Reduce<U256>>::reduce_bytes(&digest)
+    }
+
+    /// the lagrange coefficient of a signer within this signing set
+    fn lagrange(&self, index: u32) -> Result<Scalar, FragmentError> {
+        lagrange_coefficient(&Scalar::from(index as u64), &self.indices)
+    }
+}
+
+/// bip-340 style tagged hash: sha256(sha256(tag) || sha256(tag) || ...)
+fn tagged_hash(tag: &[u8]) -> Sha256 {
+    let tag_hash = Sha256::digest(tag);
+    Sha256::new().chain_update(tag_hash).chain_update(tag_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+    use crate::core::vss::{evaluate_commitments, split_secret_verifiable};
+
+    /// runs both rounds for the given signers and aggregates the result
+    fn threshold_sign(
+        shares: &[SecretShare],
+        commitments: &[ProjectivePoint],
+        signers: &[usize],
+        message: &[u8],
+    ) -> Result<[u8; 64], FragmentError> {
+        let group_public_key = commitments[0];
+
+        let round1: Vec<(SigningNonces, SigningCommitments)> =
+            signers.iter().map(|i| commit(*i as u32 + 1)).collect();
+        let signing_commitments: Vec<SigningCommitments> = round1.iter().map(|(_, c)| *c).collect();
+
+        let mut signature_shares = Vec::new();
+        let mut public_shares = Vec::new();
+        for (i, (nonces, _)) in signers.iter().zip(round1) {
+            signature_shares.push(sign(&shares[*i], nonces, &group_public_key, message, &signing_commitments)?);
+            public_shares.push(evaluate_commitments(commitments, &shares[*i].x));
+        }
+
+        aggregate(&group_public_key, message, &signing_commitments, &signature_shares, &public_shares)
+    }
+
+    #[test]
+    fn test_threshold_signature_verifies() {
+        let message = Sha256::digest(b"transfer 1 btc to cold storage");
+
+        // random keys, so both parities of the group key get exercised
+        for _ in 0..8 {
+            let secret = Scalar::generate_vartime(&mut OsRng);
+            let (shares, commitments) = split_secret_verifiable(&secret, 3, 5).unwrap();
+
+            let signature = threshold_sign(&shares, &commitments, &[0, 2, 4], &message).unwrap();
+            assert!(verify_signature(&commitments[0], &message, &signature));
+
+            let signature = threshold_sign(&shares, &commitments, &[1, 2, 3], &message).unwrap();
+            assert!(verify_signature(&commitments[0], &message, &signature));
+        }
+    }
+
+    #[test]
+    fn test_signature_rejected_for_other_message() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 3).unwrap();
+
+        let signature = threshold_sign(&shares, &commitments, &[0, 1], b"message one").unwrap();
+        assert!(!verify_signature(&commitments[0], b"message two", &signature));
+    }
+
+    #[test]
+    fn test_bad_share_identified() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (mut shares, commitments) = split_secret_verifiable(&secret, 2, 3).unwrap();
+
+        // signer 2 signs with a corrupted share
+        *shares[1].y.expose_secret_mut() += Scalar::ONE;
+        let err = threshold_sign(&shares, &commitments, &[0, 1], b"message").unwrap_err();
+        assert!(err.to_string().contains("signer 2"));
+    }
+
+    #[test]
+    fn test_nonces_must_match_commitments() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 3).unwrap();
+
+        let (_, c1) = commit(1);
+        let (nonces, _) = commit(1);
+        let (_, c2) = commit(2);
+        assert!(sign(&shares[0], nonces, &commitments[0], b"m", &[c1, c2]).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+// two-level group thresholds
+// expresses policies like "2 of 3 departments, each needing 2 of its 4 officers": the
+// secret is shared across the groups, and every group's share is shared again among
+// its members. recovery rebuilds each group share from its members, then the secret
+// from the group shares, enforcing the member threshold of every group used and the
+// group threshold on top
+
+use std::collections::BTreeMap;
+
+use k256::Scalar;
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::core::scheme::{
+    random_polynomial, recover_secret, shares_from_polynomial, split_secret, tag_shares, FragmentError, SecretShare,
+};
+use crate::core::secret::SecretScalar;
+
+/// a member's share, tagged with the group it belongs to
+/// the member threshold is recorded in the inner share's metadata
+#[derive(Debug, Clone)]
+pub struct GroupShare {
+    /// random identifier common to every share of one grouped split
+    pub set_id: [u8; 16],
+    /// the group's index, also the x coordinate of its group share
+    pub group: u32,
+    pub group_threshold: u32,
+    pub share: SecretShare,
+}
+
+/// splits a secret across groups of members
+///
+/// - group_threshold: how many groups are needed
+/// - groups: (member threshold, member count) for every group; a member threshold of
+///   1 is only allowed for a single-member group
+///
+/// returns the member shares of every group, in the order the groups were given
+pub fn split_secret_grouped(
+    secret: &Scalar,
+    group_threshold: usize,
+    groups: &[(usize, usize)],
+) -> Result<Vec<Vec<GroupShare>>, FragmentError> {
+    if group_threshold == 0 || group_threshold > groups.len() {
+        return Err(FragmentError::InvalidThreshold);
+    }
+
+    let mut set_id = [0u8; 16];
+    OsRng.fill_bytes(&mut set_id);
+
+    let group_shares = shares_from_polynomial(&random_polynomial(secret, group_threshold), groups.len());
+    group_shares
+        .iter()
+        .zip(groups)
+        .zip(1u32..)
+        .map(|((group_share, (member_threshold, member_count)), group)| {
+            let members = match (member_threshold, member_count) {
+                (1, 1) => tag_shares(vec![SecretShare::new(Scalar::ONE, *group_share.y.expose_secret())], 1),
+                _ => split_secret(group_share.y.expose_secret(), *member_threshold, *member_count)?,
+            };
+            Ok(members
+                .into_iter()
+                .map(|share| GroupShare {
+                    set_id,
+                    group,
+                    group_threshold: group_threshold as u32,
+                    share,
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// recovers a secret from member shares of a grouped split
+///
+/// groups with fewer members than their threshold are skipped, so officers can hand in
+/// whatever they have; fails with InsufficientShares if fewer than the group threshold
+/// of groups are complete, and with MixedShareSets if the shares come from different splits
+pub fn recover_secret_grouped(shares: &[GroupShare]) -> Result<SecretScalar, FragmentError> {
+    let first = shares.first().ok_or(FragmentError::InsufficientShares)?;
+    if shares
+        .iter()
+        .any(|share| share.set_id != first.set_id || share.group_threshold != first.group_threshold)
+    {
+        return Err(FragmentError::MixedShareSets);
+    }
+
+    let mut groups: BTreeMap<u32, Vec<SecretShare>> = BTreeMap::new();
+    for share in shares {
+        groups.entry(share.group).or_default().push(share.share.clone());
+    }
+
+    let mut group_shares = Vec::new();
+    for (group, members) in &groups {
+        match recover_secret(members) {
+            Ok(y) => group_shares.push(SecretShare { x: Scalar::from(*group as u64), y, meta: None }),
+            // an incomplete group just doesn't count
+            Err(FragmentError::InsufficientShares) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    if group_shares.len() < first.group_threshold as usize {
+        return Err(FragmentError::InsufficientShares);
+    }
+    if first.group_threshold == 1 {
+        return Ok(group_shares[0].y.clone());
+    }
+    recover_secret(&group_shares)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_of_three_departments() {
+        let secret = Scalar::from(123456789u64);
+        let groups = split_secret_grouped(&secret, 2, &[(2, 4), (2, 4), (2, 4)]).unwrap();
+        assert_eq!(groups.len(), 3);
+        assert!(groups.iter().all(|members| members.len() == 4));
+
+        let shares = vec![
+            groups[0][1].clone(),
+            groups[0][3].clone(),
+            groups[2][0].clone(),
+            groups[2][2].clone(),
+            // a lone officer of the second department doesn't hurt
+            groups[1][0].clone(),
+        ];
+        assert_eq!(*recover_secret_grouped(&shares).unwrap().expose_secret(), secret);
+    }
+
+    #[test]
+    fn test_both_thresholds_enforced() {
+        let secret = Scalar::from(42u64);
+        let groups = split_secret_grouped(&secret, 2, &[(2, 4), (2, 4), (2, 4)]).unwrap();
+
+        // a whole department is still just one group
+        assert!(matches!(
+            recover_secret_grouped(&groups[0]),
+            Err(FragmentError::InsufficientShares)
+        ));
+
+        // two departments, but only one officer from the second
+        let shares = vec![groups[0][0].clone(), groups[0][1].clone(), groups[1][0].clone()];
+        assert!(matches!(
+            recover_secret_grouped(&shares),
+            Err(FragmentError::InsufficientShares)
+        ));
+    }
+
+    #[test]
+    fn test_single_member_groups_and_mixing() {
+        let secret = Scalar::from(7u64);
+        // the ceo alone, or two of three board members
+        let groups = split_secret_grouped(&secret, 1, &[(1, 1), (2, 3)]).unwrap();
+        assert_eq!(*recover_secret_grouped(&groups[0]).unwrap().expose_secret(), secret);
+        assert_eq!(*recover_secret_grouped(&groups[1][1..]).unwrap().expose_secret(), secret);
+
+        let other = split_secret_grouped(&secret, 1, &[(1, 1), (2, 3)]).unwrap();
+        let mixed = vec![groups[1][0].clone(), other[1][1].clone()];
+        assert!(matches!(recover_secret_grouped(&mixed), Err(FragmentError::MixedShareSets)));
+
+        assert!(split_secret_grouped(&secret, 3, &[(2, 3), (2, 3)]).is_err());
+        assert!(split_secret_grouped(&secret, 1, &[(1, 2)]).is_err());
+    }
+}
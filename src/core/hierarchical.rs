@@ -0,0 +1,207 @@
+// hierarchical threshold sharing (tassa)
+// expresses policies like "at least one executive plus any two others", which weights
+// can't: levels are ordered from most to least senior, each with a cumulative threshold.
+// level 0 holds plain values f(x) of the polynomial, and every lower level holds a
+// derivative of it of order equal to the previous level's threshold, so its shares
+// carry no information about the low coefficients on their own. recovery is birkhoff
+// interpolation over the mix of values and derivatives
+
+use std::collections::BTreeSet;
+
+use k256::Scalar;
+
+use crate::core::math::{birkhoff_interpolate, evaluate_derivative};
+use crate::core::scheme::{random_polynomial, FragmentError, SecretShare};
+
+/// a participant's share, tagged with its level in the hierarchy
+/// share.y is the derivative of the given order at share.x
+#[derive(Debug, Clone)]
+pub struct HierarchicalShare {
+    pub level: u32,
+    pub order: u32,
+    pub share: SecretShare,
+}
+
+/// splits a secret across a hierarchy of participants
+///
+/// - thresholds: the cumulative threshold of every level, most senior first; a set is
+///   authorized when, for every level, it has at least that many participants at that
+///   level or above. must be strictly increasing, and the last one is the overall threshold
+/// - counts: the number of participants at every level
+///
+/// returns the shares of every level, in the order the levels were given, at
+/// x = 1, 2, 3, ... counted from the most senior level down
+pub fn split_secret_hierarchical(
+    secret: &Scalar,
+    thresholds: &[usize],
+    counts: &[usize],
+) -> Result<Vec<Vec<HierarchicalShare>>, FragmentError> {
+    let threshold = *thresholds.last().ok_or(FragmentError::InvalidThreshold)?;
+    if thresholds.len() != counts.len() || threshold < 2 || thresholds[0] == 0 {
+        return Err(FragmentError::InvalidThreshold);
+    }
+    if thresholds.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(FragmentError::InvalidThreshold);
+    }
+    // every level's threshold has to be reachable by the participants at or above it
+    let mut available = 0;
+    for (level_threshold, count) in thresholds.iter().zip(counts) {
+        available += count;
+        if available < *level_threshold {
+            return Err(FragmentError::InvalidThreshold);
+        }
+    }
+
+    let coefficients = random_polynomial(secret, threshold);
+
+    let mut x = 0u64;
+    let levels = counts
+        .iter()
+        .zip(derivative_orders(thresholds))
+        .zip(0u32..)
+        .map(|((count, order), level)| {
+            (0..*count)
+                .map(|_| {
+                    x += 1;
+                    let point = Scalar::from(x);
+                    HierarchicalShare {
+                        level,
+                        order: order as u32,
+                        share: SecretShare::new(point, evaluate_derivative(&coefficients, order, &point)),
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(levels)
+}
+
+/// checks whether participants at the given levels may recover the secret:
+/// for every level, at least its threshold of them must be at that level or above
+pub fn is_authorized(thresholds: &[usize], levels: &[u32]) -> bool {
+    if levels.iter().any(|level| *level as usize >= thresholds.len()) {
+        return false;
+    }
+
+    thresholds.iter().zip(0u32..).all(|(level_threshold, level)| {
+        levels.iter().filter(|participant| **participant <= level).count() >= *level_threshold
+    })
+}
+
+/// recovers a secret from the shares of an authorized set
+///
+/// fails with InsufficientShares without interpolating anything if the set isn't
+/// authorized, and also if the birkhoff system happens to have no unique solution
+pub fn recover_secret_hierarchical(
+    shares: &[HierarchicalShare],
+    thresholds: &[usize],
+) -> Result<Scalar, FragmentError> {
+    let orders = derivative_orders(thresholds);
+    let mut seen = BTreeSet::new();
+    for share in shares {
+        if orders.get(share.level as usize) != Some(&(share.order as usize)) {
+            return Err(FragmentError::MalformedShare(format!(
+                "share at level {} doesn't match the hierarchy",
+                share.level
+            )));
+        }
+        if !seen.insert(share.share.x.to_bytes()) {
+            return Err(FragmentError::MalformedShare("a participant was counted twice".to_string()));
+        }
+    }
+
+    let levels: Vec<u32> = shares.iter().map(|share| share.level).collect();
+    let threshold = *thresholds.last().ok_or(FragmentError::InvalidThreshold)?;
+    if threshold < 2 || !is_authorized(thresholds, &levels) {
+        return Err(FragmentError::InsufficientShares);
+    }
+
+    let points: Vec<(Scalar, usize, Scalar)> = shares
+        .iter()
+        .map(|share| (share.share.x, share.order as usize, *share.share.y.expose_secret()))
+        .collect();
+    let coefficients = birkhoff_interpolate(&points, threshold).ok_or(FragmentError::InsufficientShares)?;
+
+    Ok(coefficients[0])
+}
+
+/// the derivative order of every level: level 0 takes values, level j the derivative
+/// of order thresholds[j - 1]
+fn derivative_orders(thresholds: &[usize]) -> Vec<usize> {
+    std::iter::once(0).chain(thresholds.iter().copied()).take(thresholds.len()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_executive_plus_two_others() {
+        let secret = Scalar::from(123456789u64);
+        // at least one of two executives, three people in total
+        let thresholds = [1, 3];
+        let levels = split_secret_hierarchical(&secret, &thresholds, &[2, 4]).unwrap();
+        let (executives, staff) = (&levels[0], &levels[1]);
+        assert_eq!(staff[0].order, 1);
+
+        let shares = vec![executives[1].clone(), staff[0].clone(), staff[3].clone()];
+        assert_eq!(recover_secret_hierarchical(&shares, &thresholds).unwrap(), secret);
+
+        let both = vec![executives[0].clone(), executives[1].clone(), staff[2].clone()];
+        assert_eq!(recover_secret_hierarchical(&both, &thresholds).unwrap(), secret);
+
+        // any number of staff without an executive isn't enough
+        assert!(!is_authorized(&thresholds, &[1, 1, 1, 1]));
+        assert!(matches!(
+            recover_secret_hierarchical(staff, &thresholds),
+            Err(FragmentError::InsufficientShares)
+        ));
+
+        // and neither is an executive with only one other
+        let pair = vec![executives[0].clone(), staff[1].clone()];
+        assert!(matches!(
+            recover_secret_hierarchical(&pair, &thresholds),
+            Err(FragmentError::InsufficientShares)
+        ));
+    }
+
+    #[test]
+    fn test_three_levels() {
+        let secret = Scalar::from(42u64);
+        // one director, two of the top two levels, four people overall
+        let thresholds = [1, 2, 4];
+        let levels = split_secret_hierarchical(&secret, &thresholds, &[1, 3, 4]).unwrap();
+        assert_eq!(levels.iter().map(Vec::len).collect::<Vec<_>>(), vec![1, 3, 4]);
+
+        assert!(is_authorized(&thresholds, &[0, 1, 2, 2]));
+        assert!(is_authorized(&thresholds, &[0, 0, 2, 2]));
+        assert!(!is_authorized(&thresholds, &[0, 2, 2, 2]));
+        assert!(!is_authorized(&thresholds, &[1, 1, 2, 2]));
+        assert!(!is_authorized(&thresholds, &[0, 1, 2, 3]));
+
+        let shares = vec![
+            levels[0][0].clone(),
+            levels[1][2].clone(),
+            levels[2][0].clone(),
+            levels[2][3].clone(),
+        ];
+        assert_eq!(recover_secret_hierarchical(&shares, &thresholds).unwrap(), secret);
+
+        let repeated = vec![shares[0].clone(), shares[1].clone(), shares[2].clone(), shares[2].clone()];
+        assert!(matches!(
+            recover_secret_hierarchical(&repeated, &thresholds),
+            Err(FragmentError::MalformedShare(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_hierarchies_rejected() {
+        let secret = Scalar::from(7u64);
+        assert!(split_secret_hierarchical(&secret, &[2, 2], &[2, 2]).is_err());
+        assert!(split_secret_hierarchical(&secret, &[0, 2], &[2, 2]).is_err());
+        assert!(split_secret_hierarchical(&secret, &[2, 3], &[1, 5]).is_err());
+        assert!(split_secret_hierarchical(&secret, &[1, 3], &[1]).is_err());
+        assert!(split_secret_hierarchical(&secret, &[], &[]).is_err());
+    }
+}
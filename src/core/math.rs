@@ -2,6 +2,9 @@
 // all computations stay within secp256k1's scalar field
 
 use k256::Scalar;
+use rand::rngs::OsRng;
+
+use crate::core::scheme::FragmentError;
 
 /// evaluates a polynomial at point x using horner's method
 /// coefficients are ordered from lowest to highest degree: [a0, a1, a2, ...]
@@ -15,34 +18,419 @@ pub fn evaluate_polynomial(coefficients: &[Scalar], x: &Scalar) -> Scalar {
         .fold(Scalar::ZERO, |acc, coeff| acc * x + coeff)
 }
 
+/// recovers every coefficient of the polynomial through the points, lowest degree first
+///
+/// uses newton's divided differences, batching the inversions of every level, and then
+/// expands the newton form; the result has points.len() coefficients, with zeros on top
+/// when the points lie on a polynomial of lower degree. unlike share recovery, x = 0 is
+/// allowed, the x coordinates only have to be distinct
+pub fn interpolate_polynomial(points: &[(Scalar, Scalar)]) -> Result<Vec<Scalar>, FragmentError> {
+    let xs: Vec<Scalar> = points.iter().map(|(x, _)| *x).collect();
+    check_distinct(&xs)?;
+
+    // after level j, differences[i] = f[x_{i-j}, ..., x_i] for every i >= j
+    let mut differences: Vec<Scalar> = points.iter().map(|(_, y)| *y).collect();
+    for level in 1..xs.len() {
+        let gaps: Vec<Scalar> = xs[level..].iter().zip(&xs).map(|(x_i, x_j)| *x_i - x_j).collect();
+        let inverses = invert_gaps(&gaps)?;
+        // from the top down, so differences[i - 1] still holds the previous level
+        for (i, inverse) in (level..xs.len()).zip(&inverses).rev() {
+            differences[i] = (differences[i] - differences[i - 1]) * inverse;
+        }
+    }
+
+    // p(x) = d_0 + (x - x_0)(d_1 + (x - x_1)(d_2 + ...)), expanded from the inside out
+    let (last, rest) = differences.split_last().expect("check_distinct rejects empty input");
+    let mut coefficients = vec![*last];
+    for (difference, x_k) in rest.iter().zip(&xs).rev() {
+        // multiplying by (x - x_k) and adding the next difference
+        let mut next = vec![Scalar::ZERO; coefficients.len() + 1];
+        for (i, coefficient) in coefficients.iter().enumerate() {
+            next[i + 1] += coefficient;
+            next[i] -= *coefficient * x_k;
+        }
+        next[0] += difference;
+        coefficients = next;
+    }
+
+    Ok(coefficients)
+}
+
+/// the degree of a polynomial, ignoring zero coefficients on top
+/// returns None for the zero polynomial
+pub fn polynomial_degree(coefficients: &[Scalar]) -> Option<usize> {
+    coefficients.iter().rposition(|coefficient| !bool::from(coefficient.is_zero()))
+}
+
+/// checks whether the points all lie on one polynomial of at most the given degree,
+/// e.g. whether n shares are consistent with a threshold of degree + 1
+pub fn fits_degree(points: &[(Scalar, Scalar)], degree: usize) -> Result<bool, FragmentError> {
+    let coefficients = interpolate_polynomial(points)?;
+    Ok(polynomial_degree(&coefficients).is_none_or(|actual| actual <= degree))
+}
+
 /// reconstructs the secret (y-intercept at x=0) from a set of share points
 /// uses lagrange interpolation to find the constant term of the polynomial
-pub fn lagrange_interpolate(shares: &[(Scalar, Scalar)]) -> Scalar {
-    let mut secret = Scalar::ZERO;
-
-    for (i, (x_i, y_i)) in shares.iter().enumerate() {
-        // computing the lagrange basis polynomial L_i(0)
-        // L_i(0) = product of (-x_j / (x_i - x_j)) for all j != i
-        let mut basis = Scalar::ONE;
-
-        for (j, (x_j, _)) in shares.iter().enumerate() {
-            if i != j {
-                // numerator is -x_j (since we're evaluating at x=0)
-                // denominator is (x_i - x_j)
-                let numerator = Scalar::ZERO - x_j;
-                let denominator = *x_i - x_j;
-
-                // dividing in a finite field means multiplying by the inverse
-                let denominator_inv = denominator.invert().unwrap();
-                basis = basis * numerator * denominator_inv;
+///
+/// runs in constant time with respect to the y values, see `LagrangeBasis`.
+/// the x coordinates are checked first, see `validate_indices`
+pub fn lagrange_interpolate(shares: &[(Scalar, Scalar)]) -> Result<Scalar, FragmentError> {
+    let xs: Vec<Scalar> = shares.iter().map(|(x, _)| *x).collect();
+    let ys: Vec<Scalar> = shares.iter().map(|(_, y)| *y).collect();
+    LagrangeBasis::new(&xs, &Scalar::ZERO)?.interpolate(&ys)
+}
+
+/// the lagrange basis coefficients L_i(at) of a fixed set of share x coordinates
+///
+/// the coefficients only depend on the x coordinates and the evaluation point, so
+/// recovering many secrets split over the same indices can build the basis once and
+/// then interpolate each y vector with t multiplications, instead of redoing the
+/// O(t^2) products and the inversion for every secret
+///
+/// interpolation runs in constant time with respect to the y values: every coefficient
+/// is built from the same products, all denominators share a single batched inversion,
+/// and nothing branches or exits early on the shares' values
+#[derive(Debug, Clone)]
+pub struct LagrangeBasis {
+    xs: Vec<Scalar>,
+    at: Scalar,
+    coefficients: Vec<Scalar>,
+}
+
+impl LagrangeBasis {
+    /// precomputes L_i(at) for every x_i, after checking them with `validate_indices`
+    pub fn new(xs: &[Scalar], at: &Scalar) -> Result<Self, FragmentError> {
+        validate_indices(xs)?;
+
+        // L_i(at) = product of (at - x_j) / (x_i - x_j) for all j != i
+        let mut numerators = Vec::with_capacity(xs.len());
+        let mut denominators = Vec::with_capacity(xs.len());
+        for (i, x_i) in xs.iter().enumerate() {
+            let mut numerator = Scalar::ONE;
+            let mut denominator = Scalar::ONE;
+            for (_, x_j) in xs.iter().enumerate().filter(|(j, _)| *j != i) {
+                numerator *= *at - x_j;
+                denominator *= *x_i - x_j;
             }
+            numerators.push(numerator);
+            denominators.push(denominator);
         }
 
-        // adding this share's contribution to the final result
-        secret = secret + (*y_i * basis);
+        // dividing in a finite field means multiplying by the inverse
+        // the x coordinates are distinct, so none of the denominators is zero
+        let inverses = batch_invert(&denominators)
+            .ok_or_else(|| FragmentError::MalformedShare("share x coordinates aren't distinct".to_string()))?;
+        let coefficients = numerators.iter().zip(&inverses).map(|(n, inverse)| *n * inverse).collect();
+
+        Ok(LagrangeBasis {
+            xs: xs.to_vec(),
+            at: *at,
+            coefficients,
+        })
+    }
+
+    /// the x coordinates the basis was built for
+    pub fn xs(&self) -> &[Scalar] {
+        &self.xs
+    }
+
+    /// the point the basis evaluates at
+    pub fn at(&self) -> &Scalar {
+        &self.at
     }
 
-    secret
+    /// L_i(at), in the order of `xs`
+    pub fn coefficients(&self) -> &[Scalar] {
+        &self.coefficients
+    }
+
+    /// evaluates the polynomial through (xs[i], ys[i]) at the basis point
+    /// ys must be in the order of `xs`, a vector of another length is malformed
+    pub fn interpolate(&self, ys: &[Scalar]) -> Result<Scalar, FragmentError> {
+        if ys.len() != self.coefficients.len() {
+            return Err(FragmentError::MalformedShare(format!(
+                "expected {} share values, got {}",
+                self.coefficients.len(),
+                ys.len()
+            )));
+        }
+
+        Ok(ys
+            .iter()
+            .zip(&self.coefficients)
+            .fold(Scalar::ZERO, |acc, (y_i, coefficient)| acc + *y_i * coefficient))
+    }
+}
+
+/// checks that share x coordinates can be interpolated over
+/// fails with InsufficientShares if there are none, ZeroIndex if one is zero (that
+/// share would be the secret itself) and DuplicateShare naming the first repeated one
+pub fn validate_indices(xs: &[Scalar]) -> Result<(), FragmentError> {
+    if xs.iter().any(|x| bool::from(x.is_zero())) {
+        return Err(FragmentError::ZeroIndex);
+    }
+    check_distinct(xs)
+}
+
+/// fails with InsufficientShares if there are no x coordinates and DuplicateShare
+/// naming the first repeated one
+fn check_distinct(xs: &[Scalar]) -> Result<(), FragmentError> {
+    if xs.is_empty() {
+        return Err(FragmentError::InsufficientShares);
+    }
+    for (i, x) in xs.iter().enumerate() {
+        if xs[..i].contains(x) {
+            return Err(FragmentError::DuplicateShare(hex::encode(x.to_bytes())));
+        }
+    }
+    Ok(())
+}
+
+/// inverts every value with a single field inversion (montgomery's trick)
+/// the prefix products are inverted once and unwound, so the cost is one `invert()`
+/// plus three multiplications per value, and the work doesn't depend on the values.
+/// returns None if any value is zero
+pub fn batch_invert(values: &[Scalar]) -> Option<Vec<Scalar>> {
+    // prefixes[i] = values[0] * ... * values[i - 1]
+    let mut prefixes = Vec::with_capacity(values.len());
+    let mut product = Scalar::ONE;
+    for value in values {
+        prefixes.push(product);
+        product *= value;
+    }
+
+    let mut inverse = Option::<Scalar>::from(product.invert())?;
+    let mut inverses = vec![Scalar::ZERO; values.len()];
+    for ((slot, prefix), value) in inverses.iter_mut().zip(&prefixes).zip(values).rev() {
+        // inverse holds 1 / (values[0] * ... * values[i]) here
+        *slot = inverse * prefix;
+        inverse *= value;
+    }
+
+    Some(inverses)
+}
+
+/// samples a uniformly random scalar in constant time
+/// `Scalar::generate_vartime` rejection-samples 256-bit strings, so how long it takes
+/// depends on the rng output; this reduces 512 random bits instead, with a bias of
+/// about 2^-256 and no data-dependent loop. used for polynomial coefficients and
+/// anything else that ends up in a share
+pub fn random_scalar() -> Scalar {
+    Scalar::generate_biased(&mut OsRng)
+}
+
+/// evaluates the polynomial through the share points at an arbitrary x
+/// lagrange_interpolate is the special case x = 0; other points let a threshold
+/// of holders rebuild the share at a lost x coordinate. the x coordinates have to be
+/// distinct, but unlike for `lagrange_interpolate` one of them may be zero
+pub fn lagrange_interpolate_at(shares: &[(Scalar, Scalar)], x: &Scalar) -> Result<Scalar, FragmentError> {
+    let xs: Vec<Scalar> = shares.iter().map(|(x_i, _)| *x_i).collect();
+    check_distinct(&xs)?;
+
+    shares.iter().try_fold(Scalar::ZERO, |acc, (x_i, y_i)| Ok(acc + *y_i * basis_coefficient(x_i, &xs, x)?))
+}
+
+/// evaluates the polynomial through the share points at every x in xs
+/// the barycentric weights only depend on the share x coordinates, so they are
+/// computed once for the whole batch instead of once per point. the share x
+/// coordinates have to be distinct, as for `lagrange_interpolate_at`
+pub fn lagrange_interpolate_many(shares: &[(Scalar, Scalar)], xs: &[Scalar]) -> Result<Vec<Scalar>, FragmentError> {
+    let share_xs: Vec<Scalar> = shares.iter().map(|(x_i, _)| *x_i).collect();
+    check_distinct(&share_xs)?;
+
+    // w_i = 1 / product of (x_i - x_j) for all j != i
+    let products: Vec<Scalar> = share_xs
+        .iter()
+        .map(|x_i| {
+            share_xs
+                .iter()
+                .filter(|x_j| *x_j != x_i)
+                .fold(Scalar::ONE, |acc, x_j| acc * (*x_i - x_j))
+        })
+        .collect();
+    let weights = invert_gaps(&products)?;
+
+    xs.iter()
+        .map(|x| {
+            // a target that is one of the shares needs no interpolation
+            if let Some((_, y)) = shares.iter().find(|(x_i, _)| x_i == x) {
+                return Ok(*y);
+            }
+            // p(x) = l(x) * sum of w_i * y_i / (x - x_i), with l(x) = product of (x - x_j)
+            let gaps: Vec<Scalar> = share_xs.iter().map(|x_i| *x - x_i).collect();
+            let l = gaps.iter().fold(Scalar::ONE, |acc, gap| acc * gap);
+            let sum = shares
+                .iter()
+                .zip(&weights)
+                .zip(invert_gaps(&gaps)?)
+                .fold(Scalar::ZERO, |acc, (((_, y_i), w_i), inverse)| acc + *w_i * y_i * inverse);
+            Ok(l * sum)
+        })
+        .collect()
+}
+
+/// computes the lagrange basis coefficient L_i(0) for x_i within the index set xs
+/// multiplying a share's y by this and summing over the set gives the secret,
+/// which also works "in the exponent" for public shares and partial signatures
+pub fn lagrange_coefficient(x_i: &Scalar, xs: &[Scalar]) -> Result<Scalar, FragmentError> {
+    lagrange_coefficient_at(x_i, xs, &Scalar::ZERO)
+}
+
+/// computes the lagrange basis coefficient L_i(x) for x_i within the index set xs
+/// L_i(x) = product of (x - x_j) / (x_i - x_j) for all j != i
+///
+/// xs has to be a set of distinct x coordinates containing x_i; callers often get it
+/// off the network, and a repeated or missing index would give a silently wrong coefficient
+pub fn lagrange_coefficient_at(x_i: &Scalar, xs: &[Scalar], x: &Scalar) -> Result<Scalar, FragmentError> {
+    check_distinct(xs)?;
+    if !xs.contains(x_i) {
+        return Err(FragmentError::MalformedShare(format!(
+            "x = {} is not part of the index set",
+            hex::encode(x_i.to_bytes())
+        )));
+    }
+    basis_coefficient(x_i, xs, x)
+}
+
+/// L_i(x) without checking the index set, for callers that already did
+fn basis_coefficient(x_i: &Scalar, xs: &[Scalar], x: &Scalar) -> Result<Scalar, FragmentError> {
+    let mut numerator = Scalar::ONE;
+    let mut denominator = Scalar::ONE;
+
+    for x_j in xs.iter().filter(|x_j| *x_j != x_i) {
+        numerator *= *x - x_j;
+        denominator *= *x_i - x_j;
+    }
+
+    Ok(numerator * invert_gaps(&[denominator])?[0])
+}
+
+/// inverts differences of x coordinates, which are only ever zero when two of them repeat
+fn invert_gaps(gaps: &[Scalar]) -> Result<Vec<Scalar>, FragmentError> {
+    batch_invert(gaps).ok_or_else(|| FragmentError::MalformedShare("x coordinates aren't distinct".to_string()))
+}
+
+/// solves the linear system matrix * x = rhs by gaussian elimination
+/// free variables are set to zero; returns None if the system is inconsistent
+pub fn solve_linear_system(matrix: Vec<Vec<Scalar>>, rhs: Vec<Scalar>) -> Option<Vec<Scalar>> {
+    gaussian_elimination(matrix, rhs).map(|(solution, _)| solution)
+}
+
+/// like `solve_linear_system`, but also returns None unless the solution is unique
+pub fn solve_linear_system_unique(matrix: Vec<Vec<Scalar>>, rhs: Vec<Scalar>) -> Option<Vec<Scalar>> {
+    let columns = matrix.first().map_or(0, Vec::len);
+    gaussian_elimination(matrix, rhs).and_then(|(solution, rank)| (rank == columns).then_some(solution))
+}
+
+/// reduces the system to row echelon form, returns a solution and the matrix rank
+fn gaussian_elimination(mut matrix: Vec<Vec<Scalar>>, mut rhs: Vec<Scalar>) -> Option<(Vec<Scalar>, usize)> {
+    let columns = matrix.first().map_or(0, Vec::len);
+    let mut pivots = Vec::new();
+    let mut row = 0;
+
+    for column in 0..columns {
+        let Some(pivot) = (row..matrix.len()).find(|r| matrix[*r][column] != Scalar::ZERO) else {
+            continue;
+        };
+        matrix.swap(row, pivot);
+        rhs.swap(row, pivot);
+
+        // scaling the pivot row to 1 and clearing the column everywhere else
+        let inverse = Option::<Scalar>::from(matrix[row][column].invert())?;
+        for entry in matrix[row].iter_mut() {
+            *entry *= inverse;
+        }
+        rhs[row] *= inverse;
+
+        let pivot_row = matrix[row].clone();
+        let pivot_rhs = rhs[row];
+        for (other, (entries, value)) in matrix.iter_mut().zip(rhs.iter_mut()).enumerate() {
+            let factor = entries[column];
+            if other != row && factor != Scalar::ZERO {
+                for (entry, p) in entries.iter_mut().zip(&pivot_row) {
+                    *entry -= factor * p;
+                }
+                *value -= factor * pivot_rhs;
+            }
+        }
+
+        pivots.push(column);
+        row += 1;
+    }
+
+    // a leftover row reading 0 = nonzero means there's no solution
+    if rhs[row..].iter().any(|value| *value != Scalar::ZERO) {
+        return None;
+    }
+
+    let rank = pivots.len();
+    let mut solution = vec![Scalar::ZERO; columns];
+    for (r, column) in pivots.into_iter().enumerate() {
+        solution[column] = rhs[r];
+    }
+    Some((solution, rank))
+}
+
+/// evaluates the order-th derivative of a polynomial at x
+/// for order 0 this is `evaluate_polynomial`
+pub fn evaluate_derivative(coefficients: &[Scalar], order: usize, x: &Scalar) -> Scalar {
+    let mut row = vec![Scalar::ZERO; coefficients.len()];
+    derivative_row(&mut row, order, x);
+    row.iter().zip(coefficients).fold(Scalar::ZERO, |acc, (r, c)| acc + r * c)
+}
+
+/// fills row[n] with d^order/dx^order of x^n at x, i.e. n! / (n - order)! * x^(n - order)
+fn derivative_row(row: &mut [Scalar], order: usize, x: &Scalar) {
+    let mut power = Scalar::ONE;
+    for (n, entry) in row.iter_mut().enumerate().skip(order) {
+        let falling = ((n - order + 1)..=n).fold(Scalar::ONE, |acc, k| acc * Scalar::from(k as u64));
+        *entry = falling * power;
+        power *= x;
+    }
+}
+
+/// birkhoff interpolation: finds the polynomial with `size` coefficients whose
+/// derivatives of the given orders take the given values, from (x, order, value) points
+///
+/// unlike lagrange interpolation the problem isn't always solvable, even with `size`
+/// distinct points; returns None unless there is exactly one such polynomial
+pub fn birkhoff_interpolate(points: &[(Scalar, usize, Scalar)], size: usize) -> Option<Vec<Scalar>> {
+    let mut matrix = Vec::with_capacity(points.len());
+    let mut rhs = Vec::with_capacity(points.len());
+
+    for (x, order, value) in points {
+        let mut row = vec![Scalar::ZERO; size];
+        derivative_row(&mut row, *order, x);
+        matrix.push(row);
+        rhs.push(*value);
+    }
+
+    solve_linear_system_unique(matrix, rhs)
+}
+
+/// divides two polynomials (coefficients from lowest to highest degree)
+/// returns (quotient, remainder), or None if the divisor is empty or its leading
+/// coefficient is zero
+pub fn divide_polynomials(numerator: &[Scalar], divisor: &[Scalar]) -> Option<(Vec<Scalar>, Vec<Scalar>)> {
+    let degree = divisor.len().checked_sub(1)?;
+    let lead_inverse = Option::<Scalar>::from(divisor[degree].invert())?;
+    let mut remainder = numerator.to_vec();
+
+    if numerator.len() < divisor.len() {
+        return Some((Vec::new(), remainder));
+    }
+
+    let mut quotient = vec![Scalar::ZERO; numerator.len() - degree];
+    for position in (0..quotient.len()).rev() {
+        let coefficient = remainder[position + degree] * lead_inverse;
+        quotient[position] = coefficient;
+        for (offset, d) in divisor.iter().enumerate() {
+            remainder[position + offset] -= coefficient * d;
+        }
+    }
+    remainder.truncate(degree);
+
+    Some((quotient, remainder))
 }
 
 #[cfg(test)]
@@ -72,7 +460,327 @@ mod tests {
             (Scalar::from(1u64), Scalar::from(49u64)),
             (Scalar::from(2u64), Scalar::from(56u64)),
         ];
-        let secret = lagrange_interpolate(&shares);
+        let secret = lagrange_interpolate(&shares).unwrap();
+        assert_eq!(secret, Scalar::from(42u64));
+    }
+
+    #[test]
+    fn test_lagrange_basis_reused_across_secrets() {
+        let xs: Vec<Scalar> = [3u64, 5, 8, 13].into_iter().map(Scalar::from).collect();
+        let at_zero = LagrangeBasis::new(&xs, &Scalar::ZERO).unwrap();
+        let at_two = LagrangeBasis::new(&xs, &Scalar::from(2u64)).unwrap();
+
+        for seed in 1..20u64 {
+            let coefficients: Vec<Scalar> = (0..4).map(|i| Scalar::from(seed * 31 + i)).collect();
+            let ys: Vec<Scalar> = xs.iter().map(|x| evaluate_polynomial(&coefficients, x)).collect();
+            let shares: Vec<(Scalar, Scalar)> = xs.iter().copied().zip(ys.iter().copied()).collect();
+
+            assert_eq!(at_zero.interpolate(&ys).unwrap(), coefficients[0]);
+            assert_eq!(at_zero.interpolate(&ys).unwrap(), lagrange_interpolate(&shares).unwrap());
+            assert_eq!(
+                at_two.interpolate(&ys).unwrap(),
+                evaluate_polynomial(&coefficients, &Scalar::from(2u64))
+            );
+        }
+
+        // evaluating at one of the indices picks that share out
+        let at_five = LagrangeBasis::new(&xs, &Scalar::from(5u64)).unwrap();
+        assert_eq!(at_five.coefficients(), &[Scalar::ZERO, Scalar::ONE, Scalar::ZERO, Scalar::ZERO]);
+
+        assert!(at_zero.interpolate(&[Scalar::ONE]).is_err());
+        assert!(matches!(
+            LagrangeBasis::new(&[Scalar::ONE, Scalar::ONE], &Scalar::ZERO),
+            Err(FragmentError::DuplicateShare(_))
+        ));
+    }
+
+    #[test]
+    fn test_batch_invert() {
+        let values: Vec<Scalar> = (1..=5u64).map(Scalar::from).collect();
+        let inverses = batch_invert(&values).unwrap();
+        assert!(values.iter().zip(&inverses).all(|(value, inverse)| *value * inverse == Scalar::ONE));
+
+        assert_eq!(batch_invert(&[]), Some(Vec::new()));
+        assert_eq!(batch_invert(&[Scalar::ONE, Scalar::ZERO]), None);
+    }
+
+    /// welch's t statistic between two samples
+    fn welch_t(a: &[f64], b: &[f64]) -> f64 {
+        let stats = |xs: &[f64]| {
+            let mean = xs.iter().sum::<f64>() / xs.len() as f64;
+            let variance = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (xs.len() - 1) as f64;
+            (mean, variance / xs.len() as f64)
+        };
+        let ((mean_a, var_a), (mean_b, var_b)) = (stats(a), stats(b));
+        (mean_a - mean_b) / (var_a + var_b).sqrt()
+    }
+
+    /// drops the slowest tenth of a sample, which is mostly scheduler noise
+    fn crop(mut xs: Vec<f64>) -> Vec<f64> {
+        xs.sort_by(f64::total_cmp);
+        xs.truncate(xs.len() * 9 / 10);
+        xs
+    }
+
+    // a wall-clock statistic is slow and noisy on shared machines, so it stays out of the
+    // default run; run it on a quiet one with
+    // cargo test --release --lib -- --ignored test_interpolation_timing
+    #[test]
+    #[ignore = "timing test, run with --ignored on a quiet machine"]
+    fn test_interpolation_timing_is_independent_of_shares() {
+        // dudect: time recovery of a fixed set of share values (all zero) against fresh
+        // random ones, interleaved at random, and check that welch's t-test can't tell
+        // the two classes apart. |t| > 10 is a leak dudect would flag with near certainty
+        let xs: Vec<Scalar> = (1..=5u64).map(Scalar::from).collect();
+        let fixed: Vec<(Scalar, Scalar)> = xs.iter().map(|x| (*x, Scalar::ZERO)).collect();
+
+        // inputs are prepared up front so generating them doesn't disturb the timings
+        let mut rng = OsRng;
+        let inputs: Vec<(bool, Vec<(Scalar, Scalar)>)> = (0..4000)
+            .map(|_| {
+                let use_fixed = rand::RngCore::next_u32(&mut rng) & 1 == 0;
+                let shares = if use_fixed {
+                    fixed.clone()
+                } else {
+                    xs.iter().map(|x| (*x, random_scalar())).collect()
+                };
+                (use_fixed, shares)
+            })
+            .collect();
+
+        let (mut fixed_times, mut random_times) = (Vec::new(), Vec::new());
+        for (use_fixed, shares) in &inputs {
+            let start = std::time::Instant::now();
+            for _ in 0..4 {
+                let _ = std::hint::black_box(lagrange_interpolate(std::hint::black_box(shares)));
+            }
+            let elapsed = start.elapsed().as_nanos() as f64;
+
+            if *use_fixed {
+                fixed_times.push(elapsed);
+            } else {
+                random_times.push(elapsed);
+            }
+        }
+
+        let t = welch_t(&crop(fixed_times), &crop(random_times));
+        assert!(t.abs() < 10.0, "timing depends on the share values (t = {:.2})", t);
+    }
+
+    #[test]
+    fn test_lagrange_coefficients_sum_to_secret() {
+        // same polynomial as above, recovering through the coefficients instead
+        let xs = vec![Scalar::from(1u64), Scalar::from(2u64)];
+        let ys = [Scalar::from(49u64), Scalar::from(56u64)];
+
+        let secret = xs
+            .iter()
+            .zip(ys.iter())
+            .fold(Scalar::ZERO, |acc, (x, y)| acc + *y * lagrange_coefficient(x, &xs).unwrap());
         assert_eq!(secret, Scalar::from(42u64));
     }
+
+    #[test]
+    fn test_interpolate_at_other_points() {
+        // 42 + 7x again: from the shares at x=1 and x=2, rebuild the share at x=5 (77)
+        let shares = vec![
+            (Scalar::from(1u64), Scalar::from(49u64)),
+            (Scalar::from(2u64), Scalar::from(56u64)),
+        ];
+        assert_eq!(lagrange_interpolate_at(&shares, &Scalar::from(5u64)).unwrap(), Scalar::from(77u64));
+        assert_eq!(
+            lagrange_interpolate_at(&shares, &Scalar::ZERO).unwrap(),
+            lagrange_interpolate(&shares).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_solve_linear_system() {
+        // x + y = 5, x - y = 1
+        let matrix = vec![
+            vec![Scalar::ONE, Scalar::ONE],
+            vec![Scalar::ONE, -Scalar::ONE],
+        ];
+        let solution = solve_linear_system(matrix, vec![Scalar::from(5u64), Scalar::ONE]).unwrap();
+        assert_eq!(solution, vec![Scalar::from(3u64), Scalar::from(2u64)]);
+
+        // x + y = 1, x + y = 2 has no solution
+        let matrix = vec![vec![Scalar::ONE, Scalar::ONE], vec![Scalar::ONE, Scalar::ONE]];
+        assert!(solve_linear_system(matrix, vec![Scalar::ONE, Scalar::from(2u64)]).is_none());
+    }
+
+    #[test]
+    fn test_polynomial_division() {
+        // (x^2 + 3x + 2) / (x + 1) = x + 2, and adding 5 leaves a remainder of 5
+        let numerator = [Scalar::from(7u64), Scalar::from(3u64), Scalar::ONE];
+        let (quotient, remainder) = divide_polynomials(&numerator, &[Scalar::ONE, Scalar::ONE]).unwrap();
+        assert_eq!(quotient, vec![Scalar::from(2u64), Scalar::ONE]);
+        assert_eq!(remainder, vec![Scalar::from(5u64)]);
+
+        // an empty divisor or one with a zero leading coefficient is refused, not a panic
+        assert!(divide_polynomials(&numerator, &[]).is_none());
+        assert!(divide_polynomials(&numerator, &[Scalar::ONE, Scalar::ZERO]).is_none());
+    }
+
+    #[test]
+    fn test_birkhoff_interpolation() {
+        // f(x) = 5 + 3x + 2x^2, so f'(x) = 3 + 4x and f''(x) = 4
+        let coeffs = vec![Scalar::from(5u64), Scalar::from(3u64), Scalar::from(2u64)];
+        let x = Scalar::from(2u64);
+        assert_eq!(evaluate_derivative(&coeffs, 0, &x), Scalar::from(19u64));
+        assert_eq!(evaluate_derivative(&coeffs, 1, &x), Scalar::from(11u64));
+        assert_eq!(evaluate_derivative(&coeffs, 2, &x), Scalar::from(4u64));
+
+        // one value and two first derivatives pin it down
+        let points = vec![
+            (Scalar::ONE, 0, Scalar::from(10u64)),
+            (Scalar::from(2u64), 1, Scalar::from(11u64)),
+            (Scalar::from(3u64), 1, Scalar::from(15u64)),
+        ];
+        assert_eq!(birkhoff_interpolate(&points, 3).unwrap(), coeffs);
+
+        // derivatives alone never determine the constant term
+        let derivatives_only = vec![
+            (Scalar::ONE, 1, Scalar::from(7u64)),
+            (Scalar::from(2u64), 1, Scalar::from(11u64)),
+            (Scalar::from(3u64), 1, Scalar::from(15u64)),
+        ];
+        assert!(birkhoff_interpolate(&derivatives_only, 3).is_none());
+    }
+
+    #[test]
+    fn test_interpolate_many() {
+        // f(x) = 7 + 2x + 3x^2 + x^3
+        let coeffs: Vec<Scalar> = [7u64, 2, 3, 1].into_iter().map(Scalar::from).collect();
+        let shares: Vec<(Scalar, Scalar)> = (1..=4u64)
+            .map(|x| (Scalar::from(x), evaluate_polynomial(&coeffs, &Scalar::from(x))))
+            .collect();
+
+        let xs = vec![Scalar::ZERO, -Scalar::ONE, Scalar::from(3u64), Scalar::from(10u64)];
+        let expected: Vec<Scalar> = xs.iter().map(|x| evaluate_polynomial(&coeffs, x)).collect();
+        assert_eq!(lagrange_interpolate_many(&shares, &xs).unwrap(), expected);
+        assert_eq!(lagrange_interpolate_many(&shares, &xs[..1]).unwrap()[0], lagrange_interpolate(&shares).unwrap());
+
+        // a repeated share x used to be skipped over and give a wrong value
+        let repeated = [shares[0], shares[0], shares[1]];
+        assert!(matches!(lagrange_interpolate_many(&repeated, &xs), Err(FragmentError::DuplicateShare(_))));
+
+        let share_xs: Vec<Scalar> = shares.iter().map(|(x, _)| *x).collect();
+        assert!(matches!(
+            lagrange_coefficient_at(&share_xs[0], &[share_xs[0], share_xs[0]], &Scalar::ONE),
+            Err(FragmentError::DuplicateShare(_))
+        ));
+        assert!(matches!(
+            lagrange_coefficient_at(&Scalar::from(9u64), &share_xs, &Scalar::ONE),
+            Err(FragmentError::MalformedShare(_))
+        ));
+    }
+
+    #[test]
+    fn test_interpolate_polynomial() {
+        // 5 + 3x + 2x^2 through x = 1, 2, 3
+        let coefficients = vec![Scalar::from(5u64), Scalar::from(3u64), Scalar::from(2u64)];
+        let points: Vec<(Scalar, Scalar)> = (1..=3u64)
+            .map(|x| {
+                let x = Scalar::from(x);
+                (x, evaluate_polynomial(&coefficients, &x))
+            })
+            .collect();
+        assert_eq!(interpolate_polynomial(&points).unwrap(), coefficients);
+        assert_eq!(polynomial_degree(&coefficients), Some(2));
+
+        // a fourth point on the same parabola keeps the degree at 2
+        let mut more = points.clone();
+        more.push((Scalar::ZERO, Scalar::from(5u64)));
+        let recovered = interpolate_polynomial(&more).unwrap();
+        assert_eq!(recovered[..3], coefficients[..]);
+        assert_eq!(recovered[3], Scalar::ZERO);
+        assert!(fits_degree(&more, 2).unwrap());
+        assert!(!fits_degree(&more, 1).unwrap());
+
+        // moving it off the parabola needs the full cubic
+        more[3].1 += Scalar::ONE;
+        assert!(!fits_degree(&more, 2).unwrap());
+        assert_eq!(polynomial_degree(&interpolate_polynomial(&more).unwrap()), Some(3));
+
+        assert_eq!(polynomial_degree(&[Scalar::ZERO, Scalar::ZERO]), None);
+        assert!(matches!(
+            interpolate_polynomial(&[points[0], points[0]]),
+            Err(FragmentError::DuplicateShare(_))
+        ));
+        assert!(matches!(interpolate_polynomial(&[]), Err(FragmentError::InsufficientShares)));
+        assert!(matches!(
+            lagrange_interpolate_at(&[points[1], points[1]], &Scalar::ONE),
+            Err(FragmentError::DuplicateShare(_))
+        ));
+    }
+
+    mod properties {
+        use super::*;
+        use k256::elliptic_curve::ops::Reduce;
+        use k256::U256;
+        use proptest::collection::{btree_set, vec};
+        use proptest::prelude::*;
+
+        fn scalar() -> impl Strategy<Value = Scalar> {
+            any::<[u8; 32]>().prop_map(|bytes| <Scalar as Reduce<U256>>::reduce_bytes(&bytes.into()))
+        }
+
+        /// a random polynomial of degree below 12 with enough distinct x coordinates to fix it,
+        /// plus `extra` more
+        fn polynomial_and_xs(extra: usize) -> impl Strategy<Value = (Vec<Scalar>, Vec<Scalar>)> {
+            vec(scalar(), 1..12).prop_flat_map(move |coefficients| {
+                let xs = btree_set(any::<u64>(), coefficients.len() + extra)
+                    .prop_map(|xs| xs.into_iter().map(Scalar::from).collect::<Vec<_>>());
+                (Just(coefficients), xs)
+            })
+        }
+
+        fn points_on(coefficients: &[Scalar], xs: &[Scalar]) -> Vec<(Scalar, Scalar)> {
+            xs.iter().map(|x| (*x, evaluate_polynomial(coefficients, x))).collect()
+        }
+
+        proptest! {
+            #[test]
+            fn interpolate_polynomial_recovers_coefficients((coefficients, xs) in polynomial_and_xs(0)) {
+                let points = points_on(&coefficients, &xs);
+                prop_assert_eq!(interpolate_polynomial(&points).unwrap(), coefficients);
+            }
+
+            #[test]
+            fn interpolate_at_matches_evaluation(
+                (coefficients, xs) in polynomial_and_xs(0),
+                at in scalar(),
+            ) {
+                let points = points_on(&coefficients, &xs);
+                prop_assert_eq!(
+                    lagrange_interpolate_at(&points, &at).unwrap(),
+                    evaluate_polynomial(&coefficients, &at)
+                );
+                // share indices can't be zero, but any other set of xs gives the same basis
+                if !xs.contains(&Scalar::ZERO) {
+                    let ys: Vec<Scalar> = points.iter().map(|(_, y)| *y).collect();
+                    let basis = LagrangeBasis::new(&xs, &at).unwrap();
+                    prop_assert_eq!(basis.interpolate(&ys).unwrap(), evaluate_polynomial(&coefficients, &at));
+                }
+            }
+
+            #[test]
+            fn extra_points_fit_the_degree(
+                (coefficients, xs) in polynomial_and_xs(2),
+                tampered in 0usize..64,
+                offset in scalar(),
+            ) {
+                let degree = coefficients.len() - 1;
+                let mut points = points_on(&coefficients, &xs);
+                prop_assert!(fits_degree(&points, degree).unwrap());
+
+                // one point off the polynomial needs every extra coefficient
+                prop_assume!(!bool::from(offset.is_zero()));
+                let i = tampered % points.len();
+                points[i].1 += offset;
+                prop_assert!(!fits_degree(&points, degree).unwrap());
+            }
+        }
+    }
 }
@@ -1,4 +1,24 @@
 // core module exports
 
+pub mod bip32;
+pub mod decryption;
+pub mod dkg;
+pub mod ecdsa;
+pub mod ecies;
+pub mod frost;
+pub mod gf256;
+pub mod groups;
+pub mod hierarchical;
 pub mod math;
+pub mod mta;
+pub mod packed;
+pub mod paillier;
+pub mod pubkey;
+pub mod refresh;
+pub mod repair;
+pub mod reshare;
 pub mod scheme;
+pub mod secret;
+pub mod slip39;
+pub mod vss;
+pub mod weighted;
@@ -0,0 +1,670 @@
+// multiplicative-to-additive (mta) share conversion with paillier and range proofs
+// alice holds a, bob holds b; afterwards alpha + beta = a * b mod q and neither learns the other's input
+// the proofs follow appendix a of gennaro-goldfeder 2018, the log proof gennaro-goldfeder 2020
+
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::PrimeField;
+use k256::{ProjectivePoint, Scalar};
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::rngs::OsRng;
+
+use crate::core::paillier::{
+    decode_biguints, encode_biguints, transcript_hash, PaillierPublicKey, PaillierSecretKey,
+    RingPedersenParams,
+};
+
+/// the secp256k1 group order as a big integer
+pub fn curve_order() -> BigUint {
+    BigUint::parse_bytes(
+        b"fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+        16,
+    )
+    .expect("valid constant")
+}
+
+/// converts a scalar to the integer in [0, q)
+pub fn scalar_to_biguint(scalar: &Scalar) -> BigUint {
+    BigUint::from_bytes_be(&scalar.to_bytes())
+}
+
+/// reduces an integer mod q into a scalar
+pub fn biguint_to_scalar(value: &BigUint) -> Scalar {
+    let reduced = value % curve_order();
+    let bytes = reduced.to_bytes_be();
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    Option::from(Scalar::from_repr(padded.into())).expect("value was reduced mod q")
+}
+
+/// alice's proof that her ciphertext c = Enc(m; r) encrypts m < q^3
+/// made against the verifier's ring-pedersen parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeProof {
+    z: BigUint,
+    u: BigUint,
+    w: BigUint,
+    s: BigUint,
+    s1: BigUint,
+    s2: BigUint,
+}
+
+impl RangeProof {
+    /// proves that `ciphertext` = Enc(m; r) under `pk` with m small
+    pub fn prove(
+        pk: &PaillierPublicKey,
+        verifier: &RingPedersenParams,
+        ciphertext: &BigUint,
+        m: &BigUint,
+        r: &BigUint,
+    ) -> Self {
+        let q = curve_order();
+        let q3 = q.pow(3);
+        let n_tilde = &verifier.n_tilde;
+
+        let alpha = OsRng.gen_biguint_below(&q3);
+        let beta = pk.random_unit();
+        let gamma = OsRng.gen_biguint_below(&(&q3 * n_tilde));
+        let rho = OsRng.gen_biguint_below(&(&q * n_tilde));
+
+        let z = verifier.commit(m, &rho);
+        let u = pk.encrypt_with(&alpha, &beta);
+        let w = verifier.commit(&alpha, &gamma);
+
+        let e = range_challenge(pk, verifier, ciphertext, &z, &u, &w);
+
+        Self {
+            s: (r.modpow(&e, &pk.n) * beta) % &pk.n,
+            s1: &e * m + alpha,
+            s2: &e * rho + gamma,
+            z,
+            u,
+            w,
+        }
+    }
+
+    /// checks the proof for `ciphertext` under alice's key and our own parameters
+    pub fn verify(&self, pk: &PaillierPublicKey, verifier: &RingPedersenParams, ciphertext: &BigUint) -> bool {
+        let q = curve_order();
+        let n_tilde = &verifier.n_tilde;
+        let nn = pk.n_squared();
+
+        if self.s1 > q.pow(3) || !pk.is_valid_ciphertext(ciphertext) || !pk.is_valid_ciphertext(&self.u) {
+            return false;
+        }
+        let (Some(c_inv), Some(z_inv)) = (ciphertext.modinv(nn), self.z.modinv(n_tilde)) else {
+            return false;
+        };
+
+        let e = range_challenge(pk, verifier, ciphertext, &self.z, &self.u, &self.w);
+
+        // u == (1 + n)^s1 * s^n * c^-e mod n^2
+        let u = (pk.encrypt_with(&self.s1, &self.s) * c_inv.modpow(&e, nn)) % nn;
+        // w == h1^s1 * h2^s2 * z^-e mod n~
+        let w = (verifier.commit(&self.s1, &self.s2) * z_inv.modpow(&e, n_tilde)) % n_tilde;
+
+        u == self.u && w == self.w
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_biguints(&[&self.z, &self.u, &self.w, &self.s, &self.s1, &self.s2])
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut v = decode_biguints(bytes, 6)?.into_iter();
+        Some(Self {
+            z: v.next()?,
+            u: v.next()?,
+            w: v.next()?,
+            s: v.next()?,
+            s1: v.next()?,
+            s2: v.next()?,
+        })
+    }
+}
+
+fn range_challenge(
+    pk: &PaillierPublicKey,
+    verifier: &RingPedersenParams,
+    ciphertext: &BigUint,
+    z: &BigUint,
+    u: &BigUint,
+    w: &BigUint,
+) -> BigUint {
+    let digest = transcript_hash(
+        b"quorum/mta/range",
+        &[&pk.n, &verifier.n_tilde, &verifier.h1, &verifier.h2, ciphertext, z, u, w],
+    );
+    BigUint::from_bytes_be(&digest) % curve_order()
+}
+
+/// alice's proof that her ciphertext c = Enc(x; r) and the point X = x * B hide the same x < q^3
+/// ("pdl with slack" in gennaro-goldfeder 2020), made against the verifier's ring-pedersen parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogProof {
+    z: BigUint,
+    u1: ProjectivePoint,
+    u2: BigUint,
+    u3: BigUint,
+    s: BigUint,
+    s1: BigUint,
+    s2: BigUint,
+}
+
+impl LogProof {
+    /// proves that `ciphertext` = Enc(x; r) under `pk` and `public` = x * `base`
+    pub fn prove(
+        pk: &PaillierPublicKey,
+        verifier: &RingPedersenParams,
+        ciphertext: &BigUint,
+        base: &ProjectivePoint,
+        public: &ProjectivePoint,
+        x: &BigUint,
+        r: &BigUint,
+    ) -> Self {
+        let q = curve_order();
+        let q3 = q.pow(3);
+        let n_tilde = &verifier.n_tilde;
+
+        let alpha = OsRng.gen_biguint_below(&q3);
+        let beta = pk.random_unit();
+        let rho = OsRng.gen_biguint_below(&(&q * n_tilde));
+        let gamma = OsRng.gen_biguint_below(&(&q3 * n_tilde));
+
+        let z = verifier.commit(x, &rho);
+        let u1 = *base * biguint_to_scalar(&alpha);
+        let u2 = pk.encrypt_with(&alpha, &beta);
+        let u3 = verifier.commit(&alpha, &gamma);
+
+        let e = log_challenge(pk, verifier, ciphertext, base, public, &z, &u1, &u2, &u3);
+
+        Self {
+            s: (r.modpow(&e, &pk.n) * beta) % &pk.n,
+            s1: &e * x + alpha,
+            s2: &e * rho + gamma,
+            z,
+            u1,
+            u2,
+            u3,
+        }
+    }
+
+    /// checks the proof for `ciphertext` and `public` under alice's key and our own parameters
+    pub fn verify(
+        &self,
+        pk: &PaillierPublicKey,
+        verifier: &RingPedersenParams,
+        ciphertext: &BigUint,
+        base: &ProjectivePoint,
+        public: &ProjectivePoint,
+    ) -> bool {
+        let q = curve_order();
+        let n_tilde = &verifier.n_tilde;
+        let nn = pk.n_squared();
+
+        if self.s1 > q.pow(3) || !pk.is_valid_ciphertext(ciphertext) || !pk.is_valid_ciphertext(&self.u2) {
+            return false;
+        }
+
+        let e = log_challenge(pk, verifier, ciphertext, base, public, &self.z, &self.u1, &self.u2, &self.u3);
+
+        // s1 * B == e * X + u1
+        if *base * biguint_to_scalar(&self.s1) != *public * biguint_to_scalar(&e) + self.u1 {
+            return false;
+        }
+        // (1 + n)^s1 * s^n == u2 * c^e mod n^2
+        if pk.encrypt_with(&self.s1, &self.s) != (&self.u2 * ciphertext.modpow(&e, nn)) % nn {
+            return false;
+        }
+        // h1^s1 * h2^s2 == u3 * z^e mod n~
+        verifier.commit(&self.s1, &self.s2) == (&self.u3 * self.z.modpow(&e, n_tilde)) % n_tilde
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let u1 = BigUint::from_bytes_be(&self.u1.to_bytes());
+        encode_biguints(&[&self.z, &u1, &self.u2, &self.u3, &self.s, &self.s1, &self.s2])
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut v = decode_biguints(bytes, 7)?.into_iter();
+        let z = v.next()?;
+        let encoded: [u8; 33] = v.next()?.to_bytes_be().try_into().ok()?;
+        Some(Self {
+            z,
+            u1: Option::from(ProjectivePoint::from_bytes((&encoded).into()))?,
+            u2: v.next()?,
+            u3: v.next()?,
+            s: v.next()?,
+            s1: v.next()?,
+            s2: v.next()?,
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn log_challenge(
+    pk: &PaillierPublicKey,
+    verifier: &RingPedersenParams,
+    ciphertext: &BigUint,
+    base: &ProjectivePoint,
+    public: &ProjectivePoint,
+    z: &BigUint,
+    u1: &ProjectivePoint,
+    u2: &BigUint,
+    u3: &BigUint,
+) -> BigUint {
+    let point_int = |p: &ProjectivePoint| BigUint::from_bytes_be(&p.to_bytes());
+    let digest = transcript_hash(
+        b"quorum/mta/log",
+        &[
+            &pk.n,
+            &verifier.n_tilde,
+            &verifier.h1,
+            &verifier.h2,
+            ciphertext,
+            &point_int(base),
+            &point_int(public),
+            z,
+            &point_int(u1),
+            u2,
+            u3,
+        ],
+    );
+    BigUint::from_bytes_be(&digest) % curve_order()
+}
+
+/// bob's proof that c2 = c1^x * Enc(y; r) with x < q^3
+/// with a public point X it also proves X = g^x ("mta with check")
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RespondentProof {
+    z: BigUint,
+    z_prime: BigUint,
+    t: BigUint,
+    v: BigUint,
+    w: BigUint,
+    s: BigUint,
+    s1: BigUint,
+    s2: BigUint,
+    t1: BigUint,
+    t2: BigUint,
+    // g^alpha, only present in the "with check" variant
+    u: Option<ProjectivePoint>,
+}
+
+/// the inputs bob used for his response
+struct RespondentWitness<'a> {
+    x: &'a BigUint,
+    y: &'a BigUint,
+    r: &'a BigUint,
+}
+
+impl RespondentProof {
+    fn prove(
+        pk: &PaillierPublicKey,
+        verifier: &RingPedersenParams,
+        c1: &BigUint,
+        c2: &BigUint,
+        witness: RespondentWitness,
+        public: Option<&ProjectivePoint>,
+    ) -> Self {
+        let q = curve_order();
+        let q3 = q.pow(3);
+        let n_tilde = &verifier.n_tilde;
+        let nn = pk.n_squared();
+
+        let alpha = OsRng.gen_biguint_below(&q3);
+        let rho = OsRng.gen_biguint_below(&(&q * n_tilde));
+        let rho_prime = OsRng.gen_biguint_below(&(&q3 * n_tilde));
+        let sigma = OsRng.gen_biguint_below(&(&q * n_tilde));
+        let beta = pk.random_unit();
+        let gamma = OsRng.gen_biguint_below(&pk.n);
+        let tau = OsRng.gen_biguint_below(&(&q3 * n_tilde));
+
+        let z = verifier.commit(witness.x, &rho);
+        let z_prime = verifier.commit(&alpha, &rho_prime);
+        let t = verifier.commit(witness.y, &sigma);
+        let v = (c1.modpow(&alpha, nn) * pk.encrypt_with(&gamma, &beta)) % nn;
+        let w = verifier.commit(&gamma, &tau);
+        let u = public.map(|_| ProjectivePoint::GENERATOR * biguint_to_scalar(&alpha));
+
+        let mut proof = Self {
+            z,
+            z_prime,
+            t,
+            v,
+            w,
+            s: BigUint::zero(),
+            s1: BigUint::zero(),
+            s2: BigUint::zero(),
+            t1: BigUint::zero(),
+            t2: BigUint::zero(),
+            u,
+        };
+        let e = proof.challenge(pk, verifier, c1, c2, public);
+
+        proof.s = (witness.r.modpow(&e, &pk.n) * beta) % &pk.n;
+        proof.s1 = &e * witness.x + alpha;
+        proof.s2 = &e * rho + rho_prime;
+        proof.t1 = &e * witness.y + gamma;
+        proof.t2 = &e * sigma + tau;
+        proof
+    }
+
+    /// checks the proof for alice's ciphertext c1 and bob's response c2
+    /// `public` must be given exactly when the proof was made "with check"
+    pub fn verify(
+        &self,
+        pk: &PaillierPublicKey,
+        verifier: &RingPedersenParams,
+        c1: &BigUint,
+        c2: &BigUint,
+        public: Option<&ProjectivePoint>,
+    ) -> bool {
+        let q = curve_order();
+        let n_tilde = &verifier.n_tilde;
+        let nn = pk.n_squared();
+
+        if self.s1 > q.pow(3) || self.u.is_some() != public.is_some() {
+            return false;
+        }
+        if !pk.is_valid_ciphertext(c1) || !pk.is_valid_ciphertext(c2) || !pk.is_valid_ciphertext(&self.v) {
+            return false;
+        }
+
+        let e = self.challenge(pk, verifier, c1, c2, public);
+
+        // h1^s1 * h2^s2 == z^e * z' mod n~
+        let lhs = verifier.commit(&self.s1, &self.s2);
+        let rhs = (self.z.modpow(&e, n_tilde) * &self.z_prime) % n_tilde;
+        if lhs != rhs {
+            return false;
+        }
+
+        // h1^t1 * h2^t2 == t^e * w mod n~
+        let lhs = verifier.commit(&self.t1, &self.t2);
+        let rhs = (self.t.modpow(&e, n_tilde) * &self.w) % n_tilde;
+        if lhs != rhs {
+            return false;
+        }
+
+        // c1^s1 * s^n * (1 + n)^t1 == c2^e * v mod n^2
+        let lhs = (c1.modpow(&self.s1, nn) * pk.encrypt_with(&self.t1, &self.s)) % nn;
+        let rhs = (c2.modpow(&e, nn) * &self.v) % nn;
+        if lhs != rhs {
+            return false;
+        }
+
+        // g^s1 == X^e * u
+        match (public, &self.u) {
+            (Some(x), Some(u)) => {
+                ProjectivePoint::GENERATOR * biguint_to_scalar(&self.s1)
+                    == *x * biguint_to_scalar(&e) + u
+            }
+            _ => true,
+        }
+    }
+
+    fn challenge(
+        &self,
+        pk: &PaillierPublicKey,
+        verifier: &RingPedersenParams,
+        c1: &BigUint,
+        c2: &BigUint,
+        public: Option<&ProjectivePoint>,
+    ) -> BigUint {
+        let point_int = |p: Option<&ProjectivePoint>| {
+            p.map(|p| BigUint::from_bytes_be(&p.to_bytes()))
+                .unwrap_or_default()
+        };
+        let x = point_int(public);
+        let u = point_int(self.u.as_ref());
+
+        let digest = transcript_hash(
+            b"quorum/mta/respondent",
+            &[
+                &pk.n,
+                &verifier.n_tilde,
+                &verifier.h1,
+                &verifier.h2,
+                c1,
+                c2,
+                &x,
+                &u,
+                &self.z,
+                &self.z_prime,
+                &self.t,
+                &self.v,
+                &self.w,
+            ],
+        );
+        BigUint::from_bytes_be(&digest) % curve_order()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let u = self
+            .u
+            .map(|u| BigUint::from_bytes_be(&u.to_bytes()))
+            .unwrap_or_default();
+        encode_biguints(&[
+            &self.z,
+            &self.z_prime,
+            &self.t,
+            &self.v,
+            &self.w,
+            &self.s,
+            &self.s1,
+            &self.s2,
+            &self.t1,
+            &self.t2,
+            &u,
+        ])
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut v = decode_biguints(bytes, 11)?.into_iter();
+        let mut proof = Self {
+            z: v.next()?,
+            z_prime: v.next()?,
+            t: v.next()?,
+            v: v.next()?,
+            w: v.next()?,
+            s: v.next()?,
+            s1: v.next()?,
+            s2: v.next()?,
+            t1: v.next()?,
+            t2: v.next()?,
+            u: None,
+        };
+
+        let u = v.next()?;
+        if !u.is_zero() {
+            let encoded: [u8; 33] = u.to_bytes_be().try_into().ok()?;
+            proof.u = Some(Option::from(ProjectivePoint::from_bytes((&encoded).into()))?);
+        }
+        Some(proof)
+    }
+}
+
+/// bob's answer in an mta exchange
+pub struct MtaResponse {
+    /// c2 = c1^b * Enc(beta'), sent to alice
+    pub ciphertext: BigUint,
+    /// proof that the ciphertext is well formed, sent to alice
+    pub proof: RespondentProof,
+    /// bob's additive share, -beta' mod q
+    pub beta: Scalar,
+}
+
+/// alice's first mta message: encrypts her input a and proves it is small
+pub fn mta_request(
+    pk: &PaillierPublicKey,
+    verifier: &RingPedersenParams,
+    a: &Scalar,
+) -> (BigUint, BigUint, RangeProof) {
+    let m = scalar_to_biguint(a);
+    let (ciphertext, r) = pk.encrypt(&m);
+    let proof = RangeProof::prove(pk, verifier, &ciphertext, &m, &r);
+    (ciphertext, r, proof)
+}
+
+/// bob's mta step: multiplies alice's ciphertext by b and masks it with beta'
+///
+/// - pk, verifier: alice's paillier key and ring-pedersen parameters
+/// - c1: alice's ciphertext of a (its range proof must already be verified)
+/// - b: bob's input
+/// - public: g^b when the result has to be tied to a known public value
+pub fn mta_respond(
+    pk: &PaillierPublicKey,
+    verifier: &RingPedersenParams,
+    c1: &BigUint,
+    b: &Scalar,
+    public: Option<&ProjectivePoint>,
+) -> MtaResponse {
+    let q = curve_order();
+    let x = scalar_to_biguint(b);
+
+    // beta' < q^5 statistically hides a * b < q^2 and can't wrap around n
+    let y = OsRng.gen_biguint_below(&q.pow(5));
+    let (masked, r) = pk.encrypt(&y);
+    let ciphertext = pk.add(&pk.mul(c1, &x), &masked);
+
+    let proof = RespondentProof::prove(
+        pk,
+        verifier,
+        c1,
+        &ciphertext,
+        RespondentWitness { x: &x, y: &y, r: &r },
+        public,
+    );
+
+    MtaResponse {
+        ciphertext,
+        proof,
+        beta: -biguint_to_scalar(&y),
+    }
+}
+
+/// alice's last mta step: decrypts bob's response
+/// returns her share alpha = a * b + beta' mod q and the raw plaintext
+pub fn mta_finish(sk: &PaillierSecretKey, ciphertext: &BigUint) -> (Scalar, BigUint) {
+    let plaintext = sk.decrypt(ciphertext);
+    (biguint_to_scalar(&plaintext), plaintext)
+}
+
+/// checks that a claimed decryption (plaintext, randomness) matches a ciphertext
+pub fn verify_decryption(pk: &PaillierPublicKey, ciphertext: &BigUint, plaintext: &BigUint, randomness: &BigUint) -> bool {
+    plaintext < &pk.n
+        && randomness.gcd(&pk.n).is_one()
+        && pk.encrypt_with(plaintext, randomness) == *ciphertext
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // n has to exceed q^5 * q^2 for the mta to be correct, so 1536 bits is the floor
+    const TEST_BITS: u64 = 1536;
+
+    #[test]
+    fn test_scalar_conversion() {
+        let scalar = Scalar::generate_vartime(&mut OsRng);
+        assert_eq!(biguint_to_scalar(&scalar_to_biguint(&scalar)), scalar);
+        assert_eq!(biguint_to_scalar(&curve_order()), Scalar::ZERO);
+    }
+
+    #[test]
+    fn test_mta_with_proofs() {
+        let alice_sk = PaillierSecretKey::generate(TEST_BITS);
+        let alice_pk = alice_sk.public_key();
+        let (alice_params, _) = RingPedersenParams::generate(TEST_BITS);
+        let (bob_params, _) = RingPedersenParams::generate(TEST_BITS);
+
+        let a = Scalar::generate_vartime(&mut OsRng);
+        let b = Scalar::generate_vartime(&mut OsRng);
+        let b_public = ProjectivePoint::GENERATOR * b;
+
+        // alice proves her input to bob, under bob's parameters
+        let (c1, _, range_proof) = mta_request(alice_pk, &bob_params, &a);
+        assert!(range_proof.verify(alice_pk, &bob_params, &c1));
+        let decoded = RangeProof::from_bytes(&range_proof.to_bytes()).unwrap();
+        assert!(decoded.verify(alice_pk, &bob_params, &c1));
+
+        // bob answers with check, under alice's parameters
+        let response = mta_respond(alice_pk, &alice_params, &c1, &b, Some(&b_public));
+        assert!(response
+            .proof
+            .verify(alice_pk, &alice_params, &c1, &response.ciphertext, Some(&b_public)));
+        let decoded = RespondentProof::from_bytes(&response.proof.to_bytes()).unwrap();
+        assert!(decoded.verify(alice_pk, &alice_params, &c1, &response.ciphertext, Some(&b_public)));
+
+        let (alpha, _) = mta_finish(&alice_sk, &response.ciphertext);
+        assert_eq!(alpha + response.beta, a * b);
+    }
+
+    #[test]
+    fn test_cheating_bob_detected() {
+        let alice_sk = PaillierSecretKey::generate(TEST_BITS);
+        let alice_pk = alice_sk.public_key();
+        let (alice_params, _) = RingPedersenParams::generate(TEST_BITS);
+        let (bob_params, _) = RingPedersenParams::generate(TEST_BITS);
+
+        let a = Scalar::generate_vartime(&mut OsRng);
+        let b = Scalar::generate_vartime(&mut OsRng);
+        let (c1, _, _) = mta_request(alice_pk, &bob_params, &a);
+
+        // bob uses b but claims the public value of b + 1
+        let wrong_public = ProjectivePoint::GENERATOR * (b + Scalar::ONE);
+        let response = mta_respond(alice_pk, &alice_params, &c1, &b, Some(&wrong_public));
+        assert!(!response
+            .proof
+            .verify(alice_pk, &alice_params, &c1, &response.ciphertext, Some(&wrong_public)));
+
+        // bob tampers with the ciphertext after proving
+        let response = mta_respond(alice_pk, &alice_params, &c1, &b, None);
+        let tampered = alice_pk.add(&response.ciphertext, &alice_pk.encrypt(&BigUint::one()).0);
+        assert!(!response.proof.verify(alice_pk, &alice_params, &c1, &tampered, None));
+    }
+
+    #[test]
+    fn test_log_proof() {
+        let alice_sk = PaillierSecretKey::generate(TEST_BITS);
+        let alice_pk = alice_sk.public_key();
+        let (bob_params, _) = RingPedersenParams::generate(TEST_BITS);
+
+        let k = Scalar::generate_vartime(&mut OsRng);
+        let base = ProjectivePoint::GENERATOR * Scalar::generate_vartime(&mut OsRng);
+        let public = base * k;
+        let m = scalar_to_biguint(&k);
+        let (c, r) = alice_pk.encrypt(&m);
+
+        let proof = LogProof::prove(alice_pk, &bob_params, &c, &base, &public, &m, &r);
+        assert!(proof.verify(alice_pk, &bob_params, &c, &base, &public));
+        let decoded = LogProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(decoded.verify(alice_pk, &bob_params, &c, &base, &public));
+
+        // a point for a different value than the ciphertext holds fails
+        let shifted = public + base;
+        let proof = LogProof::prove(alice_pk, &bob_params, &c, &base, &shifted, &m, &r);
+        assert!(!proof.verify(alice_pk, &bob_params, &c, &base, &shifted));
+    }
+
+    #[test]
+    fn test_oversized_input_rejected() {
+        let alice_sk = PaillierSecretKey::generate(TEST_BITS);
+        let alice_pk = alice_sk.public_key();
+        let (bob_params, _) = RingPedersenParams::generate(TEST_BITS);
+
+        // an input far above q^3 can't produce a valid range proof
+        let m = curve_order().pow(4);
+        let (c, r) = alice_pk.encrypt(&m);
+        let proof = RangeProof::prove(alice_pk, &bob_params, &c, &m, &r);
+        assert!(!proof.verify(alice_pk, &bob_params, &c));
+
+        // and a verified decryption has to match the ciphertext
+        let plaintext = alice_sk.decrypt(&c);
+        let randomness = alice_sk.recover_randomness(&c, &plaintext);
+        assert!(verify_decryption(alice_pk, &c, &plaintext, &randomness));
+        assert!(!verify_decryption(alice_pk, &c, &(plaintext + 1u32), &randomness));
+    }
+}
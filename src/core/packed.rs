@@ -0,0 +1,139 @@
+// packed shamir's secret sharing
+// embeds a batch of k secrets in one polynomial of degree t + k - 1, at the points
+// 0, -1, ..., -(k - 1), so a single share per node covers the whole batch. up to t
+// shares reveal nothing about any secret, t + k shares recover all of them. the
+// price is the gap between the two: t + 1 to t + k - 1 shares leak partial
+// information, so t is the number of nodes that may collude, not the recovery threshold
+
+use k256::Scalar;
+
+use crate::core::math::{lagrange_interpolate_many, random_scalar, validate_indices};
+use crate::core::scheme::{FragmentError, SecretShare};
+
+/// one node's share of a packed batch
+#[derive(Debug, Clone)]
+pub struct PackedShare {
+    /// how many secrets the polynomial carries
+    pub secrets: u32,
+    /// how many shares can be combined without learning anything
+    pub privacy_threshold: u32,
+    pub share: SecretShare,
+}
+
+impl PackedShare {
+    /// the number of shares needed to recover the batch
+    pub fn threshold(&self) -> usize {
+        (self.secrets + self.privacy_threshold) as usize
+    }
+}
+
+/// the points the secrets sit at: 0, -1, ..., -(count - 1)
+/// shares are handed out at positive x, so the two never meet
+fn secret_points(count: usize) -> Vec<Scalar> {
+    (0..count as u64).map(|i| -Scalar::from(i)).collect()
+}
+
+/// packs a batch of secrets into shares of one polynomial
+///
+/// - secrets: the batch, at least one
+/// - privacy_threshold: how many shares may be seen together without leaking anything
+/// - total_shares: total number of shares, at least privacy_threshold + secrets.len()
+///
+/// returns shares at x = 1, 2, 3, ...
+pub fn split_secrets_packed(
+    secrets: &[Scalar],
+    privacy_threshold: usize,
+    total_shares: usize,
+) -> Result<Vec<PackedShare>, FragmentError> {
+    if secrets.is_empty() {
+        return Err(FragmentError::MalformedShare("nothing to pack".to_string()));
+    }
+    if privacy_threshold == 0 || privacy_threshold + secrets.len() > total_shares {
+        return Err(FragmentError::InvalidThreshold);
+    }
+
+    // the secrets plus privacy_threshold random points fix the polynomial
+    let degree_bound = secrets.len() + privacy_threshold;
+    let anchors: Vec<(Scalar, Scalar)> = secret_points(degree_bound)
+        .into_iter()
+        .zip(secrets.iter().copied().chain(std::iter::repeat_with(random_scalar)))
+        .collect();
+
+    let xs: Vec<Scalar> = (1..=total_shares as u64).map(Scalar::from).collect();
+    let ys = lagrange_interpolate_many(&anchors, &xs)?;
+
+    Ok(xs
+        .into_iter()
+        .zip(ys)
+        .map(|(x, y)| PackedShare {
+            secrets: secrets.len() as u32,
+            privacy_threshold: privacy_threshold as u32,
+            share: SecretShare::new(x, y),
+        })
+        .collect())
+}
+
+/// recovers every secret of a packed batch from at least threshold shares
+pub fn recover_secrets_packed(shares: &[PackedShare]) -> Result<Vec<Scalar>, FragmentError> {
+    let first = shares.first().ok_or(FragmentError::InsufficientShares)?;
+    if shares
+        .iter()
+        .any(|share| share.secrets != first.secrets || share.privacy_threshold != first.privacy_threshold)
+    {
+        return Err(FragmentError::MixedShareSets);
+    }
+    if first.secrets == 0 || shares.len() < first.threshold() {
+        return Err(FragmentError::InsufficientShares);
+    }
+    let xs: Vec<Scalar> = shares.iter().map(|share| share.share.x).collect();
+    validate_indices(&xs)?;
+
+    let points: Vec<(Scalar, Scalar)> = shares
+        .iter()
+        .map(|share| (share.share.x, *share.share.y.expose_secret()))
+        .collect();
+    lagrange_interpolate_many(&points, &secret_points(first.secrets as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_batch_of_keys_in_one_share() {
+        let mut rng = OsRng;
+        let secrets: Vec<Scalar> = (0..4).map(|_| Scalar::generate_vartime(&mut rng)).collect();
+
+        // 2 colluding nodes learn nothing, any 6 of 8 recover the whole batch
+        let shares = split_secrets_packed(&secrets, 2, 8).unwrap();
+        assert_eq!(shares.len(), 8);
+        assert_eq!(shares[0].threshold(), 6);
+
+        assert_eq!(recover_secrets_packed(&shares[..6]).unwrap(), secrets);
+        assert_eq!(recover_secrets_packed(&shares[2..]).unwrap(), secrets);
+        assert_eq!(recover_secrets_packed(&shares).unwrap(), secrets);
+    }
+
+    #[test]
+    fn test_packed_recovery_checks() {
+        let secrets = vec![Scalar::from(1u64), Scalar::from(2u64)];
+        let shares = split_secrets_packed(&secrets, 1, 4).unwrap();
+
+        assert!(matches!(
+            recover_secrets_packed(&shares[..2]),
+            Err(FragmentError::InsufficientShares)
+        ));
+
+        let other = split_secrets_packed(&secrets, 2, 4).unwrap();
+        let mixed = vec![shares[0].clone(), shares[1].clone(), other[2].clone()];
+        assert!(matches!(recover_secrets_packed(&mixed), Err(FragmentError::MixedShareSets)));
+
+        let repeated = vec![shares[0].clone(), shares[1].clone(), shares[1].clone()];
+        assert!(matches!(recover_secrets_packed(&repeated), Err(FragmentError::DuplicateShare(_))));
+
+        assert!(split_secrets_packed(&secrets, 0, 4).is_err());
+        assert!(split_secrets_packed(&secrets, 3, 4).is_err());
+        assert!(split_secrets_packed(&[], 1, 4).is_err());
+    }
+}
@@ -0,0 +1,889 @@
+// paillier encryption and ring-pedersen parameters for threshold ecdsa
+// paillier is additively homomorphic: Enc(a) * Enc(b) = Enc(a + b) and Enc(a)^k = Enc(k * a)
+//
+// a signer's moduli come with the proofs of canetti-gennaro-goldfeder-makriyannis-peled 2021
+// (cggmp21): the paillier modulus is a product of two blum primes (pi-mod) with no small
+// factor (pi-fac), and the ring-pedersen generators generate each other (pi-prm). without
+// them a signer can pick a modulus with small factors and pull other signers' key shares
+// out of the mta range proofs (the bitforge attack, cve-2023-33241)
+
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive, Zero};
+use rand::rngs::OsRng;
+use std::sync::OnceLock;
+
+use k256::sha2::{Digest, Sha256};
+
+/// modulus size used by the custodian nodes
+pub const PAILLIER_BITS: u64 = 2048;
+
+/// smallest modulus accepted from a peer
+/// mta plaintexts reach q^5 + q^2, which has to fit below n without wrapping
+pub const MIN_PAILLIER_BITS: u64 = 1536;
+
+// miller-rabin rounds for prime generation, error probability at most 4^-40
+const MILLER_RABIN_ROUNDS: usize = 40;
+
+// rounds of the binary-challenge proofs (pi-mod and pi-prm), soundness error 2^-80
+const PROOF_ROUNDS: usize = 80;
+
+// size of the factor proof's challenge, the size of the curve order it protects
+const CHALLENGE_BITS: u64 = 256;
+
+// slack that lets the factor proof's masks statistically hide p and q
+const SLACK_BITS: u64 = 2 * CHALLENGE_BITS;
+
+// how far a safe prime search walks from its random starting point
+const SAFE_PRIME_WINDOW: u64 = 1 << 16;
+
+// odd primes for trial division before the expensive tests
+const SMALL_PRIMES: [u32; 53] = [
+    3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251,
+];
+
+/// a paillier public key with generator n + 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaillierPublicKey {
+    pub n: BigUint,
+    nn: BigUint,
+}
+
+/// a paillier secret key, the factorization of n into two blum primes
+#[derive(Clone)]
+pub struct PaillierSecretKey {
+    public: PaillierPublicKey,
+    p: BigUint,
+    q: BigUint,
+    phi: BigUint,
+    // phi^-1 mod n, precomputed for decryption
+    mu: BigUint,
+}
+
+impl PaillierPublicKey {
+    /// wraps a modulus received from another party
+    pub fn new(n: BigUint) -> Self {
+        let nn = &n * &n;
+        Self { n, nn }
+    }
+
+    /// n^2, the ciphertext modulus
+    pub fn n_squared(&self) -> &BigUint {
+        &self.nn
+    }
+
+    /// samples a random unit r in Z*_n
+    pub fn random_unit(&self) -> BigUint {
+        loop {
+            let r = OsRng.gen_biguint_below(&self.n);
+            if !r.is_zero() && r.gcd(&self.n).is_one() {
+                return r;
+            }
+        }
+    }
+
+    /// encrypts m with fresh randomness, returns the ciphertext and the randomness used
+    pub fn encrypt(&self, m: &BigUint) -> (BigUint, BigUint) {
+        let r = self.random_unit();
+        (self.encrypt_with(m, &r), r)
+    }
+
+    /// encrypts m with the given randomness: (1 + n)^m * r^n mod n^2
+    pub fn encrypt_with(&self, m: &BigUint, r: &BigUint) -> BigUint {
+        // (1 + n)^m = 1 + m * n mod n^2, which saves an exponentiation
+        let gm = (BigUint::one() + (m % &self.n) * &self.n) % &self.nn;
+        (gm * r.modpow(&self.n, &self.nn)) % &self.nn
+    }
+
+    /// homomorphic addition of the plaintexts
+    pub fn add(&self, c1: &BigUint, c2: &BigUint) -> BigUint {
+        (c1 * c2) % &self.nn
+    }
+
+    /// homomorphic multiplication of the plaintext by k
+    pub fn mul(&self, c: &BigUint, k: &BigUint) -> BigUint {
+        c.modpow(k, &self.nn)
+    }
+
+    /// checks that a ciphertext is a unit mod n^2
+    pub fn is_valid_ciphertext(&self, c: &BigUint) -> bool {
+        !c.is_zero() && c < &self.nn && c.gcd(&self.n).is_one()
+    }
+}
+
+impl PaillierSecretKey {
+    /// generates a key with an n of the given bit size
+    /// both primes are 3 mod 4, which the modulus proof relies on
+    pub fn generate(bits: u64) -> Self {
+        loop {
+            let p = generate_blum_prime(bits / 2);
+            let q = generate_blum_prime(bits / 2);
+            if p == q {
+                continue;
+            }
+
+            let n = &p * &q;
+            let phi = (&p - 1u32) * (&q - 1u32);
+            if let Some(mu) = phi.modinv(&n) {
+                return Self {
+                    public: PaillierPublicKey::new(n),
+                    p,
+                    q,
+                    phi,
+                    mu,
+                };
+            }
+        }
+    }
+
+    pub fn public_key(&self) -> &PaillierPublicKey {
+        &self.public
+    }
+
+    /// decrypts a ciphertext: L(c^phi mod n^2) * phi^-1 mod n, with L(u) = (u - 1) / n
+    pub fn decrypt(&self, c: &BigUint) -> BigUint {
+        let n = &self.public.n;
+        let u = c.modpow(&self.phi, &self.public.nn);
+        let l = (u - 1u32) / n;
+        (l * &self.mu) % n
+    }
+
+    /// recovers the randomness r of a ciphertext with known plaintext m
+    /// lets the key holder prove a decryption publicly by revealing (m, r)
+    pub fn recover_randomness(&self, c: &BigUint, m: &BigUint) -> BigUint {
+        let n = &self.public.n;
+        let nn = &self.public.nn;
+
+        // c * (1 + n)^-m = r^n mod n^2, and r = (r^n mod n)^(n^-1 mod phi) mod n
+        let gm_inv = (BigUint::one() + (n - (m % n)) * n) % nn;
+        let rn = (c * gm_inv) % nn % n;
+        let n_inv = n.modinv(&self.phi).expect("n is coprime to phi for paillier moduli");
+        rn.modpow(&n_inv, n)
+    }
+
+    /// proves that n is a product of two blum primes (pi-mod)
+    ///
+    /// for every challenge y the prover shows an n-th root of y, which only exists for all y
+    /// when n is coprime to phi(n), and a fourth root of y times -1 and w as needed, which
+    /// only exists for all y when n = p^a * q^b with p = q = 3 mod 4
+    pub fn prove_modulus(&self) -> ModulusProof {
+        let n = &self.public.n;
+        let n_inv = n.modinv(&self.phi).expect("n is coprime to phi for paillier moduli");
+
+        'proof: loop {
+            // w is a non-residue mod exactly one of the primes, -1 is one mod both of them
+            let w = loop {
+                let w = OsRng.gen_biguint_below(n);
+                if jacobi(&w, n) == -1 {
+                    break w;
+                }
+            };
+
+            let mut rounds = Vec::with_capacity(PROOF_ROUNDS);
+            for i in 0..PROOF_ROUNDS {
+                let y = modulus_challenge(n, &w, i);
+                // exactly one of y, -y, w * y and -w * y is a residue mod both primes,
+                // unless y shares a factor with n, which a fresh w avoids
+                let Some((a, b, residue)) = [(false, false), (true, false), (false, true), (true, true)]
+                    .into_iter()
+                    .map(|(a, b)| (a, b, twist(n, &w, &y, a, b)))
+                    .find(|(_, _, v)| is_residue(v, &self.p) && is_residue(v, &self.q))
+                else {
+                    continue 'proof;
+                };
+
+                rounds.push(ModulusRound {
+                    x: self.fourth_root(&residue),
+                    a,
+                    b,
+                    z: y.modpow(&n_inv, n),
+                });
+            }
+            return ModulusProof { w, rounds };
+        }
+    }
+
+    /// proves that neither factor of n is much smaller than sqrt(n) (pi-fac)
+    /// made against the verifier's ring-pedersen parameters, which hide p and q from it
+    pub fn prove_factors(&self, verifier: &RingPedersenParams) -> FactorProof {
+        let n0 = &self.public.n;
+        let n_hat = &verifier.n_tilde;
+        let sqrt_n0 = n0.sqrt();
+
+        // masks for p and q, and for their commitments' randomness
+        let alpha = OsRng.gen_biguint_below(&(&sqrt_n0 << (CHALLENGE_BITS + SLACK_BITS)));
+        let beta = OsRng.gen_biguint_below(&(&sqrt_n0 << (CHALLENGE_BITS + SLACK_BITS)));
+        let mu = OsRng.gen_biguint_below(&(n_hat << CHALLENGE_BITS));
+        let nu = OsRng.gen_biguint_below(&(n_hat << CHALLENGE_BITS));
+        let sigma_hat = OsRng.gen_biguint_below(&((n0 * n_hat) << CHALLENGE_BITS));
+        let r = OsRng.gen_biguint_below(&((n0 * n_hat) << (CHALLENGE_BITS + SLACK_BITS)));
+        let x = OsRng.gen_biguint_below(&(n_hat << (CHALLENGE_BITS + SLACK_BITS)));
+        let y = OsRng.gen_biguint_below(&(n_hat << (CHALLENGE_BITS + SLACK_BITS)));
+
+        // sigma = sigma_hat + nu * p, so R = h1^n0 * h2^sigma = Q^p * h2^sigma_hat
+        let sigma = &sigma_hat + &nu * &self.p;
+        let big_p = verifier.commit(&self.p, &mu);
+        let big_q = verifier.commit(&self.q, &nu);
+        let a = verifier.commit(&alpha, &x);
+        let b = verifier.commit(&beta, &y);
+        let t = (big_q.modpow(&alpha, n_hat) * verifier.h2.modpow(&r, n_hat)) % n_hat;
+
+        let e = factor_challenge(n0, verifier, &[&big_p, &big_q, &a, &b, &t, &sigma]);
+        FactorProof {
+            z1: alpha + &e * &self.p,
+            z2: beta + &e * &self.q,
+            w1: x + &e * mu,
+            w2: y + &e * &nu,
+            v: r + &e * sigma_hat,
+            big_p,
+            big_q,
+            a,
+            b,
+            t,
+            sigma,
+        }
+    }
+
+    /// a fourth root of a residue mod both primes, through the roots mod p and q
+    fn fourth_root(&self, residue: &BigUint) -> BigUint {
+        // for p = 3 mod 4, v^((p + 1) / 4) is the square root of v that is itself a residue
+        let root = |prime: &BigUint| {
+            let e = (prime + 1u32) >> 2;
+            residue.modpow(&(&e * &e), prime)
+        };
+        let (xp, xq) = (root(&self.p), root(&self.q));
+        let p_inv = self.p.modinv(&self.q).expect("distinct primes are coprime");
+        let h = ((&xq + &self.q - &xp % &self.q) * p_inv) % &self.q;
+        xp + h * &self.p
+    }
+}
+
+/// proof that a paillier modulus is a product of two blum primes (pi-mod of cggmp21)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModulusProof {
+    w: BigUint,
+    rounds: Vec<ModulusRound>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ModulusRound {
+    // fourth root of (-1)^a * w^b * y
+    x: BigUint,
+    a: bool,
+    b: bool,
+    // n-th root of y
+    z: BigUint,
+}
+
+impl ModulusProof {
+    /// checks the proof for the modulus n
+    pub fn verify(&self, n: &BigUint) -> bool {
+        if n.is_even() || is_probable_prime(n) || self.rounds.len() != PROOF_ROUNDS {
+            return false;
+        }
+        if self.w >= *n || jacobi(&self.w, n) != -1 {
+            return false;
+        }
+
+        self.rounds.iter().enumerate().all(|(i, round)| {
+            let y = modulus_challenge(n, &self.w, i);
+            round.z < *n
+                && round.x < *n
+                && round.z.modpow(n, n) == y
+                && round.x.modpow(&BigUint::from(4u32), n) == twist(n, &self.w, &y, round.a, round.b)
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let flags: Vec<BigUint> = self
+            .rounds
+            .iter()
+            .map(|round| BigUint::from(round.a as u32 | (round.b as u32) << 1))
+            .collect();
+        let mut values = vec![&self.w];
+        for (round, flag) in self.rounds.iter().zip(&flags) {
+            values.extend([&round.x, flag, &round.z]);
+        }
+        encode_biguints(&values)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut values = decode_biguints(bytes, 1 + 3 * PROOF_ROUNDS)?.into_iter();
+        let w = values.next()?;
+        let mut rounds = Vec::with_capacity(PROOF_ROUNDS);
+        for _ in 0..PROOF_ROUNDS {
+            let x = values.next()?;
+            let flag = values.next()?;
+            if flag > BigUint::from(3u32) {
+                return None;
+            }
+            rounds.push(ModulusRound {
+                x,
+                a: flag.bit(0),
+                b: flag.bit(1),
+                z: values.next()?,
+            });
+        }
+        Some(Self { w, rounds })
+    }
+}
+
+/// (-1)^a * w^b * y mod n
+fn twist(n: &BigUint, w: &BigUint, y: &BigUint, a: bool, b: bool) -> BigUint {
+    let v = if b { (w * y) % n } else { y % n };
+    if a && !v.is_zero() {
+        n - v
+    } else {
+        v
+    }
+}
+
+/// the i-th challenge of a modulus proof, an element of Z_n
+fn modulus_challenge(n: &BigUint, w: &BigUint, i: usize) -> BigUint {
+    hash_to_range(b"quorum/paillier/modulus", &[n, w, &BigUint::from(i)], n)
+}
+
+/// proof that the factors of a paillier modulus n0 are both close to sqrt(n0)
+/// (pi-fac of cggmp21), made against the verifier's ring-pedersen parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FactorProof {
+    big_p: BigUint,
+    big_q: BigUint,
+    a: BigUint,
+    b: BigUint,
+    t: BigUint,
+    sigma: BigUint,
+    z1: BigUint,
+    z2: BigUint,
+    w1: BigUint,
+    w2: BigUint,
+    v: BigUint,
+}
+
+impl FactorProof {
+    /// checks the proof for n0 under our own ring-pedersen parameters
+    pub fn verify(&self, n0: &BigUint, verifier: &RingPedersenParams) -> bool {
+        let n_hat = &verifier.n_tilde;
+
+        // z1 and z2 mask e * p and e * q, so they bound both factors by about
+        // 2^(challenge + slack) * sqrt(n0), and then neither can be small
+        let bound = (n0.sqrt() + 1u32) << (CHALLENGE_BITS + SLACK_BITS + 1);
+        if self.z1 > bound || self.z2 > bound {
+            return false;
+        }
+        let commitments = [&self.big_p, &self.big_q, &self.a, &self.b, &self.t];
+        if commitments.iter().any(|c| *c >= n_hat || !c.gcd(n_hat).is_one()) {
+            return false;
+        }
+
+        let e = factor_challenge(n0, verifier, &[&self.big_p, &self.big_q, &self.a, &self.b, &self.t, &self.sigma]);
+        let big_r = verifier.commit(n0, &self.sigma);
+
+        // h1^z1 * h2^w1 == A * P^e, h1^z2 * h2^w2 == B * Q^e, Q^z1 * h2^v == T * R^e
+        verifier.commit(&self.z1, &self.w1) == (&self.a * self.big_p.modpow(&e, n_hat)) % n_hat
+            && verifier.commit(&self.z2, &self.w2) == (&self.b * self.big_q.modpow(&e, n_hat)) % n_hat
+            && (self.big_q.modpow(&self.z1, n_hat) * verifier.h2.modpow(&self.v, n_hat)) % n_hat
+                == (&self.t * big_r.modpow(&e, n_hat)) % n_hat
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_biguints(&[
+            &self.big_p,
+            &self.big_q,
+            &self.a,
+            &self.b,
+            &self.t,
+            &self.sigma,
+            &self.z1,
+            &self.z2,
+            &self.w1,
+            &self.w2,
+            &self.v,
+        ])
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut v = decode_biguints(bytes, 11)?.into_iter();
+        Some(Self {
+            big_p: v.next()?,
+            big_q: v.next()?,
+            a: v.next()?,
+            b: v.next()?,
+            t: v.next()?,
+            sigma: v.next()?,
+            z1: v.next()?,
+            z2: v.next()?,
+            w1: v.next()?,
+            w2: v.next()?,
+            v: v.next()?,
+        })
+    }
+}
+
+fn factor_challenge(n0: &BigUint, verifier: &RingPedersenParams, values: &[&BigUint]) -> BigUint {
+    let mut transcript = vec![n0, &verifier.n_tilde, &verifier.h1, &verifier.h2];
+    transcript.extend_from_slice(values);
+    BigUint::from_bytes_be(&transcript_hash(b"quorum/paillier/factors", &transcript))
+}
+
+/// ring-pedersen parameters (n~, h1, h2) used as the verifier's commitment scheme in range proofs
+/// n~ is a product of two safe primes, h2 = h1^lambda, and nobody but the owner knows lambda
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RingPedersenParams {
+    pub n_tilde: BigUint,
+    pub h1: BigUint,
+    pub h2: BigUint,
+}
+
+/// proof that h2 is in the group generated by h1 (pi-prm of cggmp21)
+/// every round has a one-bit challenge, so a prover that doesn't know log_h1(h2)
+/// gets caught with probability 1/2 per round
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RingPedersenProof {
+    commitments: Vec<BigUint>,
+    responses: Vec<BigUint>,
+}
+
+impl RingPedersenParams {
+    /// generates fresh parameters with a modulus of the given bit size, plus a proof of validity
+    ///
+    /// with safe primes p = 2p' + 1 and q = 2q' + 1 the squares mod n~ form a group of order
+    /// p' * q' with no small subgroups, so a random square h1 generates it with
+    /// overwhelming probability
+    pub fn generate(bits: u64) -> (Self, RingPedersenProof) {
+        loop {
+            let p = generate_safe_prime(bits / 2);
+            let q = generate_safe_prime(bits / 2);
+            if p == q {
+                continue;
+            }
+
+            let n_tilde = &p * &q;
+            let phi = (&p - 1u32) * (&q - 1u32);
+
+            // h1 is a random quadratic residue, h2 = h1^lambda
+            let r = OsRng.gen_biguint_below(&n_tilde);
+            let h1 = r.modpow(&BigUint::from(2u32), &n_tilde);
+            let lambda = OsRng.gen_biguint_below(&phi);
+            let h2 = h1.modpow(&lambda, &n_tilde);
+            if h1.is_one() || h2.is_one() || h1 == h2 || !h1.gcd(&n_tilde).is_one() {
+                continue;
+            }
+
+            let params = Self { n_tilde, h1, h2 };
+            let proof = params.prove(&lambda, &phi);
+            return (params, proof);
+        }
+    }
+
+    fn prove(&self, lambda: &BigUint, phi: &BigUint) -> RingPedersenProof {
+        let masks: Vec<BigUint> = (0..PROOF_ROUNDS).map(|_| OsRng.gen_biguint_below(phi)).collect();
+        let commitments: Vec<BigUint> = masks.iter().map(|a| self.h1.modpow(a, &self.n_tilde)).collect();
+        let challenge = self.challenge(&commitments);
+
+        let responses = masks
+            .into_iter()
+            .enumerate()
+            .map(|(i, a)| if challenge_bit(&challenge, i) { (a + lambda) % phi } else { a })
+            .collect();
+        RingPedersenProof {
+            commitments,
+            responses,
+        }
+    }
+
+    /// checks the proof that h2 is a power of h1
+    pub fn verify(&self, proof: &RingPedersenProof) -> bool {
+        let n = &self.n_tilde;
+        if n.is_even() || self.h1 >= *n || self.h2 >= *n || !self.h1.gcd(n).is_one() || !self.h2.gcd(n).is_one() {
+            return false;
+        }
+        if proof.commitments.len() != PROOF_ROUNDS || proof.responses.len() != PROOF_ROUNDS {
+            return false;
+        }
+
+        // h1^z == a * h2^e mod n~ for every round
+        let challenge = self.challenge(&proof.commitments);
+        proof.commitments.iter().zip(&proof.responses).enumerate().all(|(i, (a, z))| {
+            let expected = if challenge_bit(&challenge, i) { (a * &self.h2) % n } else { a % n };
+            self.h1.modpow(z, n) == expected
+        })
+    }
+
+    /// commits to x with randomness r: h1^x * h2^r mod n~
+    pub fn commit(&self, x: &BigUint, r: &BigUint) -> BigUint {
+        (self.h1.modpow(x, &self.n_tilde) * self.h2.modpow(r, &self.n_tilde)) % &self.n_tilde
+    }
+
+    fn challenge(&self, commitments: &[BigUint]) -> [u8; 32] {
+        let mut transcript = vec![&self.n_tilde, &self.h1, &self.h2];
+        transcript.extend(commitments);
+        transcript_hash(b"quorum/paillier/ring-pedersen", &transcript)
+    }
+}
+
+impl RingPedersenProof {
+    /// serializes the proof for transmission
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let values: Vec<&BigUint> = self.commitments.iter().chain(&self.responses).collect();
+        encode_biguints(&values)
+    }
+
+    /// parses a proof produced by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut values = decode_biguints(bytes, 2 * PROOF_ROUNDS)?;
+        let responses = values.split_off(PROOF_ROUNDS);
+        Some(Self {
+            commitments: values,
+            responses,
+        })
+    }
+}
+
+/// the i-th bit of a fiat-shamir challenge
+fn challenge_bit(challenge: &[u8; 32], i: usize) -> bool {
+    challenge[i / 8] >> (i % 8) & 1 == 1
+}
+
+/// generates a random prime with exactly the given number of bits
+pub fn generate_prime(bits: u64) -> BigUint {
+    loop {
+        // forcing the top two bits keeps products of two primes at full size
+        let mut candidate = OsRng.gen_biguint(bits);
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(bits - 2, true);
+        candidate.set_bit(0, true);
+
+        if is_probable_prime(&candidate) {
+            return candidate;
+        }
+    }
+}
+
+/// generates a random prime p = 3 mod 4 with exactly the given number of bits
+pub fn generate_blum_prime(bits: u64) -> BigUint {
+    loop {
+        let mut candidate = OsRng.gen_biguint(bits);
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(bits - 2, true);
+        candidate.set_bit(1, true);
+        candidate.set_bit(0, true);
+
+        if is_probable_prime(&candidate) {
+            return candidate;
+        }
+    }
+}
+
+/// generates a random safe prime p = 2p' + 1, with p' prime, of exactly the given number of bits
+///
+/// safe primes are rare, so this walks p' from a random odd start and sieves out every
+/// offset where p' or p has a factor below 2^14 before running any primality test
+pub fn generate_safe_prime(bits: u64) -> BigUint {
+    let primes = sieve_primes();
+    loop {
+        let mut start = OsRng.gen_biguint(bits - 1);
+        start.set_bit(bits - 2, true);
+        start.set_bit(bits - 3, true);
+        start.set_bit(0, true);
+        let residues: Vec<u64> = primes
+            .iter()
+            .map(|r| (&start % *r).to_u64().expect("residue is below r"))
+            .collect();
+
+        for offset in (0..SAFE_PRIME_WINDOW).step_by(2) {
+            // 2p' + 1 is divisible by r exactly when p' = (r - 1) / 2 mod r
+            let sieved = primes.iter().zip(&residues).any(|(r, rest)| {
+                let rest = (rest + offset) % *r as u64;
+                rest == 0 || rest == (*r as u64 - 1) / 2
+            });
+            if sieved {
+                continue;
+            }
+
+            let half = &start + offset;
+            if half.bits() != bits - 1 {
+                break;
+            }
+            if !is_probable_prime(&half) {
+                continue;
+            }
+            let candidate = (half << 1u32) + 1u32;
+            if is_probable_prime(&candidate) {
+                return candidate;
+            }
+        }
+    }
+}
+
+/// the odd primes below 2^14, for sieving safe prime candidates
+fn sieve_primes() -> &'static [u32] {
+    static PRIMES: OnceLock<Vec<u32>> = OnceLock::new();
+    PRIMES.get_or_init(|| {
+        const BOUND: usize = 1 << 14;
+        let mut composite = vec![false; BOUND];
+        let mut primes = Vec::new();
+        for i in (3..BOUND).step_by(2) {
+            if !composite[i] {
+                primes.push(i as u32);
+                for j in (i * i..BOUND).step_by(2 * i) {
+                    composite[j] = true;
+                }
+            }
+        }
+        primes
+    })
+}
+
+/// trial division by small primes followed by miller-rabin
+pub fn is_probable_prime(n: &BigUint) -> bool {
+    if *n < BigUint::from(2u32) {
+        return false;
+    }
+    if n.is_even() {
+        return *n == BigUint::from(2u32);
+    }
+    for p in SMALL_PRIMES {
+        let p = BigUint::from(p);
+        if *n == p {
+            return true;
+        }
+        if (n % &p).is_zero() {
+            return false;
+        }
+    }
+
+    // writing n - 1 = d * 2^s with d odd
+    let n_minus_one = n - 1u32;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+    let two = BigUint::from(2u32);
+
+    'witness: for _ in 0..MILLER_RABIN_ROUNDS {
+        let a = OsRng.gen_biguint_range(&two, &n_minus_one);
+        let mut x = a.modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+
+    true
+}
+
+/// the jacobi symbol (a / n) for odd n, 0 when a and n share a factor
+pub fn jacobi(a: &BigUint, n: &BigUint) -> i8 {
+    let mut a = a % n;
+    let mut n = n.clone();
+    let mut result = 1;
+
+    while !a.is_zero() {
+        // (2 / n) = -1 exactly when n = 3 or 5 mod 8
+        let twos = a.trailing_zeros().unwrap_or(0);
+        a >>= twos;
+        let n_mod_8 = (&n % 8u32).to_u32().expect("residue is below 8");
+        if twos % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+            result = -result;
+        }
+
+        // quadratic reciprocity flips the sign when both are 3 mod 4
+        std::mem::swap(&mut a, &mut n);
+        if a.bit(1) && n.bit(1) {
+            result = -result;
+        }
+        a %= &n;
+    }
+
+    if n.is_one() {
+        result
+    } else {
+        0
+    }
+}
+
+/// euler's criterion: v is a non-zero square mod the odd prime p
+fn is_residue(v: &BigUint, p: &BigUint) -> bool {
+    v.modpow(&((p - 1u32) >> 1), p).is_one()
+}
+
+/// hashes a domain label and a list of integers to an almost uniform element of [0, modulus)
+/// the digest is stretched to 128 bits more than the modulus before reducing
+fn hash_to_range(label: &[u8], values: &[&BigUint], modulus: &BigUint) -> BigUint {
+    let blocks = (modulus.bits() + 128).div_ceil(256);
+    let mut bytes = Vec::with_capacity(blocks as usize * 32);
+    for block in 0..blocks {
+        let block = BigUint::from(block);
+        let mut transcript = values.to_vec();
+        transcript.push(&block);
+        bytes.extend_from_slice(&transcript_hash(label, &transcript));
+    }
+    BigUint::from_bytes_be(&bytes) % modulus
+}
+
+/// hashes a domain label and a list of integers into a fiat-shamir challenge
+/// every integer is length-prefixed so different splits can't collide
+pub(crate) fn transcript_hash(label: &[u8], values: &[&BigUint]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(label);
+    for value in values {
+        let bytes = value.to_bytes_be();
+        hasher.update((bytes.len() as u32).to_be_bytes());
+        hasher.update(&bytes);
+    }
+    hasher.finalize().into()
+}
+
+/// encodes a list of integers as length-prefixed big-endian byte strings
+pub(crate) fn encode_biguints(values: &[&BigUint]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for value in values {
+        let bytes = value.to_bytes_be();
+        out.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+        out.extend_from_slice(&bytes);
+    }
+    out
+}
+
+/// decodes exactly `count` integers written by `encode_biguints`
+pub(crate) fn decode_biguints(mut bytes: &[u8], count: usize) -> Option<Vec<BigUint>> {
+    let mut values = Vec::with_capacity(count);
+    for _ in 0..count {
+        let len = u32::from_be_bytes(bytes.get(..4)?.try_into().ok()?) as usize;
+        let value = bytes.get(4..4 + len)?;
+        values.push(BigUint::from_bytes_be(value));
+        bytes = &bytes[4 + len..];
+    }
+    bytes.is_empty().then_some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // small moduli keep the tests fast, the arithmetic is the same
+    const TEST_BITS: u64 = 512;
+
+    #[test]
+    fn test_primality() {
+        assert!(is_probable_prime(&BigUint::from(2u32)));
+        assert!(is_probable_prime(&BigUint::from(7919u32)));
+        assert!(!is_probable_prime(&BigUint::from(7917u32)));
+        // 2^127 - 1 is a mersenne prime, 2^128 + 1 is not prime
+        assert!(is_probable_prime(&((BigUint::one() << 127u32) - 1u32)));
+        assert!(!is_probable_prime(&((BigUint::one() << 128u32) + 1u32)));
+
+        let p = generate_prime(128);
+        assert_eq!(p.bits(), 128);
+        assert!(is_probable_prime(&p));
+
+        let p = generate_blum_prime(128);
+        assert!(is_probable_prime(&p) && p.bit(1));
+
+        let p = generate_safe_prime(128);
+        assert_eq!(p.bits(), 128);
+        assert!(is_probable_prime(&p) && is_probable_prime(&(p >> 1u32)));
+    }
+
+    #[test]
+    fn test_jacobi_symbol() {
+        // for a prime the jacobi symbol is the legendre symbol
+        let p = BigUint::from(7919u32);
+        for a in [2u32, 3, 5, 1234, 7918] {
+            let a = BigUint::from(a);
+            assert_eq!(jacobi(&a, &p) == 1, is_residue(&a, &p));
+        }
+        // and it is multiplicative in n
+        let n = BigUint::from(7919u32 * 7927);
+        for a in [2u32, 3, 5, 1234] {
+            let a = BigUint::from(a);
+            assert_eq!(jacobi(&a, &n), jacobi(&a, &p) * jacobi(&a, &BigUint::from(7927u32)));
+        }
+        assert_eq!(jacobi(&p, &n), 0);
+    }
+
+    #[test]
+    fn test_paillier_homomorphism() {
+        let sk = PaillierSecretKey::generate(TEST_BITS);
+        let pk = sk.public_key();
+
+        let a = BigUint::from(1234567u32);
+        let b = BigUint::from(7654321u32);
+        let (ca, _) = pk.encrypt(&a);
+        let (cb, _) = pk.encrypt(&b);
+
+        assert_eq!(sk.decrypt(&ca), a);
+        assert_eq!(sk.decrypt(&pk.add(&ca, &cb)), &a + &b);
+        assert_eq!(sk.decrypt(&pk.mul(&ca, &b)), &a * &b);
+    }
+
+    #[test]
+    fn test_randomness_recovery() {
+        let sk = PaillierSecretKey::generate(TEST_BITS);
+        let pk = sk.public_key();
+
+        let m = BigUint::from(99u32);
+        let (c, r) = pk.encrypt(&m);
+        assert_eq!(sk.recover_randomness(&c, &m), r);
+        assert_eq!(pk.encrypt_with(&m, &sk.recover_randomness(&c, &m)), c);
+    }
+
+    #[test]
+    fn test_ring_pedersen_proof() {
+        let (params, proof) = RingPedersenParams::generate(TEST_BITS);
+        assert!(params.verify(&proof));
+        assert_eq!(RingPedersenProof::from_bytes(&proof.to_bytes()), Some(proof.clone()));
+
+        // swapping in an unrelated h2 breaks the proof
+        let mut forged = params.clone();
+        forged.h2 = (&forged.h2 * &forged.h2) % &forged.n_tilde;
+        assert!(!forged.verify(&proof));
+    }
+
+    #[test]
+    fn test_modulus_proof() {
+        let sk = PaillierSecretKey::generate(TEST_BITS);
+        let proof = sk.prove_modulus();
+        assert!(proof.verify(&sk.public_key().n));
+        assert_eq!(ModulusProof::from_bytes(&proof.to_bytes()), Some(proof.clone()));
+
+        // the proof is tied to its modulus
+        let other = PaillierSecretKey::generate(TEST_BITS);
+        assert!(!proof.verify(&other.public_key().n));
+
+        // a prime "modulus" is rejected outright
+        assert!(!proof.verify(&generate_prime(TEST_BITS)));
+    }
+
+    #[test]
+    fn test_factor_proof() {
+        let sk = PaillierSecretKey::generate(TEST_BITS);
+        let (verifier, _) = RingPedersenParams::generate(TEST_BITS);
+        let proof = sk.prove_factors(&verifier);
+        assert!(proof.verify(&sk.public_key().n, &verifier));
+        assert_eq!(FactorProof::from_bytes(&proof.to_bytes()), Some(proof.clone()));
+
+        // a modulus with a small factor, as in the bitforge attack, can't pass; the proof's
+        // slack is sized for real moduli, so this one is as large as signers accept
+        let p = generate_blum_prime(64);
+        let q = generate_blum_prime(MIN_PAILLIER_BITS - 64);
+        let n = &p * &q;
+        let phi = (&p - 1u32) * (&q - 1u32);
+        let weak = PaillierSecretKey {
+            public: PaillierPublicKey::new(n.clone()),
+            mu: phi.modinv(&n).unwrap(),
+            p,
+            q,
+            phi,
+        };
+        let proof = weak.prove_factors(&verifier);
+        assert!(!proof.verify(&n, &verifier));
+    }
+}
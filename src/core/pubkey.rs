@@ -0,0 +1,143 @@
+// public keys from shares, without reconstructing the private key
+// every holder publishes g^y for its share; since lagrange interpolation is linear,
+// combining a threshold of those points with the same coefficients used for the secret
+// ("in the exponent") gives g^secret, the group public key. the address encodings
+// below turn that key into what wallets and explorers show
+
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::point::AffineCoordinates;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::sha2::{Digest, Sha256};
+use k256::{ProjectivePoint, Scalar, U256};
+use ripemd::Ripemd160;
+use sha3::Keccak256;
+
+use crate::core::frost::x_only;
+use crate::core::math::{lagrange_coefficient, validate_indices};
+use crate::core::scheme::{FragmentError, SecretShare};
+
+/// the public point g^y of a share
+pub fn public_share(share: &SecretShare) -> ProjectivePoint {
+    ProjectivePoint::GENERATOR * *share.y.expose_secret()
+}
+
+/// combines public shares (x, g^y) into the group public key g^secret
+/// needs at least a threshold of shares, like `recover_secret`; with fewer the result
+/// is some unrelated point, so callers should check it against the commitments if they have them
+pub fn combine_public_shares(public_shares: &[(Scalar, ProjectivePoint)]) -> Result<ProjectivePoint, FragmentError> {
+    let xs: Vec<Scalar> = public_shares.iter().map(|(x, _)| *x).collect();
+    validate_indices(&xs)?;
+
+    public_shares
+        .iter()
+        .try_fold(ProjectivePoint::IDENTITY, |acc, (x, point)| Ok(acc + *point * lagrange_coefficient(x, &xs)?))
+}
+
+/// the 33-byte compressed sec1 encoding of a public key
+pub fn compressed_public_key(public_key: &ProjectivePoint) -> [u8; 33] {
+    public_key.to_bytes().into()
+}
+
+/// the ethereum address of a public key, with the eip-55 mixed-case checksum
+/// the last 20 bytes of keccak256 over the uncompressed x || y
+pub fn ethereum_address(public_key: &ProjectivePoint) -> String {
+    let uncompressed = public_key.to_affine().to_encoded_point(false);
+    let digest = Keccak256::digest(&uncompressed.as_bytes()[1..]);
+    let address = hex::encode(&digest[12..]);
+
+    // eip-55: uppercase every letter whose nibble in keccak256(address) is 8 or more
+    let checksum = Keccak256::digest(address.as_bytes());
+    let cased: String = address
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (checksum[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    format!("0x{}", cased)
+}
+
+/// the bitcoin mainnet p2wpkh (native segwit v0) address of a public key
+/// the witness program is hash160 of the compressed key
+pub fn p2wpkh_address(public_key: &ProjectivePoint) -> String {
+    let hash160 = Ripemd160::digest(Sha256::digest(compressed_public_key(public_key)));
+    bech32::segwit::encode_v0(bech32::hrp::BC, &hash160).expect("a 20-byte program always encodes")
+}
+
+/// the bitcoin mainnet p2tr (taproot, segwit v1) address of a public key
+/// uses the bip-86 key-path-only output key Q = P + H_TapTweak(P.x) * g, with P taken
+/// with an even y coordinate, as wallets derive it
+pub fn p2tr_address(public_key: &ProjectivePoint) -> String {
+    let internal_key = if bool::from(public_key.to_affine().y_is_odd()) {
+        -*public_key
+    } else {
+        *public_key
+    };
+
+    let tag = Sha256::digest(b"TapTweak");
+    let digest = Sha256::new()
+        .chain_update(tag)
+        .chain_update(tag)
+        .chain_update(x_only(&internal_key))
+        .finalize();
+    let tweak = <Scalar as Reduce<U256>>::reduce_bytes(&digest);
+
+    let output_key = internal_key + ProjectivePoint::GENERATOR * tweak;
+    bech32::segwit::encode_v1(bech32::hrp::BC, &x_only(&output_key)).expect("a 32-byte program always encodes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::scheme::split_secret;
+
+    #[test]
+    fn test_public_key_from_public_shares() {
+        let secret = Scalar::from(123456789u64);
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        let public_shares: Vec<(Scalar, ProjectivePoint)> =
+            shares.iter().map(|share| (share.x, public_share(share))).collect();
+        let public_key = ProjectivePoint::GENERATOR * secret;
+
+        assert_eq!(combine_public_shares(&public_shares[..3]).unwrap(), public_key);
+        assert_eq!(combine_public_shares(&public_shares[2..]).unwrap(), public_key);
+        assert_ne!(combine_public_shares(&public_shares[..2]).unwrap(), public_key);
+
+        let repeated = vec![public_shares[0], public_shares[0], public_shares[1]];
+        assert!(combine_public_shares(&repeated).is_err());
+        assert!(combine_public_shares(&[]).is_err());
+    }
+
+    #[test]
+    fn test_address_vectors() {
+        // the generator, i.e. the public key of private key 1
+        let g = ProjectivePoint::GENERATOR;
+        assert_eq!(
+            hex::encode(compressed_public_key(&g)),
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        );
+        assert_eq!(ethereum_address(&g), "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf");
+        // bip-173
+        assert_eq!(p2wpkh_address(&g), "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+
+        // bip-86, first receiving address of the test mnemonic
+        let internal_key =
+            hex::decode("02cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115").unwrap();
+        let internal_key: [u8; 33] = internal_key.try_into().unwrap();
+        let internal_key = ProjectivePoint::from_bytes((&internal_key).into()).unwrap();
+        assert_eq!(
+            p2tr_address(&internal_key),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+        // only the x coordinate of the internal key matters
+        assert_eq!(p2tr_address(&-internal_key), p2tr_address(&internal_key));
+    }
+}
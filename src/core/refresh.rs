@@ -0,0 +1,191 @@
+// proactive share refresh
+// every holder deals a random polynomial with a zero constant term; adding everyone's
+// sub-shares re-randomizes each share while the secret and group key stay the same.
+// shares from before a refresh lie on a different polynomial, so they can't be
+// combined with shares from after it
+
+use k256::{ProjectivePoint, Scalar};
+
+use crate::core::math::evaluate_polynomial;
+use crate::core::scheme::{random_polynomial, FragmentError, SecretShare, ShareMetadata};
+use crate::core::vss::{commit_polynomial, verify_share};
+
+/// one holder's refresh polynomial: feldman commitments plus a sub-share per recipient
+pub struct RefreshDeal {
+    pub commitments: Vec<ProjectivePoint>,
+    pub sub_shares: Vec<SecretShare>,
+}
+
+/// a dealer's refresh contribution as seen by one recipient
+#[derive(Debug, Clone)]
+pub struct RefreshContribution {
+    pub dealer: u32,
+    pub commitments: Vec<ProjectivePoint>,
+    pub sub_share: SecretShare,
+}
+
+/// deals a zero-constant polynomial of degree (threshold - 1) to the given holder indices
+pub fn deal_refresh(threshold: usize, indices: &[u32]) -> Result<RefreshDeal, FragmentError> {
+    if threshold < 2 || threshold > indices.len() {
+        return Err(FragmentError::InvalidThreshold);
+    }
+
+    let coefficients = random_polynomial(&Scalar::ZERO, threshold);
+    let sub_shares = indices
+        .iter()
+        .map(|i| {
+            let x = Scalar::from(*i as u64);
+            SecretShare::new(x, evaluate_polynomial(&coefficients, &x))
+        })
+        .collect();
+
+    Ok(RefreshDeal {
+        commitments: commit_polynomial(&coefficients),
+        sub_shares,
+    })
+}
+
+/// checks a refresh sub-share against its dealer's commitments
+/// the first commitment has to be the identity, otherwise the dealer would shift the secret
+pub fn verify_refresh_share(sub_share: &SecretShare, commitments: &[ProjectivePoint]) -> bool {
+    commitments.first() == Some(&ProjectivePoint::IDENTITY) && verify_share(sub_share, commitments)
+}
+
+/// adds every dealer's sub-share to `share` and every dealer's commitments to the group's
+/// returns the refreshed share and group commitments
+///
+/// fails naming the first dealer whose contribution is invalid; nothing changes in that case,
+/// so the holders can keep using their current shares
+pub fn apply_refresh(
+    share: &SecretShare,
+    group_commitments: &[ProjectivePoint],
+    contributions: &[RefreshContribution],
+) -> Result<(SecretShare, Vec<ProjectivePoint>), FragmentError> {
+    if contributions.is_empty() {
+        return Err(FragmentError::RefreshAborted("no refresh contributions".to_string()));
+    }
+
+    let mut refreshed = share.clone();
+    let mut commitments = group_commitments.to_vec();
+    let mut dealers = Vec::new();
+
+    for contribution in contributions {
+        if dealers.contains(&contribution.dealer) {
+            return Err(FragmentError::RefreshAborted(format!(
+                "dealer {} contributed twice",
+                contribution.dealer
+            )));
+        }
+        dealers.push(contribution.dealer);
+
+        let valid = contribution.commitments.len() == group_commitments.len()
+            && contribution.sub_share.x == share.x
+            && verify_refresh_share(&contribution.sub_share, &contribution.commitments);
+        if !valid {
+            return Err(FragmentError::RefreshAborted(format!(
+                "invalid refresh contribution from dealer {}",
+                contribution.dealer
+            )));
+        }
+
+        *refreshed.y.expose_secret_mut() += *contribution.sub_share.y.expose_secret();
+        for (total, c) in commitments.iter_mut().zip(&contribution.commitments) {
+            *total += c;
+        }
+    }
+
+    // a tagged share moves to the next epoch, so it can't be mixed with pre-refresh shares
+    if let Some(meta) = &share.meta {
+        let next = ShareMetadata {
+            epoch: meta.epoch + 1,
+            ..meta.clone()
+        };
+        refreshed = SecretShare::tagged(refreshed.x, *refreshed.y.expose_secret(), next);
+    }
+
+    Ok((refreshed, commitments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::scheme::recover_secret;
+    use crate::core::vss::split_secret_verifiable;
+
+    /// runs a full refresh with every holder dealing
+    fn refresh_all(shares: &[SecretShare], commitments: &[ProjectivePoint]) -> (Vec<SecretShare>, Vec<ProjectivePoint>) {
+        let indices: Vec<u32> = (1..=shares.len() as u32).collect();
+        let deals: Vec<RefreshDeal> = indices
+            .iter()
+            .map(|_| deal_refresh(commitments.len(), &indices).unwrap())
+            .collect();
+
+        let mut refreshed = Vec::new();
+        let mut new_commitments = Vec::new();
+        for (i, share) in shares.iter().enumerate() {
+            let contributions: Vec<RefreshContribution> = deals
+                .iter()
+                .zip(&indices)
+                .map(|(deal, dealer)| RefreshContribution {
+                    dealer: *dealer,
+                    commitments: deal.commitments.clone(),
+                    sub_share: deal.sub_shares[i].clone(),
+                })
+                .collect();
+            let (share, commitments) = apply_refresh(share, commitments, &contributions).unwrap();
+            refreshed.push(share);
+            new_commitments = commitments;
+        }
+
+        (refreshed, new_commitments)
+    }
+
+    #[test]
+    fn test_refresh_keeps_secret() {
+        let secret = Scalar::generate_vartime(&mut rand::rngs::OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 3, 5).unwrap();
+
+        let (refreshed, new_commitments) = refresh_all(&shares, &commitments);
+
+        assert_eq!(new_commitments[0], commitments[0]);
+        assert_eq!(*recover_secret(&refreshed[1..4]).unwrap().expose_secret(), secret);
+        for (old, new) in shares.iter().zip(&refreshed) {
+            assert_ne!(old.y, new.y);
+            assert!(verify_share(new, &new_commitments));
+        }
+    }
+
+    #[test]
+    fn test_old_shares_useless_after_refresh() {
+        let secret = Scalar::generate_vartime(&mut rand::rngs::OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 3, 5).unwrap();
+
+        let (refreshed, new_commitments) = refresh_all(&shares, &commitments);
+
+        // an attacker holding two old shares and one new share learns nothing
+        let mixed = vec![shares[0].clone(), shares[1].clone(), refreshed[2].clone()];
+        assert!(matches!(recover_secret(&mixed), Err(FragmentError::MixedShareSets)));
+        let bare: Vec<SecretShare> = mixed.iter().map(|s| SecretShare::new(s.x, *s.y.expose_secret())).collect();
+        assert_ne!(*recover_secret(&bare).unwrap().expose_secret(), secret);
+        assert!(!verify_share(&shares[0], &new_commitments));
+    }
+
+    #[test]
+    fn test_nonzero_refresh_polynomial_rejected() {
+        let secret = Scalar::generate_vartime(&mut rand::rngs::OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 3).unwrap();
+
+        // a malicious dealer shares a nonzero constant to shift the secret
+        let (shift, shift_commitments) = split_secret_verifiable(&Scalar::ONE, 2, 3).unwrap();
+        let contribution = RefreshContribution {
+            dealer: 2,
+            commitments: shift_commitments,
+            sub_share: shift[0].clone(),
+        };
+
+        assert!(matches!(
+            apply_refresh(&shares[0], &commitments, &[contribution]),
+            Err(FragmentError::RefreshAborted(_))
+        ));
+    }
+}
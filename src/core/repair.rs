@@ -0,0 +1,169 @@
+// share repair for a lost custodian
+// a threshold of helpers rebuild f(x_r) = sum of L_i(x_r) * y_i for the lost x_r without
+// anyone learning it: each helper splits its term into random additive pieces, one per helper,
+// every helper sums the pieces it received and forwards only that sum to the new node,
+// which adds the sums up. any single piece or sum is uniformly random
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use k256::{ProjectivePoint, Scalar};
+
+use crate::core::math::{lagrange_coefficient_at, random_scalar};
+use crate::core::scheme::{FragmentError, SecretShare};
+use crate::core::vss::verify_share;
+
+/// splits this helper's term L_i(x_r) * y_i into one random piece per helper
+/// returns the pieces keyed by the helper they go to, including this helper's own
+///
+/// refuses fewer helpers than the sharing's threshold: with fewer, the sums are no longer
+/// masked by a full threshold of shares (a lone helper's term is its own share), so
+/// repeated repairs would hand out linear combinations of the helpers' shares
+pub fn repair_pieces(
+    share: &SecretShare,
+    helpers: &[u32],
+    lost: u32,
+    threshold: usize,
+) -> Result<BTreeMap<u32, Scalar>, FragmentError> {
+    let unique: BTreeSet<u32> = helpers.iter().copied().collect();
+    if unique.len() != helpers.len() {
+        return Err(FragmentError::RepairAborted("duplicate helper index".to_string()));
+    }
+    if threshold < 2 || helpers.len() < threshold {
+        return Err(FragmentError::InsufficientShares);
+    }
+    if lost == 0 || unique.contains(&lost) {
+        return Err(FragmentError::RepairAborted(format!(
+            "share {} can't be repaired by its own holder",
+            lost
+        )));
+    }
+
+    let xs: Vec<Scalar> = helpers.iter().map(|i| Scalar::from(*i as u64)).collect();
+    if !xs.contains(&share.x) {
+        return Err(FragmentError::RepairAborted("this share is not one of the helpers".to_string()));
+    }
+    let term = *share.y.expose_secret() * lagrange_coefficient_at(&share.x, &xs, &Scalar::from(lost as u64))?;
+
+    // random pieces for the other helpers, this helper keeps whatever makes them sum to the term
+    let mut pieces = BTreeMap::new();
+    let mut remaining = term;
+    for (helper, x) in helpers.iter().zip(&xs) {
+        if *x != share.x {
+            let piece = random_scalar();
+            remaining -= piece;
+            pieces.insert(*helper, piece);
+        }
+    }
+    let own = helpers[xs.iter().position(|x| *x == share.x).unwrap()];
+    pieces.insert(own, remaining);
+
+    Ok(pieces)
+}
+
+/// what a helper forwards to the new node: the sum of the pieces it received
+pub fn sum_pieces(pieces: &[Scalar]) -> Scalar {
+    pieces.iter().fold(Scalar::ZERO, |acc, piece| acc + piece)
+}
+
+/// the new node's last step: adds up the helpers' sums and checks the result
+/// against the group's feldman commitments before accepting it
+pub fn complete_repair(lost: u32, sums: &[Scalar], commitments: &[ProjectivePoint]) -> Result<SecretShare, FragmentError> {
+    if commitments.is_empty() || sums.len() < commitments.len() {
+        return Err(FragmentError::InsufficientShares);
+    }
+
+    let share = SecretShare::new(Scalar::from(lost as u64), sum_pieces(sums));
+    if !verify_share(&share, commitments) {
+        return Err(FragmentError::RepairAborted(
+            "repaired share does not match the group commitments".to_string(),
+        ));
+    }
+
+    Ok(share)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+    use crate::core::vss::split_secret_verifiable;
+
+    /// runs the repair with the holders at the given indices helping
+    fn repair(shares: &[SecretShare], threshold: usize, helpers: &[u32], lost: u32) -> Vec<Scalar> {
+        let all_pieces: Vec<BTreeMap<u32, Scalar>> = helpers
+            .iter()
+            .map(|i| repair_pieces(&shares[*i as usize - 1], helpers, lost, threshold).unwrap())
+            .collect();
+
+        helpers
+            .iter()
+            .map(|j| sum_pieces(&all_pieces.iter().map(|pieces| pieces[j]).collect::<Vec<_>>()))
+            .collect()
+    }
+
+    #[test]
+    fn test_repair_rebuilds_lost_share() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 3, 5).unwrap();
+
+        let sums = repair(&shares, 3, &[1, 3, 5], 2);
+        let repaired = complete_repair(2, &sums, &commitments).unwrap();
+
+        assert_eq!(repaired.x, shares[1].x);
+        assert_eq!(repaired.y, shares[1].y);
+        // no single sum gives the share away
+        assert!(sums.iter().all(|sum| *sum != *shares[1].y.expose_secret()));
+    }
+
+    #[test]
+    fn test_repair_with_too_few_helpers_rejected() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 3, 5).unwrap();
+
+        let sums = repair(&shares, 3, &[1, 3, 4], 2);
+        assert!(matches!(
+            complete_repair(2, &sums[..2], &commitments),
+            Err(FragmentError::InsufficientShares)
+        ));
+    }
+
+    #[test]
+    fn test_too_few_helpers_refused() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, _) = split_secret_verifiable(&secret, 3, 5).unwrap();
+
+        // a lone helper's lagrange coefficient is 1, its piece would be its own share
+        assert!(matches!(
+            repair_pieces(&shares[0], &[1], 2, 3),
+            Err(FragmentError::InsufficientShares)
+        ));
+        assert!(matches!(
+            repair_pieces(&shares[0], &[1, 3], 2, 3),
+            Err(FragmentError::InsufficientShares)
+        ));
+        assert!(repair_pieces(&shares[0], &[1, 3, 4], 2, 3).is_ok());
+    }
+
+    #[test]
+    fn test_corrupted_sum_detected() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 3).unwrap();
+
+        let mut sums = repair(&shares, 2, &[1, 2], 3);
+        sums[0] += Scalar::ONE;
+
+        assert!(matches!(
+            complete_repair(3, &sums, &commitments),
+            Err(FragmentError::RepairAborted(_))
+        ));
+    }
+
+    #[test]
+    fn test_lost_index_cannot_help() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, _) = split_secret_verifiable(&secret, 2, 3).unwrap();
+
+        assert!(repair_pieces(&shares[0], &[1, 2], 2, 2).is_err());
+        assert!(repair_pieces(&shares[0], &[2, 3], 1, 2).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+// resharing to a new threshold and custodian set
+// a threshold of old holders each share lambda_i * x_i (their additive share of the secret)
+// with a fresh polynomial of the new degree; each new holder adds up what it receives.
+// the sum of the dealers' constants is the secret, so the new committee holds a (t', n')
+// sharing of the same key, and nobody ever reconstructs it
+
+use std::collections::BTreeSet;
+
+use k256::{ProjectivePoint, Scalar};
+
+use crate::core::math::{evaluate_polynomial, lagrange_coefficient};
+use crate::core::scheme::{random_polynomial, FragmentError, SecretShare};
+use crate::core::vss::{commit_polynomial, evaluate_commitments, verify_share};
+
+/// one old holder's resharing polynomial: feldman commitments plus a sub-share per new holder
+pub struct ReshareDeal {
+    pub dealer: u32,
+    pub commitments: Vec<ProjectivePoint>,
+    pub sub_shares: Vec<SecretShare>,
+}
+
+/// an old holder's contribution as seen by one new holder
+#[derive(Debug, Clone)]
+pub struct ReshareContribution {
+    pub dealer: u32,
+    pub commitments: Vec<ProjectivePoint>,
+    pub sub_share: SecretShare,
+}
+
+/// deals an old share to the new committee
+///
+/// - dealer: the old holder's index (the x coordinate of `share`)
+/// - dealers: every old holder taking part, at least `old_threshold` of them
+/// - new_threshold, new_indices: the new committee's threshold and x coordinates
+///
+/// refuses a dealer set below the old threshold, whose lagrange coefficients don't
+/// interpolate the secret, and repeated or zero indices on either side
+pub fn deal_reshare(
+    dealer: u32,
+    share: &SecretShare,
+    dealers: &[u32],
+    old_threshold: usize,
+    new_threshold: usize,
+    new_indices: &[u32],
+) -> Result<ReshareDeal, FragmentError> {
+    let unique_dealers: BTreeSet<u32> = dealers.iter().copied().collect();
+    if unique_dealers.len() != dealers.len() || old_threshold < 2 || dealers.len() < old_threshold {
+        return Err(FragmentError::InsufficientShares);
+    }
+    let unique_indices: BTreeSet<u32> = new_indices.iter().copied().collect();
+    if unique_indices.len() != new_indices.len() || unique_indices.contains(&0) {
+        return Err(FragmentError::ResharingAborted(
+            "new committee indices must be distinct and non-zero".to_string(),
+        ));
+    }
+    if new_threshold < 2 || new_threshold > new_indices.len() {
+        return Err(FragmentError::InvalidThreshold);
+    }
+    if share.x != Scalar::from(dealer as u64) || !dealers.contains(&dealer) {
+        return Err(FragmentError::ResharingAborted(format!(
+            "dealer {} is not part of the old holder set",
+            dealer
+        )));
+    }
+
+    let xs: Vec<Scalar> = dealers.iter().map(|i| Scalar::from(*i as u64)).collect();
+    let constant = *share.y.expose_secret() * lagrange_coefficient(&share.x, &xs)?;
+    let coefficients = random_polynomial(&constant, new_threshold);
+
+    let sub_shares = new_indices
+        .iter()
+        .map(|j| {
+            let x = Scalar::from(*j as u64);
+            SecretShare::new(x, evaluate_polynomial(&coefficients, &x))
+        })
+        .collect();
+
+    Ok(ReshareDeal {
+        dealer,
+        commitments: commit_polynomial(&coefficients),
+        sub_shares,
+    })
+}
+
+/// checks a contribution against the old group commitments
+/// the dealer's constant term has to commit to lambda_i * x_i, which everyone can compute
+/// from the old commitments, so a dealer can't shift the secret
+pub fn verify_reshare_contribution(
+    contribution: &ReshareContribution,
+    old_commitments: &[ProjectivePoint],
+    dealers: &[u32],
+) -> bool {
+    let xs: Vec<Scalar> = dealers.iter().map(|i| Scalar::from(*i as u64)).collect();
+    let x = Scalar::from(contribution.dealer as u64);
+    let Ok(lambda) = lagrange_coefficient(&x, &xs) else {
+        return false;
+    };
+    let expected = evaluate_commitments(old_commitments, &x) * lambda;
+
+    dealers.contains(&contribution.dealer)
+        && contribution.commitments.first() == Some(&expected)
+        && verify_share(&contribution.sub_share, &contribution.commitments)
+}
+
+/// combines the contributions of every old dealer into a new holder's share
+/// returns the share and the new group commitments, whose first entry is the unchanged group key
+///
+/// fails naming the first dealer whose contribution is invalid
+pub fn complete_reshare(
+    index: u32,
+    old_commitments: &[ProjectivePoint],
+    dealers: &[u32],
+    contributions: &[ReshareContribution],
+) -> Result<(SecretShare, Vec<ProjectivePoint>), FragmentError> {
+    let unique: BTreeSet<u32> = dealers.iter().copied().collect();
+    if unique.len() != dealers.len() || dealers.len() < old_commitments.len() {
+        return Err(FragmentError::InsufficientShares);
+    }
+
+    let received: BTreeSet<u32> = contributions.iter().map(|c| c.dealer).collect();
+    if received != unique || contributions.len() != dealers.len() {
+        return Err(FragmentError::ResharingAborted(
+            "expected exactly one contribution from every dealer".to_string(),
+        ));
+    }
+
+    let x = Scalar::from(index as u64);
+    let new_threshold = contributions[0].commitments.len();
+    let mut share = SecretShare::new(x, Scalar::ZERO);
+    let mut commitments = vec![ProjectivePoint::IDENTITY; new_threshold];
+
+    for contribution in contributions {
+        let valid = contribution.sub_share.x == x
+            && contribution.commitments.len() == new_threshold
+            && verify_reshare_contribution(contribution, old_commitments, dealers);
+        if !valid {
+            return Err(FragmentError::ResharingAborted(format!(
+                "invalid contribution from dealer {}",
+                contribution.dealer
+            )));
+        }
+
+        *share.y.expose_secret_mut() += *contribution.sub_share.y.expose_secret();
+        for (total, c) in commitments.iter_mut().zip(&contribution.commitments) {
+            *total += c;
+        }
+    }
+
+    Ok((share, commitments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::scheme::recover_secret;
+    use crate::core::vss::split_secret_verifiable;
+    use rand::rngs::OsRng;
+
+    /// reshares from the old holders at positions `dealers` to new indices 1..=new_total
+    fn reshare(
+        shares: &[SecretShare],
+        commitments: &[ProjectivePoint],
+        dealers: &[u32],
+        new_threshold: usize,
+        new_total: u32,
+    ) -> (Vec<SecretShare>, Vec<ProjectivePoint>) {
+        let new_indices: Vec<u32> = (1..=new_total).collect();
+        let deals: Vec<ReshareDeal> = dealers
+            .iter()
+            .map(|d| {
+                deal_reshare(*d, &shares[*d as usize - 1], dealers, commitments.len(), new_threshold, &new_indices)
+                    .unwrap()
+            })
+            .collect();
+
+        let mut new_shares = Vec::new();
+        let mut new_commitments = Vec::new();
+        for (position, index) in new_indices.iter().enumerate() {
+            let contributions: Vec<ReshareContribution> = deals
+                .iter()
+                .map(|deal| ReshareContribution {
+                    dealer: deal.dealer,
+                    commitments: deal.commitments.clone(),
+                    sub_share: deal.sub_shares[position].clone(),
+                })
+                .collect();
+            let (share, commitments) = complete_reshare(*index, commitments, dealers, &contributions).unwrap();
+            new_shares.push(share);
+            new_commitments = commitments;
+        }
+
+        (new_shares, new_commitments)
+    }
+
+    #[test]
+    fn test_reshare_to_larger_committee() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 3).unwrap();
+
+        let (new_shares, new_commitments) = reshare(&shares, &commitments, &[1, 3], 3, 5);
+
+        assert_eq!(new_commitments.len(), 3);
+        assert_eq!(new_commitments[0], commitments[0]);
+        assert_eq!(*recover_secret(&new_shares[2..5]).unwrap().expose_secret(), secret);
+        let spread = [new_shares[0].clone(), new_shares[3].clone(), new_shares[4].clone()];
+        assert_eq!(*recover_secret(&spread).unwrap().expose_secret(), secret);
+        for share in &new_shares {
+            assert!(verify_share(share, &new_commitments));
+        }
+
+        // two new shares are no longer enough
+        assert_ne!(*recover_secret(&new_shares[..2]).unwrap().expose_secret(), secret);
+    }
+
+    #[test]
+    fn test_reshare_to_smaller_committee() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 3, 5).unwrap();
+
+        let (new_shares, new_commitments) = reshare(&shares, &commitments, &[2, 4, 5], 2, 3);
+
+        assert_eq!(new_commitments[0], commitments[0]);
+        assert_eq!(*recover_secret(&new_shares[1..]).unwrap().expose_secret(), secret);
+    }
+
+    #[test]
+    fn test_shifted_contribution_rejected() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 3).unwrap();
+        let dealers = [1, 2];
+
+        let honest = deal_reshare(1, &shares[0], &dealers, 2, 2, &[1, 2, 3]).unwrap();
+        // dealer 2 adds one to its share before dealing
+        let shifted = SecretShare::new(shares[1].x, *shares[1].y.expose_secret() + Scalar::ONE);
+        let cheating = deal_reshare(2, &shifted, &dealers, 2, 2, &[1, 2, 3]).unwrap();
+
+        let contributions: Vec<ReshareContribution> = [honest, cheating]
+            .into_iter()
+            .map(|deal| ReshareContribution {
+                dealer: deal.dealer,
+                commitments: deal.commitments,
+                sub_share: deal.sub_shares[0].clone(),
+            })
+            .collect();
+
+        let err = complete_reshare(1, &commitments, &dealers, &contributions).unwrap_err();
+        assert!(matches!(err, FragmentError::ResharingAborted(reason) if reason.contains("dealer 2")));
+    }
+
+    #[test]
+    fn test_too_few_dealers_rejected() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, commitments) = split_secret_verifiable(&secret, 3, 5).unwrap();
+
+        // a dealer won't deal for fewer holders than the old threshold
+        assert!(matches!(
+            deal_reshare(1, &shares[0], &[1, 2], 3, 2, &[1, 2]),
+            Err(FragmentError::InsufficientShares)
+        ));
+
+        // and a new holder won't combine their contributions either
+        let deal = deal_reshare(1, &shares[0], &[1, 2, 3], 3, 2, &[1, 2]).unwrap();
+        let contribution = ReshareContribution {
+            dealer: 1,
+            commitments: deal.commitments,
+            sub_share: deal.sub_shares[0].clone(),
+        };
+        assert!(matches!(
+            complete_reshare(1, &commitments, &[1, 2], &[contribution]),
+            Err(FragmentError::InsufficientShares)
+        ));
+    }
+
+    #[test]
+    fn test_repeated_indices_rejected() {
+        let secret = Scalar::generate_vartime(&mut OsRng);
+        let (shares, _) = split_secret_verifiable(&secret, 2, 3).unwrap();
+
+        // a repeated dealer would count towards the old threshold twice
+        assert!(matches!(
+            deal_reshare(1, &shares[0], &[1, 1], 2, 2, &[1, 2, 3]),
+            Err(FragmentError::InsufficientShares)
+        ));
+        // a repeated recipient would get two sub-shares of the same point
+        assert!(matches!(
+            deal_reshare(1, &shares[0], &[1, 2], 2, 2, &[1, 2, 2]),
+            Err(FragmentError::ResharingAborted(_))
+        ));
+        assert!(deal_reshare(1, &shares[0], &[1, 2], 2, 2, &[0, 1, 2]).is_err());
+    }
+}
@@ -1,11 +1,17 @@
 // high-level secret sharing scheme
 // implements split and recover operations for shamir's secret sharing
 
+use k256::sha2::{Digest, Sha256};
 use k256::Scalar;
 use rand::rngs::OsRng;
+use rand::RngCore;
 use thiserror::Error;
 
-use crate::core::math::{evaluate_polynomial, lagrange_interpolate};
+use crate::core::math::{
+    divide_polynomials, evaluate_polynomial, lagrange_interpolate, lagrange_interpolate_many, random_scalar,
+    solve_linear_system, validate_indices,
+};
+use crate::core::secret::SecretScalar;
 
 /// errors that can occur during secret sharing operations
 #[derive(Error, Debug)]
@@ -15,14 +21,128 @@ pub enum FragmentError {
 
     #[error("not enough shares to recover the secret (need at least threshold shares)")]
     InsufficientShares,
+
+    #[error("ciphertext is malformed or failed authentication")]
+    DecryptionFailed,
+
+    #[error("distributed key generation aborted: {0}")]
+    KeyGenerationAborted(String),
+
+    #[error("threshold signing aborted: {0}")]
+    SigningAborted(String),
+
+    #[error("signer {index} misbehaved: {reason}")]
+    SignerMisbehaved { index: u32, reason: String },
+
+    #[error("partial decryption from custodian {index} failed its proof")]
+    DecryptionShareInvalid { index: u32 },
+
+    #[error("share refresh aborted: {0}")]
+    RefreshAborted(String),
+
+    #[error("resharing aborted: {0}")]
+    ResharingAborted(String),
+
+    #[error("share repair aborted: {0}")]
+    RepairAborted(String),
+
+    #[error("shares come from different splits or epochs and can't be combined")]
+    MixedShareSets,
+
+    #[error("more than one share at x = {0}")]
+    DuplicateShare(String),
+
+    #[error("a share at x = 0 would be the secret itself")]
+    ZeroIndex,
+
+    #[error("share at x = {0} failed its checksum and is corrupted")]
+    ChecksumMismatch(String),
+
+    #[error("malformed share: {0}")]
+    MalformedShare(String),
+
+    #[error("key derivation failed: {0}")]
+    DerivationFailed(String),
+
+    #[error("too many corrupted shares to recover the secret (can correct at most {correctable})")]
+    TooManyCorruptShares { correctable: usize },
+
+    #[error("{} of the shares don't lie on the polynomial the others define", .disagreeing.len())]
+    InconsistentShares { disagreeing: Vec<Scalar> },
 }
 
 /// represents a single share of the secret
 /// x is the evaluation point, y is the polynomial value at that point
+///
+/// shares from a dealer split carry metadata describing the split they belong to;
+/// shares that come out of the distributed protocols (dkg, refresh, resharing, repair)
+/// and off the wire have none, their threshold is fixed by the group commitments instead
+///
+/// y is a `SecretScalar`: it's wiped on drop and redacted when the share is formatted
 #[derive(Debug, Clone)]
-pub struct Share {
+pub struct SecretShare {
     pub x: Scalar,
-    pub y: Scalar,
+    pub y: SecretScalar,
+    pub meta: Option<ShareMetadata>,
+}
+
+/// the field a share's values live in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Curve {
+    /// the scalar field of secp256k1
+    Secp256k1,
+}
+
+/// what a share records about the split that produced it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareMetadata {
+    pub threshold: u32,
+    pub total: u32,
+    /// random identifier shared by every share of one split
+    pub set_id: [u8; 16],
+    pub curve: Curve,
+    /// bumped by every proactive refresh, shares of different epochs don't combine
+    pub epoch: u64,
+    /// truncated sha-256 over the share and the fields above
+    pub checksum: [u8; 8],
+}
+
+/// domain separation tag for share checksums
+const SHARE_CHECKSUM_TAG: &[u8] = b"quorum/share-checksum";
+
+impl SecretShare {
+    /// a bare share without metadata
+    pub fn new(x: Scalar, y: Scalar) -> Self {
+        SecretShare { x, y: SecretScalar::new(y), meta: None }
+    }
+
+    /// a share tagged with the split it belongs to, `meta.checksum` is recomputed
+    pub fn tagged(x: Scalar, y: Scalar, mut meta: ShareMetadata) -> Self {
+        meta.checksum = share_checksum(&x, &y, &meta);
+        SecretShare { x, y: SecretScalar::new(y), meta: Some(meta) }
+    }
+
+    /// true unless the share carries metadata whose checksum doesn't match
+    pub fn checksum_valid(&self) -> bool {
+        self.meta
+            .as_ref()
+            .is_none_or(|meta| meta.checksum == share_checksum(&self.x, self.y.expose_secret(), meta))
+    }
+}
+
+fn share_checksum(x: &Scalar, y: &Scalar, meta: &ShareMetadata) -> [u8; 8] {
+    let digest = Sha256::new()
+        .chain_update(SHARE_CHECKSUM_TAG)
+        .chain_update(meta.threshold.to_be_bytes())
+        .chain_update(meta.total.to_be_bytes())
+        .chain_update(meta.set_id)
+        .chain_update([meta.curve as u8])
+        .chain_update(meta.epoch.to_be_bytes())
+        .chain_update(x.to_bytes())
+        .chain_update(y.to_bytes())
+        .finalize();
+
+    digest[..8].try_into().unwrap()
 }
 
 /// splits a secret into multiple shares using shamir's secret sharing
@@ -36,53 +156,236 @@ pub fn split_secret(
     secret: &Scalar,
     threshold: usize,
     total_shares: usize,
-) -> Result<Vec<Share>, FragmentError> {
+) -> Result<Vec<SecretShare>, FragmentError> {
     // sanity checks
     if threshold < 2 || threshold > total_shares {
         return Err(FragmentError::InvalidThreshold);
     }
 
-    // generating random coefficients for the polynomial
+    // the constant term of the polynomial is the secret, the rest is random
+    let coefficients = random_polynomial(secret, threshold);
+    let shares = tag_shares(shares_from_polynomial(&coefficients, total_shares), threshold);
+
+    Ok(shares)
+}
+
+/// tags the shares of one fresh split with a new random share-set id
+pub(crate) fn tag_shares(shares: Vec<SecretShare>, threshold: usize) -> Vec<SecretShare> {
+    let mut set_id = [0u8; 16];
+    OsRng.fill_bytes(&mut set_id);
+    let total = shares.len() as u32;
+
+    shares
+        .into_iter()
+        .map(|share| {
+            SecretShare::tagged(
+                share.x,
+                *share.y.expose_secret(),
+                ShareMetadata {
+                    threshold: threshold as u32,
+                    total,
+                    set_id,
+                    curve: Curve::Secp256k1,
+                    epoch: 0,
+                    checksum: [0; 8],
+                },
+            )
+        })
+        .collect()
+}
+
+/// checks that the tagged shares handed to a recovery all come from the same split and epoch
+/// returns the split's threshold if any share records it
+///
+/// once one share is tagged, every share has to be: a bare share can't be checked against
+/// the split, so it would slip past both the set and the threshold checks
+fn recorded_threshold(shares: &[SecretShare]) -> Result<Option<usize>, FragmentError> {
+    let Some(first) = shares.iter().find_map(|share| share.meta.as_ref()) else {
+        return Ok(None);
+    };
+    let same_set = |meta: &ShareMetadata| {
+        meta.set_id == first.set_id
+            && meta.epoch == first.epoch
+            && meta.threshold == first.threshold
+            && meta.total == first.total
+            && meta.curve == first.curve
+    };
+    if !shares.iter().all(|share| share.meta.as_ref().is_some_and(same_set)) {
+        return Err(FragmentError::MixedShareSets);
+    }
+
+    Ok(Some(first.threshold as usize))
+}
+
+/// generates a random polynomial of degree (threshold - 1) with the given constant term
+/// coefficients are ordered from lowest to highest degree, so coefficients[0] is the constant
+pub(crate) fn random_polynomial(constant: &Scalar, threshold: usize) -> Vec<Scalar> {
     // the constant term (a0) is the secret itself
     // we need (threshold - 1) random coefficients for degrees 1 to (threshold - 1)
-    let mut coefficients = vec![*secret];
-    let mut rng = OsRng;
+    let mut coefficients = vec![*constant];
     
     for _ in 1..threshold {
-        // generating a random scalar using k256's built-in method
-        let random_coeff = Scalar::generate_vartime(&mut rng);
-        coefficients.push(random_coeff);
+        // sampled in constant time, the coefficients are as secret as the constant
+        coefficients.push(random_scalar());
     }
 
-    // evaluating the polynomial at x = 1, 2, 3, ... to create shares
+    coefficients
+}
+
+/// evaluates the polynomial at x = 1, 2, 3, ... to create shares
+pub(crate) fn shares_from_polynomial(coefficients: &[Scalar], total_shares: usize) -> Vec<SecretShare> {
     let mut shares = Vec::with_capacity(total_shares);
     
     for i in 1..=total_shares {
         let x = Scalar::from(i as u64);
-        let y = evaluate_polynomial(&coefficients, &x);
-        shares.push(Share { x, y });
+        let y = evaluate_polynomial(coefficients, &x);
+        shares.push(SecretShare::new(x, y));
     }
 
-    Ok(shares)
+    shares
 }
 
 /// reconstructs the secret from a set of shares using lagrange interpolation
 /// 
 /// - shares: the shares to use for reconstruction (must be at least threshold shares)
 /// 
-/// returns the original secret if enough valid shares are provided
-pub fn recover_secret(shares: &[Share]) -> Result<Scalar, FragmentError> {
-    if shares.len() < 2 {
+/// returns the original secret if enough valid shares are provided.
+/// tagged shares are checked first: they must pass their checksums, come from the
+/// same split and number at least its threshold; untagged shares need at least 2.
+/// a repeated or zero x coordinate fails with DuplicateShare or ZeroIndex, so the same
+/// share passed twice never counts towards the threshold
+pub fn recover_secret(shares: &[SecretShare]) -> Result<SecretScalar, FragmentError> {
+    if let Some(bad) = shares.iter().find(|share| !share.checksum_valid()) {
+        return Err(FragmentError::ChecksumMismatch(hex::encode(bad.x.to_bytes())));
+    }
+    let xs: Vec<Scalar> = shares.iter().map(|share| share.x).collect();
+    validate_indices(&xs)?;
+    let threshold = recorded_threshold(shares)?.unwrap_or(2);
+    if shares.len() < threshold {
         return Err(FragmentError::InsufficientShares);
     }
 
     // converting shares to the format expected by lagrange interpolation
-    let points: Vec<(Scalar, Scalar)> = shares.iter().map(|s| (s.x, s.y)).collect();
+    let points: Vec<(Scalar, Scalar)> = shares.iter().map(|s| (s.x, *s.y.expose_secret())).collect();
 
     // reconstructing the secret (y-intercept at x=0)
-    let secret = lagrange_interpolate(&points);
+    let secret = lagrange_interpolate(&points)?;
+
+    Ok(SecretScalar::new(secret))
+}
+
+/// reconstructs the secret from more than `threshold` shares, correcting corrupted ones
+///
+/// the shares form a reed-solomon codeword, so berlekamp-welch decoding finds the
+/// polynomial of degree (threshold - 1) that agrees with all but at most
+/// (shares - threshold) / 2 of them. returns the secret and the x coordinates of the
+/// shares that don't lie on that polynomial
+///
+/// fails with TooManyCorruptShares if no such polynomial exists; with exactly `threshold`
+/// shares nothing can be checked, so at least threshold + 1 are needed to detect a bad one.
+/// checksums aren't enforced here, a share failing its checksum is just one more error
+/// to decode past, but tagged shares still have to come from one split of this threshold
+pub fn recover_secret_robust(shares: &[SecretShare], threshold: usize) -> Result<(SecretScalar, Vec<Scalar>), FragmentError> {
+    if threshold < 2 || recorded_threshold(shares)?.is_some_and(|recorded| recorded != threshold) {
+        return Err(FragmentError::InvalidThreshold);
+    }
+    let xs: Vec<Scalar> = shares.iter().map(|share| share.x).collect();
+    validate_indices(&xs)?;
+    if shares.len() < threshold {
+        return Err(FragmentError::InsufficientShares);
+    }
+
+    let correctable = (shares.len() - threshold) / 2;
+    let polynomial =
+        berlekamp_welch(shares, threshold, correctable).ok_or(FragmentError::TooManyCorruptShares { correctable })?;
+
+    let corrupted: Vec<Scalar> = shares
+        .iter()
+        .filter(|share| evaluate_polynomial(&polynomial, &share.x) != *share.y.expose_secret())
+        .map(|share| share.x)
+        .collect();
+    if corrupted.len() > correctable {
+        return Err(FragmentError::TooManyCorruptShares { correctable });
+    }
+
+    Ok((SecretScalar::new(polynomial[0]), corrupted))
+}
+
+/// checks that shares beyond the threshold agree with the rest, without any commitments
+///
+/// the first `threshold` shares fix a polynomial of degree (threshold - 1); every extra
+/// share is evaluated against it and the x coordinates of the ones that don't lie on it
+/// are returned, so an empty result means all the shares are consistent. a bad share
+/// among the first `threshold` makes every extra one disagree, callers that need to
+/// tell which side is wrong want `recover_secret_robust` instead
+pub fn verify_consistency(shares: &[SecretShare], threshold: usize) -> Result<Vec<Scalar>, FragmentError> {
+    if threshold < 2 || recorded_threshold(shares)?.is_some_and(|recorded| recorded != threshold) {
+        return Err(FragmentError::InvalidThreshold);
+    }
+    let xs: Vec<Scalar> = shares.iter().map(|share| share.x).collect();
+    validate_indices(&xs)?;
+    if shares.len() < threshold {
+        return Err(FragmentError::InsufficientShares);
+    }
 
-    Ok(secret)
+    let (base, extra) = shares.split_at(threshold);
+    let points: Vec<(Scalar, Scalar)> = base.iter().map(|s| (s.x, *s.y.expose_secret())).collect();
+    let expected = lagrange_interpolate_many(&points, &xs[threshold..])?;
+
+    Ok(extra
+        .iter()
+        .zip(&expected)
+        .filter(|(share, y)| share.y.expose_secret() != *y)
+        .map(|(share, _)| share.x)
+        .collect())
+}
+
+/// like `recover_secret`, but first checks any shares beyond the threshold against the
+/// rest with `verify_consistency`, failing with InconsistentShares listing the ones that
+/// disagree instead of silently interpolating through them
+pub fn recover_secret_checked(shares: &[SecretShare], threshold: usize) -> Result<SecretScalar, FragmentError> {
+    let disagreeing = verify_consistency(shares, threshold)?;
+    if !disagreeing.is_empty() {
+        return Err(FragmentError::InconsistentShares { disagreeing });
+    }
+    recover_secret(shares)
+}
+
+/// finds the polynomial P of degree < k through all but at most e of the shares
+/// solves Q(x_i) = y_i * E(x_i) for a monic error locator E of degree e and Q of
+/// degree < k + e, then P = Q / E; returns None if there's no solution or E doesn't divide Q
+fn berlekamp_welch(shares: &[SecretShare], k: usize, e: usize) -> Option<Vec<Scalar>> {
+    // unknowns: q_0..q_{k+e-1}, then e_0..e_{e-1} (E's leading coefficient is 1)
+    let mut matrix = Vec::with_capacity(shares.len());
+    let mut rhs = Vec::with_capacity(shares.len());
+
+    for share in shares {
+        let mut row = Vec::with_capacity(k + 2 * e);
+        let mut power = Scalar::ONE;
+        let mut powers = Vec::with_capacity(k + e + 1);
+        for _ in 0..=k + e {
+            powers.push(power);
+            power *= share.x;
+        }
+
+        row.extend_from_slice(&powers[..k + e]);
+        let y = share.y.expose_secret();
+        row.extend(powers[..e].iter().map(|p| -(y * p)));
+        matrix.push(row);
+        rhs.push(y * &powers[e]);
+    }
+
+    let solution = solve_linear_system(matrix, rhs)?;
+    let q = &solution[..k + e];
+    let mut locator = solution[k + e..].to_vec();
+    locator.push(Scalar::ONE);
+
+    let (mut polynomial, remainder) = divide_polynomials(q, &locator)?;
+    if remainder.iter().any(|r| *r != Scalar::ZERO) {
+        return None;
+    }
+    polynomial.resize(k, Scalar::ZERO);
+    Some(polynomial)
 }
 
 #[cfg(test)]
@@ -100,11 +403,11 @@ mod tests {
 
         // recovering with exactly 3 shares
         let recovered = recover_secret(&shares[0..3]).unwrap();
-        assert_eq!(recovered, secret);
+        assert_eq!(*recovered.expose_secret(), secret);
 
         // recovering with different 3 shares should also work
         let recovered2 = recover_secret(&shares[2..5]).unwrap();
-        assert_eq!(recovered2, secret);
+        assert_eq!(*recovered2.expose_secret(), secret);
     }
 
     #[test]
@@ -117,4 +420,145 @@ mod tests {
         // threshold greater than total
         assert!(split_secret(&secret, 6, 5).is_err());
     }
+
+    #[test]
+    fn test_robust_recovery_corrects_bad_shares() {
+        let secret = Scalar::from(123456789u64);
+        let mut shares = split_secret(&secret, 3, 7).unwrap();
+
+        // flipping two shares: (7 - 3) / 2 = 2 errors can be corrected
+        *shares[1].y.expose_secret_mut() += Scalar::ONE;
+        *shares[5].y.expose_secret_mut() = Scalar::from(42u64);
+
+        let (recovered, corrupted) = recover_secret_robust(&shares, 3).unwrap();
+        assert_eq!(*recovered.expose_secret(), secret);
+        assert_eq!(corrupted, vec![shares[1].x, shares[5].x]);
+
+        // with all shares intact nothing is flagged
+        let shares = split_secret(&secret, 3, 7).unwrap();
+        assert_eq!(recover_secret_robust(&shares, 3).unwrap(), (SecretScalar::new(secret), Vec::new()));
+    }
+
+    #[test]
+    fn test_robust_recovery_detects_too_many_bad_shares() {
+        let secret = Scalar::from(42u64);
+
+        // one spare share: a corrupted share is detected but can't be located
+        let mut shares = split_secret(&secret, 2, 3).unwrap();
+        *shares[0].y.expose_secret_mut() += Scalar::ONE;
+        assert!(matches!(
+            recover_secret_robust(&shares, 2),
+            Err(FragmentError::TooManyCorruptShares { correctable: 0 })
+        ));
+
+        let mut shares = split_secret(&secret, 3, 5).unwrap();
+        *shares[0].y.expose_secret_mut() += Scalar::ONE;
+        *shares[3].y.expose_secret_mut() += Scalar::from(5u64);
+        assert!(recover_secret_robust(&shares, 3).is_err());
+
+        assert!(matches!(
+            recover_secret_robust(&shares[..2], 3),
+            Err(FragmentError::InsufficientShares)
+        ));
+    }
+
+    #[test]
+    fn test_recover_enforces_recorded_threshold() {
+        let secret = Scalar::from(42u64);
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        let meta = shares[0].meta.as_ref().unwrap();
+        assert_eq!((meta.threshold, meta.total, meta.epoch), (3, 5, 0));
+
+        // two shares used to be "enough" and silently gave a wrong secret
+        assert!(matches!(
+            recover_secret(&shares[..2]),
+            Err(FragmentError::InsufficientShares)
+        ));
+        assert_eq!(*recover_secret(&shares[..3]).unwrap().expose_secret(), secret);
+    }
+
+    #[test]
+    fn test_recover_refuses_mixed_splits() {
+        let secret = Scalar::from(42u64);
+        let first = split_secret(&secret, 2, 3).unwrap();
+        let second = split_secret(&secret, 2, 3).unwrap();
+
+        let mixed = vec![first[0].clone(), second[1].clone()];
+        assert!(matches!(recover_secret(&mixed), Err(FragmentError::MixedShareSets)));
+    }
+
+    #[test]
+    fn test_recover_refuses_tagged_and_bare_shares() {
+        let secret = Scalar::from(42u64);
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        // stripping the tag off two shares must not get them past the threshold of 3
+        let bare = |share: &SecretShare| SecretShare::new(share.x, *share.y.expose_secret());
+        let mixed = vec![shares[0].clone(), bare(&shares[1]), bare(&shares[2])];
+        assert!(matches!(recover_secret(&mixed), Err(FragmentError::MixedShareSets)));
+        assert!(matches!(
+            recover_secret_robust(&mixed, 2),
+            Err(FragmentError::MixedShareSets)
+        ));
+    }
+
+    #[test]
+    fn test_consistency_check_names_disagreeing_shares() {
+        let secret = Scalar::from(42u64);
+        let mut shares = split_secret(&secret, 3, 6).unwrap();
+        assert!(verify_consistency(&shares, 3).unwrap().is_empty());
+        assert!(verify_consistency(&shares[..3], 3).unwrap().is_empty());
+
+        // bare shares have no checksum to give the tampering away
+        let mut bare: Vec<SecretShare> = shares.iter().map(|s| SecretShare::new(s.x, *s.y.expose_secret())).collect();
+        *bare[4].y.expose_secret_mut() += Scalar::ONE;
+        assert_eq!(verify_consistency(&bare, 3).unwrap(), vec![bare[4].x]);
+        match recover_secret_checked(&bare, 3) {
+            Err(FragmentError::InconsistentShares { disagreeing }) => assert_eq!(disagreeing, vec![bare[4].x]),
+            other => panic!("expected inconsistent shares, got {:?}", other),
+        }
+        // plain recovery interpolates straight through it
+        assert_ne!(*recover_secret(&bare).unwrap().expose_secret(), secret);
+
+        assert_eq!(*recover_secret_checked(&shares, 3).unwrap().expose_secret(), secret);
+        assert!(matches!(verify_consistency(&shares[..2], 3), Err(FragmentError::InsufficientShares)));
+        assert!(matches!(verify_consistency(&shares, 2), Err(FragmentError::InvalidThreshold)));
+
+        shares.swap(0, 5);
+        assert!(verify_consistency(&shares, 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recover_rejects_duplicate_and_zero_indices() {
+        let secret = Scalar::from(42u64);
+        let shares = split_secret(&secret, 2, 3).unwrap();
+
+        // the same share twice doesn't make two
+        let repeated = vec![shares[0].clone(), shares[0].clone()];
+        assert!(matches!(recover_secret(&repeated), Err(FragmentError::DuplicateShare(_))));
+        let bare = vec![SecretShare::new(Scalar::ONE, Scalar::ONE), SecretShare::new(Scalar::ONE, Scalar::from(2u64))];
+        assert!(matches!(recover_secret(&bare), Err(FragmentError::DuplicateShare(_))));
+        assert!(matches!(recover_secret_robust(&bare, 2), Err(FragmentError::DuplicateShare(_))));
+
+        let zero = vec![SecretShare::new(Scalar::ZERO, secret), SecretShare::new(Scalar::ONE, Scalar::ONE)];
+        assert!(matches!(recover_secret(&zero), Err(FragmentError::ZeroIndex)));
+    }
+
+    #[test]
+    fn test_corrupted_share_fails_checksum() {
+        let secret = Scalar::from(42u64);
+        let mut shares = split_secret(&secret, 2, 3).unwrap();
+        *shares[1].y.expose_secret_mut() += Scalar::ONE;
+
+        assert!(!shares[1].checksum_valid());
+        assert!(matches!(
+            recover_secret(&shares[..2]),
+            Err(FragmentError::ChecksumMismatch(_))
+        ));
+
+        // bare shares carry nothing to check
+        let bare: Vec<SecretShare> = shares.iter().map(|s| SecretShare::new(s.x, *s.y.expose_secret())).collect();
+        assert!(bare[1].checksum_valid());
+    }
 }
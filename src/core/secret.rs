@@ -0,0 +1,91 @@
+// wrapper for secret scalars: share values and recovered keys
+// the value is wiped from memory when it's dropped, never shows up in Debug or Display
+// output, and reading it takes an explicit `expose_secret()`, so a stray `{:?}` or log
+// line can't leak a key and every place that touches the raw value is easy to find
+
+use std::fmt;
+
+use k256::elliptic_curve::subtle::ConstantTimeEq;
+use k256::Scalar;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// a scalar that zeroizes on drop and redacts itself when formatted
+#[derive(Clone, Default)]
+pub struct SecretScalar(Scalar);
+
+impl SecretScalar {
+    pub fn new(value: Scalar) -> Self {
+        SecretScalar(value)
+    }
+
+    /// the raw value; callers are responsible for not copying it anywhere long-lived
+    pub fn expose_secret(&self) -> &Scalar {
+        &self.0
+    }
+
+    /// mutable access to the raw value, for updating a share in place
+    pub fn expose_secret_mut(&mut self) -> &mut Scalar {
+        &mut self.0
+    }
+}
+
+impl From<Scalar> for SecretScalar {
+    fn from(value: Scalar) -> Self {
+        SecretScalar(value)
+    }
+}
+
+impl Zeroize for SecretScalar {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for SecretScalar {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretScalar {}
+
+/// compares in constant time, so equality checks don't leak where two secrets differ
+impl PartialEq for SecretScalar {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
+
+impl Eq for SecretScalar {}
+
+impl fmt::Debug for SecretScalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretScalar([redacted])")
+    }
+}
+
+impl fmt::Display for SecretScalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_scalar_redacts_and_zeroizes() {
+        let value = Scalar::from(0xdeadbeefu64);
+        let mut secret = SecretScalar::new(value);
+
+        assert_eq!(format!("{:?}", secret), "SecretScalar([redacted])");
+        assert_eq!(format!("{}", secret), "[redacted]");
+        assert!(!format!("{:?}", secret).contains("deadbeef"));
+        assert_eq!(*secret.expose_secret(), value);
+        assert_eq!(secret, SecretScalar::from(value));
+
+        secret.zeroize();
+        assert_eq!(*secret.expose_secret(), Scalar::ZERO);
+    }
+}
@@ -0,0 +1,703 @@
+// slip-39 mnemonic shares
+// encodes shares as words from the slip-39 list so recovery officers can keep paper
+// backups: 10 bits per word, an rs1024 checksum over the last three words that catches
+// any transcription error in up to three words, and member/group thresholds in the header.
+//
+// two entry points:
+// - `generate_mnemonics` / `combine_mnemonics` implement the standard itself (groups of
+//   members, passphrase-encrypted master secret, gf(256) sharing), compatible with other
+//   slip-39 wallets
+// - `share_to_mnemonic` / `share_from_mnemonic` write one of our scalar-field `SecretShare`s in
+//   the slip-39 share format, so shares from `split_secret` go on paper and come back as
+//   `SecretShare` values that `recover_secret` accepts
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use hmac::{Hmac, Mac};
+use k256::elliptic_curve::PrimeField;
+use k256::sha2::{Digest, Sha256};
+use k256::Scalar;
+use rand::rngs::OsRng;
+use rand::{Rng, RngCore};
+
+use crate::core::gf256;
+use crate::core::scheme::{Curve, FragmentError, SecretShare, ShareMetadata};
+
+const WORDLIST: &str = include_str!("slip39_wordlist.txt");
+
+const RADIX_BITS: usize = 10;
+const ID_BITS: usize = 15;
+const ITERATION_EXP_BITS: usize = 4;
+/// identifier, extendable flag, iteration exponent, then five 4-bit fields
+const HEADER_WORDS: usize = 4;
+const CHECKSUM_WORDS: usize = 3;
+const MIN_SECRET_BYTES: usize = 16;
+const MIN_MNEMONIC_WORDS: usize = HEADER_WORDS + (MIN_SECRET_BYTES * 8).div_ceil(RADIX_BITS) + CHECKSUM_WORDS;
+const MAX_SHARE_COUNT: usize = 16;
+
+// domain separation tag for the identifiers of exported scalar-field shares
+const SHARE_IDENTIFIER_TAG: &[u8] = b"quorum/slip39-share-identifier";
+const CUSTOMIZATION: &[u8] = b"shamir";
+const CUSTOMIZATION_EXTENDABLE: &[u8] = b"shamir_extendable";
+
+// the encrypted master secret is split as the value at SECRET_INDEX, with a keyed
+// digest of it at DIGEST_INDEX so a wrong combination is detected
+const SECRET_INDEX: u8 = 255;
+const DIGEST_INDEX: u8 = 254;
+const DIGEST_BYTES: usize = 4;
+
+const BASE_ITERATION_COUNT: u32 = 10000;
+const ROUND_COUNT: u8 = 4;
+
+fn words() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| WORDLIST.lines().collect())
+}
+
+/// looks up a word, accepting any prefix of at least four letters since those are unique
+fn word_index(word: &str) -> Option<u16> {
+    let word = word.to_lowercase();
+    let list = words();
+    if let Ok(index) = list.binary_search(&word.as_str()) {
+        return Some(index as u16);
+    }
+
+    let position = list.partition_point(|w| *w < word.as_str());
+    match list.get(position) {
+        Some(candidate) if word.len() >= 4 && candidate.starts_with(&word) => Some(position as u16),
+        _ => None,
+    }
+}
+
+/// the reed-solomon code over gf(1024) behind the three checksum words
+fn rs1024_polymod(values: &[u16]) -> u32 {
+    const GENERATOR: [u32; 10] = [
+        0xe0e040, 0x1c1c080, 0x3838100, 0x7070200, 0xe0e0009, 0x1c0c2412, 0x38086c24, 0x3090fc48, 0x21b1f890,
+        0x3f3f120,
+    ];
+
+    values.iter().fold(1u32, |checksum, value| {
+        let top = checksum >> 20;
+        let mut checksum = ((checksum & 0xfffff) << 10) ^ *value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+        checksum
+    })
+}
+
+fn customization(extendable: bool) -> &'static [u8] {
+    if extendable {
+        CUSTOMIZATION_EXTENDABLE
+    } else {
+        CUSTOMIZATION
+    }
+}
+
+fn rs1024_checksum(data: &[u16], extendable: bool) -> [u16; CHECKSUM_WORDS] {
+    let mut values: Vec<u16> = customization(extendable).iter().map(|b| *b as u16).collect();
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0; CHECKSUM_WORDS]);
+    let polymod = rs1024_polymod(&values) ^ 1;
+
+    [
+        (polymod >> 20) as u16 & 1023,
+        (polymod >> 10) as u16 & 1023,
+        polymod as u16 & 1023,
+    ]
+}
+
+fn rs1024_verify(data: &[u16], extendable: bool) -> bool {
+    let mut values: Vec<u16> = customization(extendable).iter().map(|b| *b as u16).collect();
+    values.extend_from_slice(data);
+    rs1024_polymod(&values) == 1
+}
+
+/// pbkdf2-hmac-sha256, only ever asked for a few dozen bytes here
+fn pbkdf2(password: &[u8], salt: &[u8], iterations: u32, length: usize) -> Vec<u8> {
+    let prf = Hmac::<Sha256>::new_from_slice(password).expect("hmac accepts any key length");
+    let mut output = Vec::with_capacity(length);
+
+    for block in 1u32.. {
+        let mut mac = prf.clone();
+        mac.update(salt);
+        mac.update(&block.to_be_bytes());
+        let mut u = mac.finalize().into_bytes();
+        let mut t = u;
+        for _ in 1..iterations {
+            let mut mac = prf.clone();
+            mac.update(&u);
+            u = mac.finalize().into_bytes();
+            t.iter_mut().zip(&u).for_each(|(t, u)| *t ^= u);
+        }
+        output.extend_from_slice(&t);
+        if output.len() >= length {
+            break;
+        }
+    }
+
+    output.truncate(length);
+    output
+}
+
+/// the four-round feistel network that turns the master secret into the shared one
+fn feistel(
+    secret: &[u8],
+    passphrase: &[u8],
+    iteration_exponent: u8,
+    identifier: u16,
+    extendable: bool,
+    decrypt: bool,
+) -> Vec<u8> {
+    let half = secret.len() / 2;
+    let (mut left, mut right) = (secret[..half].to_vec(), secret[half..].to_vec());
+
+    // the non-extendable format also binds the identifier, so reissued shares can't be mixed in
+    let mut salt = Vec::new();
+    if !extendable {
+        salt.extend_from_slice(CUSTOMIZATION);
+        salt.extend_from_slice(&identifier.to_be_bytes());
+    }
+    let iterations = (BASE_ITERATION_COUNT << iteration_exponent) / ROUND_COUNT as u32;
+
+    let mut rounds: Vec<u8> = (0..ROUND_COUNT).collect();
+    if decrypt {
+        rounds.reverse();
+    }
+    for round in rounds {
+        let mut password = vec![round];
+        password.extend_from_slice(passphrase);
+        let mut round_salt = salt.clone();
+        round_salt.extend_from_slice(&right);
+
+        let f = pbkdf2(&password, &round_salt, iterations, right.len());
+        let mixed: Vec<u8> = left.iter().zip(&f).map(|(l, f)| l ^ f).collect();
+        left = std::mem::replace(&mut right, mixed);
+    }
+
+    [right, left].concat()
+}
+
+fn share_digest(random_part: &[u8], secret: &[u8]) -> [u8; DIGEST_BYTES] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(random_part).expect("hmac accepts any key length");
+    mac.update(secret);
+    mac.finalize().into_bytes()[..DIGEST_BYTES].try_into().unwrap()
+}
+
+/// splits one level of the scheme: member shares of a group, or group shares of the secret
+fn split_level(threshold: usize, count: usize, secret: &[u8]) -> Result<Vec<(u8, Vec<u8>)>, FragmentError> {
+    if threshold == 0 || threshold > count || count > MAX_SHARE_COUNT {
+        return Err(FragmentError::InvalidThreshold);
+    }
+    if threshold == 1 {
+        return Ok((0..count as u8).map(|i| (i, secret.to_vec())).collect());
+    }
+
+    let mut shares: Vec<(u8, Vec<u8>)> = (0..threshold as u8 - 2)
+        .map(|i| {
+            let mut value = vec![0u8; secret.len()];
+            OsRng.fill_bytes(&mut value);
+            (i, value)
+        })
+        .collect();
+
+    let mut random_part = vec![0u8; secret.len() - DIGEST_BYTES];
+    OsRng.fill_bytes(&mut random_part);
+    let digest = [share_digest(&random_part, secret).as_slice(), &random_part].concat();
+
+    let mut base: Vec<(u8, &[u8])> = shares.iter().map(|(x, value)| (*x, value.as_slice())).collect();
+    base.push((DIGEST_INDEX, &digest));
+    base.push((SECRET_INDEX, secret));
+    let rest: Vec<(u8, Vec<u8>)> = (threshold as u8 - 2..count as u8)
+        .map(|x| (x, gf256::interpolate_at(&base, x)))
+        .collect();
+
+    shares.extend(rest);
+    Ok(shares)
+}
+
+/// recovers one level of the scheme and checks its digest
+fn recover_level(threshold: usize, shares: &[(u8, &[u8])]) -> Result<Vec<u8>, FragmentError> {
+    if shares.len() < threshold {
+        return Err(FragmentError::InsufficientShares);
+    }
+    if threshold == 1 {
+        return Ok(shares[0].1.to_vec());
+    }
+
+    let secret = gf256::interpolate_at(shares, SECRET_INDEX);
+    let digest = gf256::interpolate_at(shares, DIGEST_INDEX);
+    if digest[..DIGEST_BYTES] != share_digest(&digest[DIGEST_BYTES..], &secret) {
+        return Err(FragmentError::MalformedShare(
+            "the mnemonics don't belong together (digest mismatch)".to_string(),
+        ));
+    }
+
+    Ok(secret)
+}
+
+/// one slip-39 share as encoded in a mnemonic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slip39Share {
+    /// random 15-bit identifier common to every share of one master secret
+    pub identifier: u16,
+    pub extendable: bool,
+    /// pbkdf2 runs 2500 << e iterations per feistel round
+    pub iteration_exponent: u8,
+    pub group_index: u8,
+    pub group_threshold: u8,
+    pub group_count: u8,
+    pub member_index: u8,
+    pub member_threshold: u8,
+    pub value: Vec<u8>,
+}
+
+impl Slip39Share {
+    /// encodes the share as space-separated words, checksum included
+    pub fn to_mnemonic(&self) -> String {
+        let mut header =
+            (self.identifier as u64) << 5 | (self.extendable as u64) << 4 | self.iteration_exponent as u64;
+        for field in [
+            self.group_index,
+            self.group_threshold - 1,
+            self.group_count - 1,
+            self.member_index,
+            self.member_threshold - 1,
+        ] {
+            header = header << 4 | field as u64;
+        }
+
+        let mut data: Vec<u16> = (0..HEADER_WORDS)
+            .rev()
+            .map(|i| (header >> (i * RADIX_BITS)) as u16 & 1023)
+            .collect();
+        data.extend(bytes_to_words(&self.value));
+        let checksum = rs1024_checksum(&data, self.extendable);
+        data.extend_from_slice(&checksum);
+
+        let list = words();
+        data.iter().map(|i| list[*i as usize]).collect::<Vec<_>>().join(" ")
+    }
+
+    /// decodes and checks a mnemonic produced by `to_mnemonic` or another slip-39 wallet
+    pub fn from_mnemonic(mnemonic: &str) -> Result<Self, FragmentError> {
+        let malformed = |reason: &str| FragmentError::MalformedShare(reason.to_string());
+
+        let data = mnemonic
+            .split_whitespace()
+            .map(|word| word_index(word).ok_or_else(|| malformed(&format!("'{}' is not a slip-39 word", word))))
+            .collect::<Result<Vec<u16>, _>>()?;
+        if data.len() < MIN_MNEMONIC_WORDS {
+            return Err(malformed(&format!("a mnemonic has at least {} words", MIN_MNEMONIC_WORDS)));
+        }
+
+        let header = data[..HEADER_WORDS].iter().fold(0u64, |acc, w| acc << RADIX_BITS | *w as u64);
+        let extendable = (header >> 24) & 1 == 1;
+        if !rs1024_verify(&data, extendable) {
+            return Err(malformed("checksum mismatch, check the words for typos"));
+        }
+
+        let field = |shift: usize| (header >> shift) as u8 & 0xf;
+        let value = words_to_bytes(&data[HEADER_WORDS..data.len() - CHECKSUM_WORDS])
+            .ok_or_else(|| malformed("invalid padding in the share value"))?;
+
+        let share = Slip39Share {
+            identifier: (header >> 25) as u16 & ((1 << ID_BITS) - 1),
+            extendable,
+            iteration_exponent: (header >> 20) as u8 & ((1 << ITERATION_EXP_BITS) - 1),
+            group_index: field(16),
+            group_threshold: field(12) + 1,
+            group_count: field(8) + 1,
+            member_index: field(4),
+            member_threshold: field(0) + 1,
+            value,
+        };
+        if share.group_threshold > share.group_count {
+            return Err(malformed("group threshold exceeds the group count"));
+        }
+
+        Ok(share)
+    }
+}
+
+/// packs bytes into 10-bit words, left-padding with zero bits
+fn bytes_to_words(bytes: &[u8]) -> Vec<u16> {
+    let word_count = (bytes.len() * 8).div_ceil(RADIX_BITS);
+    let mut words = Vec::with_capacity(word_count);
+    let mut accumulator = 0u32;
+    let mut bits = word_count * RADIX_BITS - bytes.len() * 8;
+
+    for byte in bytes {
+        accumulator = accumulator << 8 | *byte as u32;
+        bits += 8;
+        while bits >= RADIX_BITS {
+            bits -= RADIX_BITS;
+            words.push((accumulator >> bits) as u16 & 1023);
+        }
+    }
+
+    words
+}
+
+/// unpacks 10-bit words into bytes; the padding must be under a byte and all zero
+fn words_to_bytes(words: &[u16]) -> Option<Vec<u8>> {
+    let padding = words.len() * RADIX_BITS % 8;
+    let length = words.len() * RADIX_BITS / 8;
+    if length < MIN_SECRET_BYTES || !length.is_multiple_of(2) {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(length);
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+    for (i, word) in words.iter().enumerate() {
+        accumulator = accumulator << RADIX_BITS | *word as u32;
+        bits += RADIX_BITS;
+        if i == 0 {
+            if (accumulator >> (RADIX_BITS - padding)) != 0 {
+                return None;
+            }
+            bits -= padding;
+            accumulator &= (1 << bits) - 1;
+        }
+        while bits >= 8 {
+            bits -= 8;
+            bytes.push((accumulator >> bits) as u8);
+            accumulator &= (1 << bits) - 1;
+        }
+    }
+
+    Some(bytes)
+}
+
+/// splits a master secret into slip-39 mnemonics
+///
+/// - master_secret: at least 16 bytes, even length
+/// - passphrase: encrypts the master secret, the same mnemonics give a different
+///   secret under another passphrase
+/// - iteration_exponent: slows down passphrase brute force, 2500 << e pbkdf2 rounds
+/// - group_threshold, groups: how many groups are needed, and (member threshold,
+///   member count) for every group
+///
+/// returns the mnemonics of every group
+pub fn generate_mnemonics(
+    master_secret: &[u8],
+    passphrase: &[u8],
+    iteration_exponent: u8,
+    group_threshold: u8,
+    groups: &[(u8, u8)],
+) -> Result<Vec<Vec<String>>, FragmentError> {
+    if master_secret.len() < MIN_SECRET_BYTES || !master_secret.len().is_multiple_of(2) {
+        return Err(FragmentError::MalformedShare(format!(
+            "the master secret must be an even number of bytes, at least {}",
+            MIN_SECRET_BYTES
+        )));
+    }
+    if iteration_exponent >= 1 << ITERATION_EXP_BITS {
+        return Err(FragmentError::MalformedShare("iteration exponent too large".to_string()));
+    }
+    // a 1-of-n group would just hand out n copies of the group share
+    if groups.iter().any(|(threshold, count)| *threshold == 1 && *count > 1) {
+        return Err(FragmentError::InvalidThreshold);
+    }
+
+    let identifier = OsRng.gen_range(0..1u16 << ID_BITS);
+    let extendable = true;
+    let encrypted = feistel(master_secret, passphrase, iteration_exponent, identifier, extendable, false);
+
+    let group_shares = split_level(group_threshold as usize, groups.len(), &encrypted)?;
+    group_shares
+        .into_iter()
+        .zip(groups)
+        .map(|((group_index, group_secret), (member_threshold, member_count))| {
+            let members = split_level(*member_threshold as usize, *member_count as usize, &group_secret)?;
+            Ok(members
+                .into_iter()
+                .map(|(member_index, value)| {
+                    Slip39Share {
+                        identifier,
+                        extendable,
+                        iteration_exponent,
+                        group_index,
+                        group_threshold,
+                        group_count: groups.len() as u8,
+                        member_index,
+                        member_threshold: *member_threshold,
+                        value,
+                    }
+                    .to_mnemonic()
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// combines enough slip-39 mnemonics to recover the master secret
+/// every mnemonic must be valid and come from the same split; groups with too few
+/// members are ignored as long as enough complete groups remain
+pub fn combine_mnemonics(mnemonics: &[&str], passphrase: &[u8]) -> Result<Vec<u8>, FragmentError> {
+    let shares = mnemonics
+        .iter()
+        .map(|mnemonic| Slip39Share::from_mnemonic(mnemonic))
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = shares.first().ok_or(FragmentError::InsufficientShares)?;
+
+    let common = |share: &Slip39Share| {
+        (
+            share.identifier,
+            share.extendable,
+            share.iteration_exponent,
+            share.group_threshold,
+            share.group_count,
+            share.value.len(),
+        )
+    };
+    if shares.iter().any(|share| common(share) != common(first)) {
+        return Err(FragmentError::MixedShareSets);
+    }
+
+    let mut groups: BTreeMap<u8, Vec<&Slip39Share>> = BTreeMap::new();
+    for share in &shares {
+        let members = groups.entry(share.group_index).or_default();
+        if members.iter().any(|member| member.member_index == share.member_index) {
+            return Err(FragmentError::MalformedShare(format!(
+                "member {} of group {} was given twice",
+                share.member_index + 1,
+                share.group_index + 1
+            )));
+        }
+        if members.iter().any(|member| member.member_threshold != share.member_threshold) {
+            return Err(FragmentError::MixedShareSets);
+        }
+        members.push(share);
+    }
+
+    let mut group_shares = Vec::new();
+    for (group_index, members) in &groups {
+        let threshold = members[0].member_threshold as usize;
+        if members.len() >= threshold {
+            let points: Vec<(u8, &[u8])> = members.iter().map(|m| (m.member_index, m.value.as_slice())).collect();
+            group_shares.push((*group_index, recover_level(threshold, &points)?));
+        }
+    }
+
+    let points: Vec<(u8, &[u8])> = group_shares.iter().map(|(x, value)| (*x, value.as_slice())).collect();
+    let encrypted = recover_level(first.group_threshold as usize, &points)?;
+
+    Ok(feistel(
+        &encrypted,
+        passphrase,
+        first.iteration_exponent,
+        first.identifier,
+        first.extendable,
+        true,
+    ))
+}
+
+/// writes one scalar-field share in the slip-39 share format
+///
+/// the member index is x - 1 and the member threshold is the share's recorded threshold,
+/// in a single 1-of-1 group; the value is y, passphrase-encrypted like a master secret.
+/// all shares of one split and epoch get the same identifier, see `share_identifier`
+pub fn share_to_mnemonic(share: &SecretShare, passphrase: &[u8]) -> Result<String, FragmentError> {
+    let meta = share.meta.as_ref().ok_or_else(|| {
+        FragmentError::MalformedShare("only shares that record their threshold can be exported".to_string())
+    })?;
+    let index = (1..=MAX_SHARE_COUNT as u64)
+        .find(|i| Scalar::from(*i) == share.x)
+        .ok_or_else(|| FragmentError::MalformedShare("slip-39 holds shares at x = 1 to 16".to_string()))?;
+    if meta.threshold as usize > MAX_SHARE_COUNT {
+        return Err(FragmentError::InvalidThreshold);
+    }
+
+    let identifier = share_identifier(&meta.set_id, meta.epoch);
+    let extendable = false;
+    let iteration_exponent = 1;
+
+    Ok(Slip39Share {
+        identifier,
+        extendable,
+        iteration_exponent,
+        group_index: 0,
+        group_threshold: 1,
+        group_count: 1,
+        member_index: index as u8 - 1,
+        member_threshold: meta.threshold as u8,
+        value: feistel(
+            &share.y.expose_secret().to_bytes(),
+            passphrase,
+            iteration_exponent,
+            identifier,
+            extendable,
+            false,
+        ),
+    }
+    .to_mnemonic())
+}
+
+/// the 15-bit slip-39 identifier for the shares of one split at one refresh epoch
+///
+/// the epoch isn't part of the mnemonic, so it is hashed into the identifier together
+/// with the share-set id: mnemonics written before and after a refresh then come back as
+/// different share sets, instead of being combined into a wrong key. 15 bits is all the
+/// format has room for, so two unrelated splits or epochs still share an identifier with
+/// probability 2^-15; checking extra mnemonics against the threshold (as
+/// `recover_secret_checked` does) catches that case
+fn share_identifier(set_id: &[u8; 16], epoch: u64) -> u16 {
+    let digest = Sha256::new()
+        .chain_update(SHARE_IDENTIFIER_TAG)
+        .chain_update(set_id)
+        .chain_update(epoch.to_be_bytes())
+        .finalize();
+    u16::from_be_bytes([digest[0], digest[1]]) >> 1
+}
+
+/// reads a share written by `share_to_mnemonic` back into a `SecretShare`
+/// the share comes back tagged with its threshold and a share-set id holding just the
+/// mnemonic's 15-bit identifier, which stands in for the original id and epoch (see
+/// `share_identifier`); the share count isn't recorded, so `total` is 0
+pub fn share_from_mnemonic(mnemonic: &str, passphrase: &[u8]) -> Result<SecretShare, FragmentError> {
+    let share = Slip39Share::from_mnemonic(mnemonic)?;
+    if share.group_count != 1 || share.value.len() != 32 {
+        return Err(FragmentError::MalformedShare("not a quorum share mnemonic".to_string()));
+    }
+
+    let y_bytes = feistel(
+        &share.value,
+        passphrase,
+        share.iteration_exponent,
+        share.identifier,
+        share.extendable,
+        true,
+    );
+    let y_repr: [u8; 32] = y_bytes.try_into().unwrap();
+    let y = Option::from(Scalar::from_repr(y_repr.into()))
+        .ok_or_else(|| FragmentError::MalformedShare("share value out of range, wrong passphrase?".to_string()))?;
+
+    let mut set_id = [0u8; 16];
+    set_id[..2].copy_from_slice(&share.identifier.to_be_bytes());
+
+    Ok(SecretShare::tagged(
+        Scalar::from(share.member_index as u64 + 1),
+        y,
+        ShareMetadata {
+            threshold: share.member_threshold as u32,
+            total: 0,
+            set_id,
+            curve: Curve::Secp256k1,
+            epoch: 0,
+            checksum: [0; 8],
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::scheme::{recover_secret, split_secret};
+
+    #[test]
+    fn test_wordlist() {
+        let list = words();
+        assert_eq!(list.len(), 1024);
+        assert!(list.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(word_index("academic"), Some(0));
+        assert_eq!(word_index("acad"), Some(0));
+        assert_eq!(word_index("zero"), Some(1023));
+        assert_eq!(word_index("bitcoin"), None);
+    }
+
+    #[test]
+    fn test_reference_vectors() {
+        // from the slip-39 test vectors, passphrase "TREZOR"
+        let single = "duckling enlarge academic academic agency result length solution fridge kidney \
+                      coal piece deal husband erode duke ajar critical decision keyboard";
+        assert_eq!(
+            hex::encode(combine_mnemonics(&[single], b"TREZOR").unwrap()),
+            "bb54aac4b89dc868ba37d9cc21b2cece"
+        );
+
+        // the same mnemonic with the last checksum word changed
+        let typo = "duckling enlarge academic academic agency result length solution fridge kidney \
+                    coal piece deal husband erode duke ajar critical decision kidney";
+        assert!(combine_mnemonics(&[typo], b"TREZOR").is_err());
+
+        // basic 2-of-3 sharing
+        let pair = [
+            "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang \
+             wrist rescue view short owner flip making coding armed",
+            "shadow pistol academic acid actress prayer class unknown daughter sweater depict \
+             flip twice unkind craft early superior advocate guest smoking",
+        ];
+        assert_eq!(
+            hex::encode(combine_mnemonics(&pair, b"TREZOR").unwrap()),
+            "b43ceb7e57a0ea8766221624d01b0864"
+        );
+        assert!(matches!(
+            combine_mnemonics(&pair[..1], b"TREZOR"),
+            Err(FragmentError::InsufficientShares)
+        ));
+    }
+
+    #[test]
+    fn test_groups_and_passphrase() {
+        let mut master = [0u8; 32];
+        OsRng.fill_bytes(&mut master);
+
+        // 2 of 3 departments, each needing 2 of its 4 officers
+        let groups = generate_mnemonics(&master, b"vault", 0, 2, &[(2, 4), (2, 4), (2, 4)]).unwrap();
+        assert_eq!(groups.len(), 3);
+        assert!(groups.iter().all(|members| members.len() == 4));
+
+        let chosen = [
+            groups[0][1].as_str(),
+            groups[0][3].as_str(),
+            groups[2][0].as_str(),
+            groups[2][2].as_str(),
+        ];
+        assert_eq!(combine_mnemonics(&chosen, b"vault").unwrap(), master);
+        assert_ne!(combine_mnemonics(&chosen, b"wrong").unwrap(), master);
+
+        // one complete group plus a lone member of another isn't enough
+        let short = [groups[0][1].as_str(), groups[0][3].as_str(), groups[1][0].as_str()];
+        assert!(matches!(combine_mnemonics(&short, b"vault"), Err(FragmentError::InsufficientShares)));
+    }
+
+    #[test]
+    fn test_scalar_share_round_trip() {
+        let secret = Scalar::from(123456789u64);
+        let shares = split_secret(&secret, 3, 5).unwrap();
+
+        let mnemonics: Vec<String> = shares
+            .iter()
+            .map(|share| share_to_mnemonic(share, b"").unwrap())
+            .collect();
+        assert!(mnemonics.iter().all(|m| m.split(' ').count() == 33));
+
+        let imported: Vec<SecretShare> = mnemonics[1..4]
+            .iter()
+            .map(|m| share_from_mnemonic(m, b"").unwrap())
+            .collect();
+        assert_eq!(imported[0].y, shares[1].y);
+        assert_eq!(*recover_secret(&imported).unwrap().expose_secret(), secret);
+
+        // the recorded threshold still applies after the round trip
+        assert!(matches!(
+            recover_secret(&imported[..2]),
+            Err(FragmentError::InsufficientShares)
+        ));
+
+        // the same split after a refresh gets a different identifier, so the two epochs don't mix
+        let at_epoch = |share: &SecretShare, epoch: u64| {
+            let meta = ShareMetadata { set_id: [7; 16], epoch, ..share.meta.clone().unwrap() };
+            let y = *share.y.expose_secret() + Scalar::from(epoch);
+            let mnemonic = share_to_mnemonic(&SecretShare::tagged(share.x, y, meta), b"").unwrap();
+            share_from_mnemonic(&mnemonic, b"").unwrap()
+        };
+        let mixed = [at_epoch(&shares[0], 0), at_epoch(&shares[1], 0), at_epoch(&shares[2], 1)];
+        assert!(matches!(recover_secret(&mixed), Err(FragmentError::MixedShareSets)));
+    }
+}
@@ -0,0 +1,252 @@
+// verifiable secret sharing (feldman and pedersen vss)
+// publishes commitments to the polynomial so custodians can check their shares
+
+use k256::elliptic_curve::group::GroupEncoding;
+use k256::sha2::{Digest, Sha256};
+use k256::{ProjectivePoint, Scalar};
+
+use crate::core::math::random_scalar;
+use crate::core::scheme::{random_polynomial, shares_from_polynomial, tag_shares, FragmentError, SecretShare};
+
+/// commits to every coefficient of a polynomial
+/// returns [g^a0, g^a1, g^a2, ...] where g is the secp256k1 generator
+pub fn commit_polynomial(coefficients: &[Scalar]) -> Vec<ProjectivePoint> {
+    coefficients
+        .iter()
+        .map(|coeff| ProjectivePoint::GENERATOR * coeff)
+        .collect()
+}
+
+/// evaluates committed polynomial "in the exponent" at point x
+/// returns C0 * C1^x * C2^(x^2) * ... which equals g^f(x) for honest commitments
+pub fn evaluate_commitments(commitments: &[ProjectivePoint], x: &Scalar) -> ProjectivePoint {
+    // horner's method again, just with point addition and scalar multiplication
+    commitments
+        .iter()
+        .rev()
+        .fold(ProjectivePoint::IDENTITY, |acc, commitment| acc * x + commitment)
+}
+
+/// splits a secret like `split_secret`, but also returns feldman commitments
+///
+/// - secret: the value to split (typically a private key as a scalar)
+/// - threshold: minimum number of shares needed to reconstruct
+/// - total_shares: total number of shares to generate
+///
+/// returns the shares and the commitments to each polynomial coefficient.
+/// the first commitment is g^secret, i.e. the public key of the shared secret
+pub fn split_secret_verifiable(
+    secret: &Scalar,
+    threshold: usize,
+    total_shares: usize,
+) -> Result<(Vec<SecretShare>, Vec<ProjectivePoint>), FragmentError> {
+    if threshold < 2 || threshold > total_shares {
+        return Err(FragmentError::InvalidThreshold);
+    }
+
+    let coefficients = random_polynomial(secret, threshold);
+    let shares = tag_shares(shares_from_polynomial(&coefficients, total_shares), threshold);
+    let commitments = commit_polynomial(&coefficients);
+
+    Ok((shares, commitments))
+}
+
+/// checks a share against the dealer's feldman commitments
+/// returns true if g^y matches the commitments evaluated at the share's x
+pub fn verify_share(share: &SecretShare, commitments: &[ProjectivePoint]) -> bool {
+    if commitments.is_empty() {
+        return false;
+    }
+
+    let expected = evaluate_commitments(commitments, &share.x);
+    let actual = ProjectivePoint::GENERATOR * *share.y.expose_secret();
+
+    expected == actual
+}
+
+/// domain separation tag for deriving the pedersen generator h
+const PEDERSEN_GENERATOR_TAG: &[u8] = b"quorum/pedersen-vss/generator-h";
+
+/// a pedersen share: the regular shamir share plus the blinding polynomial's value at x
+/// only `share` is needed for recovery, `blinding` is used for verification
+#[derive(Debug, Clone)]
+pub struct PedersenShare {
+    pub share: SecretShare,
+    pub blinding: Scalar,
+}
+
+/// derives the second generator h for pedersen commitments
+///
+/// h is found by hashing a fixed tag with a counter until the digest is a valid
+/// x coordinate on the curve (try-and-increment). nobody knows log_g(h), which
+/// is what makes the commitments binding
+pub fn pedersen_generator() -> ProjectivePoint {
+    let mut counter: u32 = 0;
+
+    loop {
+        let digest = Sha256::new()
+            .chain_update(PEDERSEN_GENERATOR_TAG)
+            .chain_update(counter.to_be_bytes())
+            .finalize();
+
+        // interpreting the digest as the x coordinate of a point with even y
+        let mut encoded = [0u8; 33];
+        encoded[0] = 0x02;
+        encoded[1..].copy_from_slice(&digest);
+
+        let candidate: Option<ProjectivePoint> =
+            ProjectivePoint::from_bytes((&encoded).into()).into();
+        if let Some(point) = candidate {
+            return point;
+        }
+
+        counter += 1;
+    }
+}
+
+/// commits to a polynomial and a blinding polynomial of the same degree
+/// returns [g^a0 * h^b0, g^a1 * h^b1, ...]
+pub fn commit_polynomial_pedersen(
+    coefficients: &[Scalar],
+    blinding_coefficients: &[Scalar],
+) -> Vec<ProjectivePoint> {
+    let h = pedersen_generator();
+
+    coefficients
+        .iter()
+        .zip(blinding_coefficients)
+        .map(|(a, b)| ProjectivePoint::GENERATOR * a + h * b)
+        .collect()
+}
+
+/// splits a secret with pedersen vss
+///
+/// unlike feldman vss the commitments are information-theoretically hiding,
+/// so they reveal nothing about the secret (not even g^secret).
+///
+/// returns the shares and the commitments to each pair of coefficients.
+/// the inner `SecretShare` values can be passed straight to `recover_secret`
+pub fn split_secret_pedersen(
+    secret: &Scalar,
+    threshold: usize,
+    total_shares: usize,
+) -> Result<(Vec<PedersenShare>, Vec<ProjectivePoint>), FragmentError> {
+    if threshold < 2 || threshold > total_shares {
+        return Err(FragmentError::InvalidThreshold);
+    }
+
+    // the blinding polynomial has a random constant term too
+    let coefficients = random_polynomial(secret, threshold);
+    let blinding_coefficients = random_polynomial(&random_scalar(), threshold);
+
+    let shares = tag_shares(shares_from_polynomial(&coefficients, total_shares), threshold);
+    let blindings = shares_from_polynomial(&blinding_coefficients, total_shares);
+
+    let pedersen_shares = shares
+        .into_iter()
+        .zip(blindings)
+        .map(|(share, blinding)| PedersenShare {
+            share,
+            blinding: *blinding.y.expose_secret(),
+        })
+        .collect();
+
+    let commitments = commit_polynomial_pedersen(&coefficients, &blinding_coefficients);
+
+    Ok((pedersen_shares, commitments))
+}
+
+/// checks a pedersen share against the dealer's commitments
+/// returns true if g^y * h^blinding matches the commitments evaluated at the share's x
+pub fn verify_pedersen_share(share: &PedersenShare, commitments: &[ProjectivePoint]) -> bool {
+    if commitments.is_empty() {
+        return false;
+    }
+
+    let expected = evaluate_commitments(commitments, &share.share.x);
+    let actual =
+        ProjectivePoint::GENERATOR * *share.share.y.expose_secret() + pedersen_generator() * share.blinding;
+
+    expected == actual
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::scheme::recover_secret;
+
+    #[test]
+    fn test_verifiable_split() {
+        let secret = Scalar::from(987654321u64);
+        let (shares, commitments) = split_secret_verifiable(&secret, 3, 5).unwrap();
+
+        // one commitment per coefficient, the first one is g^secret
+        assert_eq!(commitments.len(), 3);
+        assert_eq!(commitments[0], ProjectivePoint::GENERATOR * secret);
+
+        // every honest share verifies
+        for share in &shares {
+            assert!(verify_share(share, &commitments));
+        }
+
+        // and the shares still recover the secret
+        assert_eq!(*recover_secret(&shares[1..4]).unwrap().expose_secret(), secret);
+    }
+
+    #[test]
+    fn test_tampered_share_rejected() {
+        let secret = Scalar::from(42u64);
+        let (shares, commitments) = split_secret_verifiable(&secret, 2, 3).unwrap();
+
+        // a share with a modified y value should not verify
+        let tampered = SecretShare::new(shares[0].x, *shares[0].y.expose_secret() + Scalar::ONE);
+        assert!(!verify_share(&tampered, &commitments));
+
+        // a valid share moved to a different x should not verify either
+        let moved = SecretShare::new(shares[1].x, *shares[0].y.expose_secret());
+        assert!(!verify_share(&moved, &commitments));
+    }
+
+    #[test]
+    fn test_pedersen_split() {
+        let secret = Scalar::from(31337u64);
+        let (shares, commitments) = split_secret_pedersen(&secret, 3, 5).unwrap();
+        assert_eq!(commitments.len(), 3);
+
+        // every honest share verifies against the pedersen commitments
+        for share in &shares {
+            assert!(verify_pedersen_share(share, &commitments));
+        }
+
+        // the commitments do not expose g^secret the way feldman ones do
+        assert_ne!(commitments[0], ProjectivePoint::GENERATOR * secret);
+
+        // recover_secret works on the inner shares
+        let plain: Vec<SecretShare> = shares.iter().map(|s| s.share.clone()).collect();
+        assert_eq!(*recover_secret(&plain[0..3]).unwrap().expose_secret(), secret);
+        assert_eq!(*recover_secret(&plain[2..5]).unwrap().expose_secret(), secret);
+    }
+
+    #[test]
+    fn test_pedersen_tampered_share_rejected() {
+        let secret = Scalar::from(7u64);
+        let (shares, commitments) = split_secret_pedersen(&secret, 2, 3).unwrap();
+
+        let mut tampered = shares[0].clone();
+        *tampered.share.y.expose_secret_mut() += Scalar::ONE;
+        assert!(!verify_pedersen_share(&tampered, &commitments));
+
+        let mut tampered = shares[1].clone();
+        tampered.blinding += Scalar::ONE;
+        assert!(!verify_pedersen_share(&tampered, &commitments));
+    }
+
+    #[test]
+    fn test_pedersen_generator_is_independent() {
+        // h is deterministic and distinct from g
+        let h = pedersen_generator();
+        assert_eq!(h, pedersen_generator());
+        assert_ne!(h, ProjectivePoint::GENERATOR);
+        assert_ne!(h, ProjectivePoint::IDENTITY);
+    }
+}
@@ -0,0 +1,122 @@
+// weighted threshold sharing
+// lets custodians count for different numbers of votes: a custodian with weight w
+// receives w shares at distinct x points of one polynomial, and any set of custodians
+// whose weights add up to the threshold holds enough points to recover the secret
+
+use std::collections::BTreeSet;
+
+use k256::{ProjectivePoint, Scalar};
+
+use crate::core::scheme::{recover_secret, FragmentError, SecretShare};
+use crate::core::secret::SecretScalar;
+use crate::core::vss::split_secret_verifiable;
+
+/// the shares held by one custodian, one per vote it carries
+#[derive(Debug, Clone)]
+pub struct WeightedShare {
+    pub custodian: u32,
+    pub shares: Vec<SecretShare>,
+}
+
+impl WeightedShare {
+    /// the number of votes this custodian counts for
+    pub fn weight(&self) -> usize {
+        self.shares.len()
+    }
+}
+
+/// splits a secret among custodians of different weights
+///
+/// - threshold: the combined weight needed to reconstruct
+/// - weights: every custodian's weight, custodian i + 1 gets weights[i] shares
+///
+/// returns every custodian's shares and the feldman commitments to the polynomial
+pub fn split_secret_weighted(
+    secret: &Scalar,
+    threshold: usize,
+    weights: &[usize],
+) -> Result<(Vec<WeightedShare>, Vec<ProjectivePoint>), FragmentError> {
+    if weights.contains(&0) {
+        return Err(FragmentError::InvalidThreshold);
+    }
+
+    let total: usize = weights.iter().sum();
+    let (shares, commitments) = split_secret_verifiable(secret, threshold, total)?;
+
+    // handing out consecutive x points, custodian by custodian
+    let mut points = shares.into_iter();
+    let custodians = weights
+        .iter()
+        .zip(1u32..)
+        .map(|(weight, custodian)| WeightedShare {
+            custodian,
+            shares: points.by_ref().take(*weight).collect(),
+        })
+        .collect();
+
+    Ok((custodians, commitments))
+}
+
+/// recovers a secret once the custodians' combined weight meets the threshold
+/// fails with InsufficientShares below the threshold, without interpolating anything
+pub fn recover_secret_weighted(custodians: &[WeightedShare], threshold: usize) -> Result<SecretScalar, FragmentError> {
+    let unique: BTreeSet<u32> = custodians.iter().map(|c| c.custodian).collect();
+    if unique.len() != custodians.len() {
+        return Err(FragmentError::MalformedShare("a custodian was counted twice".to_string()));
+    }
+
+    let weight: usize = custodians.iter().map(WeightedShare::weight).sum();
+    if threshold < 2 || weight < threshold {
+        return Err(FragmentError::InsufficientShares);
+    }
+
+    let shares: Vec<SecretShare> = custodians.iter().flat_map(|c| c.shares.iter().cloned()).collect();
+    recover_secret(&shares)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::vss::verify_share;
+
+    #[test]
+    fn test_heavy_custodian_counts_twice() {
+        let secret = Scalar::from(123456789u64);
+        // the hsm-backed node counts for two votes, the other three for one
+        let (custodians, commitments) = split_secret_weighted(&secret, 3, &[2, 1, 1, 1]).unwrap();
+
+        assert_eq!(custodians[0].weight(), 2);
+        assert!(custodians
+            .iter()
+            .flat_map(|c| &c.shares)
+            .all(|share| verify_share(share, &commitments)));
+
+        // the hsm node plus any one other node
+        let pair = [custodians[0].clone(), custodians[3].clone()];
+        assert_eq!(*recover_secret_weighted(&pair, 3).unwrap().expose_secret(), secret);
+
+        // or three ordinary nodes
+        assert_eq!(*recover_secret_weighted(&custodians[1..], 3).unwrap().expose_secret(), secret);
+    }
+
+    #[test]
+    fn test_insufficient_weight_rejected() {
+        let secret = Scalar::from(42u64);
+        let (custodians, _) = split_secret_weighted(&secret, 3, &[2, 1, 1]).unwrap();
+
+        assert!(matches!(
+            recover_secret_weighted(&custodians[1..], 3),
+            Err(FragmentError::InsufficientShares)
+        ));
+        assert!(matches!(
+            recover_secret_weighted(&custodians[..1], 3),
+            Err(FragmentError::InsufficientShares)
+        ));
+
+        let repeated = [custodians[1].clone(), custodians[1].clone(), custodians[2].clone()];
+        assert!(recover_secret_weighted(&repeated, 3).is_err());
+
+        assert!(split_secret_weighted(&secret, 2, &[1, 0]).is_err());
+        assert!(split_secret_weighted(&secret, 5, &[2, 2]).is_err());
+    }
+}
@@ -5,6 +5,13 @@ pub mod core;
 pub mod network;
 
 // re-exporting the main types for convenient access
-pub use core::scheme::{split_secret, recover_secret, Share, FragmentError};
+pub use core::scheme::{
+    split_secret, recover_secret, recover_secret_checked, recover_secret_robust, verify_consistency, SecretShare,
+    ShareMetadata, FragmentError,
+};
+pub use core::secret::SecretScalar;
+pub use core::gf256::{split_bytes, recover_bytes, ByteShare};
+pub use core::dkg::{DkgParticipant, DkgOutput};
+pub use core::vss::{split_secret_verifiable, verify_share, split_secret_pedersen, verify_pedersen_share, PedersenShare};
 pub use network::{ShareStore, CustodianService, CustodianServer, CustodianClient, ShareData, RetrieveRequest};
 
@@ -1,18 +1,17 @@
-// fragment cli demo
+// quorum cli demo
 // demonstrates splitting a secret into shares and recovering it
 
-use fragment::{split_secret, recover_secret};
-use k256::Scalar;
-use rand::rngs::OsRng;
+use quorum::core::math::random_scalar;
+use quorum::{split_secret, recover_secret, SecretScalar};
 
 fn main() {
-    println!("=== fragment: shamir's secret sharing demo ===\n");
+    println!("=== quorum: shamir's secret sharing demo ===\n");
 
     // generating a random secret (simulating a private key)
-    let mut rng = OsRng;
-    let secret = Scalar::generate_vartime(&mut rng);
+    let secret = SecretScalar::new(random_scalar());
 
-    println!("original secret: {:?}\n", secret);
+    // secrets and share values print as [redacted], compare them instead of eyeballing
+    println!("original secret: {}\n", secret);
 
     // splitting into 3 shares with a threshold of 2
     let threshold = 2;
@@ -23,12 +22,18 @@ fn main() {
         total_shares, threshold
     );
 
-    let shares = split_secret(&secret, threshold, total_shares).expect("failed to split secret");
+    let shares = split_secret(secret.expose_secret(), threshold, total_shares).expect("failed to split secret");
 
     // printing all shares
-    println!("generated shares:");
+    let meta = shares[0].meta.as_ref().expect("split shares are tagged");
+    println!(
+        "generated shares (set {}, {}-of-{}):",
+        hex::encode(meta.set_id),
+        meta.threshold,
+        meta.total
+    );
     for (i, share) in shares.iter().enumerate() {
-        println!("  share {}: x={:?}, y={:?}", i + 1, share.x, share.y);
+        println!("  share {}: x={:?}, y={}", i + 1, share.x, share.y);
     }
     println!();
 
@@ -38,7 +43,7 @@ fn main() {
     let selected_shares = &shares[0..2];
     let recovered = recover_secret(selected_shares).expect("failed to recover secret");
 
-    println!("recovered secret: {:?}\n", recovered);
+    println!("recovered secret: {}\n", recovered);
 
     // verifying correctness
     if recovered == secret {
@@ -0,0 +1,417 @@
+// conversions between shares, the threshold ecdsa and decryption messages and their protobuf form
+// shared by the node and the client, which both check the messages they relay
+// big integers travel as big-endian bytes, proofs as their own byte encodings
+
+// tonic::Status is large, but it is what the grpc handlers return anyway
+#![allow(clippy::result_large_err)]
+
+use k256::{ProjectivePoint, Scalar};
+use num_bigint::BigUint;
+use tonic::Status;
+
+use crate::core::decryption::{self, DleqProof};
+use crate::core::ecdsa::{
+    AuditReveal, Decryption, MtaMessage, Round1Message, Round3Message, Round4Message, Round5Output,
+    SchnorrProof, SigmaProof, SignatureShare, SignerSetup,
+};
+use crate::core::mta::{LogProof, RangeProof, RespondentProof};
+use crate::core::paillier::{FactorProof, ModulusProof, PaillierPublicKey, RingPedersenParams, RingPedersenProof};
+use crate::core::scheme::{Curve, SecretShare, ShareMetadata};
+use crate::core::secret::SecretScalar;
+use crate::network::service::proto;
+use crate::network::storage::{
+    bytes_to_point, bytes_to_scalar, bytes_to_secret, point_to_bytes, scalar_to_bytes, secret_to_bytes,
+};
+
+fn biguint_from(bytes: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(bytes)
+}
+
+pub(crate) fn scalar_from(bytes: &[u8], field: &str) -> Result<Scalar, Status> {
+    bytes_to_scalar(bytes).ok_or_else(|| Status::invalid_argument(format!("malformed {}", field)))
+}
+
+pub(crate) fn secret_from(bytes: &[u8], field: &str) -> Result<SecretScalar, Status> {
+    bytes_to_secret(bytes).ok_or_else(|| Status::invalid_argument(format!("malformed {}", field)))
+}
+
+fn point_from(bytes: &[u8], field: &str) -> Result<ProjectivePoint, Status> {
+    bytes_to_point(bytes).ok_or_else(|| Status::invalid_argument(format!("malformed {}", field)))
+}
+
+fn curve_name(curve: &Curve) -> &'static str {
+    match curve {
+        Curve::Secp256k1 => "secp256k1",
+    }
+}
+
+fn curve_from(name: &str) -> Result<Curve, Status> {
+    match name {
+        "secp256k1" => Ok(Curve::Secp256k1),
+        other => Err(Status::invalid_argument(format!("unknown curve '{}'", other))),
+    }
+}
+
+/// a share with the metadata of its split, other fields are left for the caller to fill in
+/// the epoch is the share's own, a node reports its stored epoch for untagged shares
+pub fn share_to_proto(ceremony_id: &str, share: &SecretShare) -> proto::ShareData {
+    let meta = share.meta.as_ref();
+    proto::ShareData {
+        ceremony_id: ceremony_id.to_string(),
+        x: scalar_to_bytes(&share.x),
+        y: secret_to_bytes(&share.y),
+        commitments: Vec::new(),
+        epoch: meta.map_or(0, |meta| meta.epoch),
+        packed_secrets: 0,
+        privacy_threshold: 0,
+        threshold: meta.map_or(0, |meta| meta.threshold),
+        total: meta.map_or(0, |meta| meta.total),
+        set_id: meta.map(|meta| meta.set_id.to_vec()).unwrap_or_default(),
+        curve: meta.map(|meta| curve_name(&meta.curve).to_string()).unwrap_or_default(),
+        checksum: meta.map(|meta| meta.checksum.to_vec()).unwrap_or_default(),
+    }
+}
+
+/// decodes a share and, if it has a threshold, the metadata of its split
+/// the checksum is carried over as is, so a share tampered with in transit fails `checksum_valid`
+pub fn share_from_proto(data: &proto::ShareData) -> Result<SecretShare, Status> {
+    let meta = if data.threshold == 0 {
+        None
+    } else {
+        let curve = curve_from(&data.curve)?;
+        Some(ShareMetadata {
+            threshold: data.threshold,
+            total: data.total,
+            set_id: data
+                .set_id
+                .as_slice()
+                .try_into()
+                .map_err(|_| Status::invalid_argument("malformed share-set id"))?,
+            curve,
+            epoch: data.epoch,
+            checksum: data
+                .checksum
+                .as_slice()
+                .try_into()
+                .map_err(|_| Status::invalid_argument("malformed share checksum"))?,
+        })
+    };
+
+    Ok(SecretShare {
+        x: scalar_from(&data.x, "share x")?,
+        y: secret_from(&data.y, "share y")?,
+        meta,
+    })
+}
+
+/// a holder's view of its sharing, left unsigned for the caller to sign
+pub fn sharing_view(
+    index: u32,
+    commitments: &[ProjectivePoint],
+    epoch: u64,
+    dealt: bool,
+    meta: Option<&ShareMetadata>,
+) -> proto::SharingView {
+    proto::SharingView {
+        index,
+        commitments: commitments.iter().map(point_to_bytes).collect(),
+        epoch,
+        dealt,
+        threshold: meta.map_or(0, |meta| meta.threshold),
+        total: meta.map_or(0, |meta| meta.total),
+        set_id: meta.map(|meta| meta.set_id.to_vec()).unwrap_or_default(),
+        curve: meta.map(|meta| curve_name(&meta.curve).to_string()).unwrap_or_default(),
+        signature: Vec::new(),
+    }
+}
+
+/// the split metadata a sharing view reports, none if the holder's share carries none
+/// the checksum is left empty, `SecretShare::tagged` computes it for the share being tagged
+pub fn view_metadata(view: &proto::SharingView) -> Result<Option<ShareMetadata>, Status> {
+    if view.threshold == 0 {
+        return Ok(None);
+    }
+    Ok(Some(ShareMetadata {
+        threshold: view.threshold,
+        total: view.total,
+        set_id: view
+            .set_id
+            .as_slice()
+            .try_into()
+            .map_err(|_| Status::invalid_argument("malformed share-set id"))?,
+        curve: curve_from(&view.curve)?,
+        epoch: view.epoch,
+        checksum: [0; 8],
+    }))
+}
+
+pub fn setup_to_proto(setup: &SignerSetup) -> proto::EcdsaSetup {
+    proto::EcdsaSetup {
+        index: setup.index,
+        paillier_n: setup.paillier.n.to_bytes_be(),
+        n_tilde: setup.ring_pedersen.n_tilde.to_bytes_be(),
+        h1: setup.ring_pedersen.h1.to_bytes_be(),
+        h2: setup.ring_pedersen.h2.to_bytes_be(),
+        proof: setup.proof.to_bytes(),
+        modulus_proof: setup.modulus_proof.to_bytes(),
+        factor_proofs: setup
+            .factor_proofs
+            .iter()
+            .map(|(verifier, proof)| proto::EcdsaFactorProof {
+                verifier: *verifier,
+                proof: proof.to_bytes(),
+            })
+            .collect(),
+    }
+}
+
+pub fn setup_from_proto(setup: &proto::EcdsaSetup) -> Result<SignerSetup, Status> {
+    let factor_proofs = setup
+        .factor_proofs
+        .iter()
+        .map(|entry| {
+            FactorProof::from_bytes(&entry.proof)
+                .map(|proof| (entry.verifier, proof))
+                .ok_or_else(|| Status::invalid_argument("malformed no-small-factor proof"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(SignerSetup {
+        index: setup.index,
+        paillier: PaillierPublicKey::new(biguint_from(&setup.paillier_n)),
+        ring_pedersen: RingPedersenParams {
+            n_tilde: biguint_from(&setup.n_tilde),
+            h1: biguint_from(&setup.h1),
+            h2: biguint_from(&setup.h2),
+        },
+        proof: RingPedersenProof::from_bytes(&setup.proof)
+            .ok_or_else(|| Status::invalid_argument("malformed ring-pedersen proof"))?,
+        modulus_proof: ModulusProof::from_bytes(&setup.modulus_proof)
+            .ok_or_else(|| Status::invalid_argument("malformed modulus proof"))?,
+        factor_proofs,
+    })
+}
+
+pub fn round1_to_proto(message: &Round1Message) -> proto::EcdsaRound1Message {
+    proto::EcdsaRound1Message {
+        index: message.index,
+        k_ciphertext: message.k_ciphertext.to_bytes_be(),
+        gamma_commitment: message.gamma_commitment.to_vec(),
+        range_proofs: message
+            .range_proofs
+            .iter()
+            .map(|(verifier, proof)| proto::EcdsaRangeProof {
+                verifier: *verifier,
+                proof: proof.to_bytes(),
+            })
+            .collect(),
+    }
+}
+
+pub fn round1_from_proto(message: &proto::EcdsaRound1Message) -> Result<Round1Message, Status> {
+    let range_proofs = message
+        .range_proofs
+        .iter()
+        .map(|entry| {
+            RangeProof::from_bytes(&entry.proof)
+                .map(|proof| (entry.verifier, proof))
+                .ok_or_else(|| Status::invalid_argument("malformed range proof"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Round1Message {
+        index: message.index,
+        k_ciphertext: biguint_from(&message.k_ciphertext),
+        gamma_commitment: message
+            .gamma_commitment
+            .as_slice()
+            .try_into()
+            .map_err(|_| Status::invalid_argument("malformed gamma commitment"))?,
+        range_proofs,
+    })
+}
+
+pub fn mta_to_proto(message: &MtaMessage) -> proto::EcdsaMtaMessage {
+    proto::EcdsaMtaMessage {
+        from: message.from,
+        to: message.to,
+        gamma_ciphertext: message.gamma_ciphertext.to_bytes_be(),
+        gamma_proof: message.gamma_proof.to_bytes(),
+        w_ciphertext: message.w_ciphertext.to_bytes_be(),
+        w_proof: message.w_proof.to_bytes(),
+    }
+}
+
+pub fn mta_from_proto(message: &proto::EcdsaMtaMessage) -> Result<MtaMessage, Status> {
+    let proof = |bytes: &[u8]| {
+        RespondentProof::from_bytes(bytes).ok_or_else(|| Status::invalid_argument("malformed mta proof"))
+    };
+
+    Ok(MtaMessage {
+        from: message.from,
+        to: message.to,
+        gamma_ciphertext: biguint_from(&message.gamma_ciphertext),
+        gamma_proof: proof(&message.gamma_proof)?,
+        w_ciphertext: biguint_from(&message.w_ciphertext),
+        w_proof: proof(&message.w_proof)?,
+    })
+}
+
+pub fn round3_to_proto(message: &Round3Message) -> proto::EcdsaRound3Message {
+    proto::EcdsaRound3Message {
+        index: message.index,
+        delta: scalar_to_bytes(&message.delta),
+        gamma_point: point_to_bytes(&message.gamma_point),
+        gamma_blinding: message.gamma_blinding.to_vec(),
+        proof_commitment: point_to_bytes(&message.gamma_proof.commitment),
+        proof_response: scalar_to_bytes(&message.gamma_proof.response),
+        sigma_commitment: point_to_bytes(&message.sigma_commitment),
+    }
+}
+
+pub fn round3_from_proto(message: &proto::EcdsaRound3Message) -> Result<Round3Message, Status> {
+    Ok(Round3Message {
+        index: message.index,
+        delta: scalar_from(&message.delta, "delta share")?,
+        gamma_point: point_from(&message.gamma_point, "gamma point")?,
+        gamma_blinding: message
+            .gamma_blinding
+            .as_slice()
+            .try_into()
+            .map_err(|_| Status::invalid_argument("malformed gamma blinding"))?,
+        gamma_proof: SchnorrProof {
+            commitment: point_from(&message.proof_commitment, "schnorr proof")?,
+            response: scalar_from(&message.proof_response, "schnorr proof")?,
+        },
+        sigma_commitment: point_from(&message.sigma_commitment, "sigma commitment")?,
+    })
+}
+
+pub fn round4_to_proto(message: &Round4Message) -> proto::EcdsaRound4Message {
+    proto::EcdsaRound4Message {
+        index: message.index,
+        nonce_share: point_to_bytes(&message.nonce_share),
+        sigma_share: point_to_bytes(&message.sigma_share),
+        nonce_proofs: message
+            .nonce_proofs
+            .iter()
+            .map(|(verifier, proof)| proto::EcdsaNonceProof {
+                verifier: *verifier,
+                proof: proof.to_bytes(),
+            })
+            .collect(),
+        sigma_proof_point: point_to_bytes(&message.sigma_proof.point_commitment),
+        sigma_proof_commitment: point_to_bytes(&message.sigma_proof.commitment),
+        sigma_proof_response: scalar_to_bytes(&message.sigma_proof.response),
+        sigma_proof_blinding: scalar_to_bytes(&message.sigma_proof.blinding_response),
+    }
+}
+
+pub fn round4_from_proto(message: &proto::EcdsaRound4Message) -> Result<Round4Message, Status> {
+    let nonce_proofs = message
+        .nonce_proofs
+        .iter()
+        .map(|entry| {
+            LogProof::from_bytes(&entry.proof)
+                .map(|proof| (entry.verifier, proof))
+                .ok_or_else(|| Status::invalid_argument("malformed nonce share proof"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Round4Message {
+        index: message.index,
+        nonce_share: point_from(&message.nonce_share, "nonce share")?,
+        sigma_share: point_from(&message.sigma_share, "sigma share")?,
+        nonce_proofs,
+        sigma_proof: SigmaProof {
+            point_commitment: point_from(&message.sigma_proof_point, "sigma proof")?,
+            commitment: point_from(&message.sigma_proof_commitment, "sigma proof")?,
+            response: scalar_from(&message.sigma_proof_response, "sigma proof")?,
+            blinding_response: scalar_from(&message.sigma_proof_blinding, "sigma proof")?,
+        },
+    })
+}
+
+pub fn round5_to_proto(output: &Round5Output) -> proto::EcdsaRound5Response {
+    use proto::ecdsa_round5_response::Result as Outcome;
+
+    match output {
+        Round5Output::Share(share) => proto::EcdsaRound5Response {
+            index: share.index,
+            result: Some(Outcome::SignatureShare(scalar_to_bytes(&share.s))),
+        },
+        Round5Output::Abort(reveal) => proto::EcdsaRound5Response {
+            index: reveal.index,
+            result: Some(Outcome::Audit(proto::EcdsaAuditReveal {
+                index: reveal.index,
+                k: scalar_to_bytes(&reveal.k),
+                k_randomness: reveal.k_randomness.to_bytes_be(),
+                gamma: scalar_to_bytes(&reveal.gamma),
+                decryptions: reveal
+                    .decryptions
+                    .iter()
+                    .map(|d| proto::EcdsaDecryption {
+                        from: d.from,
+                        gamma_plaintext: d.gamma_plaintext.to_bytes_be(),
+                        gamma_randomness: d.gamma_randomness.to_bytes_be(),
+                        w_plaintext: d.w_plaintext.to_bytes_be(),
+                        w_randomness: d.w_randomness.to_bytes_be(),
+                    })
+                    .collect(),
+            })),
+        },
+    }
+}
+
+pub fn round5_from_proto(response: &proto::EcdsaRound5Response) -> Result<Round5Output, Status> {
+    use proto::ecdsa_round5_response::Result as Outcome;
+
+    match &response.result {
+        Some(Outcome::SignatureShare(s)) => Ok(Round5Output::Share(SignatureShare {
+            index: response.index,
+            s: scalar_from(s, "signature share")?,
+        })),
+        Some(Outcome::Audit(reveal)) => Ok(Round5Output::Abort(AuditReveal {
+            index: reveal.index,
+            k: scalar_from(&reveal.k, "revealed nonce")?,
+            k_randomness: biguint_from(&reveal.k_randomness),
+            gamma: scalar_from(&reveal.gamma, "revealed gamma")?,
+            decryptions: reveal
+                .decryptions
+                .iter()
+                .map(|d| Decryption {
+                    from: d.from,
+                    gamma_plaintext: biguint_from(&d.gamma_plaintext),
+                    gamma_randomness: biguint_from(&d.gamma_randomness),
+                    w_plaintext: biguint_from(&d.w_plaintext),
+                    w_randomness: biguint_from(&d.w_randomness),
+                })
+                .collect(),
+        })),
+        None => Err(Status::invalid_argument("empty round 5 response")),
+    }
+}
+
+pub fn partial_decryption_to_proto(partial: &decryption::PartialDecryption) -> proto::PartialDecryption {
+    proto::PartialDecryption {
+        index: partial.index,
+        point: point_to_bytes(&partial.point),
+        commitment_g: point_to_bytes(&partial.proof.commitment_g),
+        commitment_r: point_to_bytes(&partial.proof.commitment_r),
+        response: scalar_to_bytes(&partial.proof.response),
+    }
+}
+
+pub fn partial_decryption_from_proto(
+    partial: &proto::PartialDecryption,
+) -> Result<decryption::PartialDecryption, Status> {
+    Ok(decryption::PartialDecryption {
+        index: partial.index,
+        point: point_from(&partial.point, "partial decryption")?,
+        proof: DleqProof {
+            commitment_g: point_from(&partial.commitment_g, "proof commitment")?,
+            commitment_r: point_from(&partial.commitment_r, "proof commitment")?,
+            response: scalar_from(&partial.response, "proof response")?,
+        },
+    })
+}
@@ -0,0 +1,212 @@
+// long-term node identities and the roster of custodian nodes
+// every node holds a bip-340 schnorr key, and the roster maps each committee index to
+// the matching public key. the roster is a key directory the operators share out of band,
+// it never passes through the client: a node signs the ephemeral encryption keys it hands
+// out, so a coordinator relaying them between nodes can't swap in keys of its own, and
+// the public protocol statements other nodes act on, so it can't forge those either
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use k256::schnorr::signature::{RandomizedSigner, Verifier};
+use k256::schnorr::{Signature, SigningKey, VerifyingKey};
+use k256::sha2::{Digest, Sha256};
+use rand::rngs::OsRng;
+
+/// domain separation tag for signed encryption keys
+const KEY_BINDING_TAG: &[u8] = b"quorum/node-identity/encryption-key";
+
+/// domain separation tag for signed protocol statements
+const STATEMENT_TAG: &[u8] = b"quorum/node-identity/statement";
+
+/// the protocol step an encryption key was handed out for
+/// signed along with the key, so a key from one protocol can't be replayed into another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyPurpose {
+    /// receiving dkg sub-shares
+    Dkg,
+    /// receiving refresh sub-shares
+    Refresh,
+    /// receiving sub-shares as a member of the new committee
+    Reshare,
+    /// receiving masked pieces from other repair helpers
+    RepairHelper,
+    /// receiving the sums that rebuild a lost share; only signed by a node holding no share
+    RepairRecipient,
+}
+
+impl KeyPurpose {
+    fn label(self) -> &'static [u8] {
+        match self {
+            KeyPurpose::Dkg => b"dkg",
+            KeyPurpose::Refresh => b"refresh",
+            KeyPurpose::Reshare => b"reshare",
+            KeyPurpose::RepairHelper => b"repair-helper",
+            KeyPurpose::RepairRecipient => b"repair-recipient",
+        }
+    }
+}
+
+/// a public message a node vouches for, other nodes act on it only with the signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Statement {
+    /// a repair helper's view of the sharing the lost share is rebuilt into
+    RepairView,
+    /// a resharing dealer's view of the sharing it deals from
+    ReshareView,
+    /// a dkg participant's echo of the commitments it received, with its complaints
+    DkgEcho,
+}
+
+impl Statement {
+    fn label(self) -> &'static [u8] {
+        match self {
+            Statement::RepairView => b"repair-view",
+            Statement::ReshareView => b"reshare-view",
+            Statement::DkgEcho => b"dkg-echo",
+        }
+    }
+}
+
+/// the digest a node signs, binding a message to its tag, label, ceremony and signer
+fn binding(tag: &[u8], label: &[u8], ceremony_id: &str, index: u32, message: &[u8]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(tag)
+        .chain_update((label.len() as u32).to_be_bytes())
+        .chain_update(label)
+        .chain_update((ceremony_id.len() as u32).to_be_bytes())
+        .chain_update(ceremony_id.as_bytes())
+        .chain_update(index.to_be_bytes())
+        .chain_update(message)
+        .finalize()
+        .into()
+}
+
+/// the digest a node signs to vouch for an encryption key
+fn key_binding(purpose: KeyPurpose, ceremony_id: &str, index: u32, key: &[u8]) -> [u8; 32] {
+    binding(KEY_BINDING_TAG, purpose.label(), ceremony_id, index, key)
+}
+
+/// the digest a node signs to vouch for a protocol statement
+fn statement_binding(statement: Statement, ceremony_id: &str, index: u32, message: &[u8]) -> [u8; 32] {
+    binding(STATEMENT_TAG, statement.label(), ceremony_id, index, message)
+}
+
+fn secret_key_path(dir: &Path, index: u32) -> PathBuf {
+    dir.join(format!("node-{}.key", index))
+}
+
+fn public_key_path(dir: &Path, index: u32) -> PathBuf {
+    dir.join(format!("node-{}.pub", index))
+}
+
+/// a node's long-term signing key and its index in the roster
+pub struct NodeIdentity {
+    index: u32,
+    signing_key: SigningKey,
+}
+
+impl NodeIdentity {
+    /// loads the identity of the node at `index` from `dir`, generating one on first start
+    /// the secret key is kept in node-<index>.key, readable by the owner only, and the
+    /// public key is published as node-<index>.pub for the roster
+    pub fn load_or_generate(dir: &Path, index: u32) -> io::Result<Self> {
+        let secret_path = secret_key_path(dir, index);
+        let signing_key = if secret_path.exists() {
+            let bytes = hex::decode(fs::read_to_string(&secret_path)?.trim())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            SigningKey::from_bytes(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        } else {
+            fs::create_dir_all(dir)?;
+            let signing_key = SigningKey::random(&mut OsRng);
+            write_private(&secret_path, &hex::encode(signing_key.to_bytes()))?;
+            signing_key
+        };
+
+        fs::write(
+            public_key_path(dir, index),
+            hex::encode(signing_key.verifying_key().to_bytes()),
+        )?;
+        Ok(Self { index, signing_key })
+    }
+
+    /// the index this node holds shares at in every ceremony
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// signs an encryption key this node hands out, binding it to the purpose and ceremony
+    pub fn sign_key(&self, purpose: KeyPurpose, ceremony_id: &str, key: &[u8]) -> Vec<u8> {
+        self.sign(&key_binding(purpose, ceremony_id, self.index, key))
+    }
+
+    /// signs a protocol statement this node makes in a ceremony
+    pub fn sign_statement(&self, statement: Statement, ceremony_id: &str, message: &[u8]) -> Vec<u8> {
+        self.sign(&statement_binding(statement, ceremony_id, self.index, message))
+    }
+
+    fn sign(&self, digest: &[u8; 32]) -> Vec<u8> {
+        let signature: Signature = self.signing_key.sign_with_rng(&mut OsRng, digest);
+        signature.to_bytes().to_vec()
+    }
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = fs::OpenOptions::new().write(true).create_new(true).mode(0o600).open(path)?;
+    file.write_all(contents.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    fs::write(path, contents)
+}
+
+/// the public identity keys of every custodian node, read from the shared key directory
+pub struct Roster {
+    dir: PathBuf,
+}
+
+impl Roster {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// the identity key of the node at `index`, None if the roster has no such node
+    /// read on every call, so nodes that joined after this one started are found too
+    pub fn identity(&self, index: u32) -> Option<VerifyingKey> {
+        let encoded = fs::read_to_string(public_key_path(&self.dir, index)).ok()?;
+        VerifyingKey::from_bytes(&hex::decode(encoded.trim()).ok()?).ok()
+    }
+
+    /// checks that the node at `index` signed `key` for this purpose and ceremony
+    pub fn verify_key(&self, purpose: KeyPurpose, ceremony_id: &str, index: u32, key: &[u8], signature: &[u8]) -> bool {
+        self.verify(index, &key_binding(purpose, ceremony_id, index, key), signature)
+    }
+
+    /// checks that the node at `index` made this statement in this ceremony
+    pub fn verify_statement(
+        &self,
+        statement: Statement,
+        ceremony_id: &str,
+        index: u32,
+        message: &[u8],
+        signature: &[u8],
+    ) -> bool {
+        self.verify(index, &statement_binding(statement, ceremony_id, index, message), signature)
+    }
+
+    fn verify(&self, index: u32, digest: &[u8; 32], signature: &[u8]) -> bool {
+        let Some(identity) = self.identity(index) else {
+            return false;
+        };
+        let Ok(signature) = Signature::try_from(signature) else {
+            return false;
+        };
+        identity.verify(digest, &signature).is_ok()
+    }
+}
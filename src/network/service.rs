@@ -1,11 +1,33 @@
 // grpc service implementation for the custodian node
 // handles incoming requests from the client coordinator
 
-use std::sync::Arc;
+// tonic::Status is large, but it is what every handler and helper here returns
+#![allow(clippy::result_large_err)]
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use k256::{ProjectivePoint, Scalar};
+use prost::Message;
 use tonic::{Request, Response, Status};
 
-use crate::core::scheme::Share;
-use crate::network::storage::{ShareStore, scalar_to_bytes, bytes_to_scalar};
+use crate::core::bip32::{self, ExtendedPublicKey};
+use crate::core::decryption;
+use crate::core::dkg::{self, DkgParticipant};
+use crate::core::ecdsa::{EcdsaSigner, SignerKeys, SignerSet};
+use crate::core::ecies;
+use crate::core::frost::{self, SigningNonces};
+use crate::core::math::random_scalar;
+use crate::core::packed::PackedShare;
+use crate::core::paillier::PAILLIER_BITS;
+use crate::core::pubkey;
+use crate::core::refresh::{self, RefreshContribution};
+use crate::core::repair;
+use crate::core::reshare::{self, ReshareContribution};
+use crate::core::scheme::{FragmentError, SecretShare, ShareMetadata};
+use crate::core::vss::{verify_share, PedersenShare};
+use crate::network::convert;
+use crate::network::identity::{KeyPurpose, NodeIdentity, Roster, Statement};
+use crate::network::storage::{ShareStore, scalar_to_bytes, bytes_to_scalar, secret_to_bytes, point_to_bytes, bytes_to_point};
 
 // import the generated protobuf types
 pub mod proto {
@@ -13,26 +35,395 @@ pub mod proto {
 }
 
 use proto::custodian_server::Custodian;
-use proto::{JoinRequest, JoinResponse, ShareData, StoreResponse, RetrieveRequest};
+use proto::{ChainCodeRequest, JoinRequest, JoinResponse, PeerKey, PublicShare, ShareData, StoreResponse, RetrieveRequest};
+use proto::SharingView;
+use proto::{
+    DkgCommitRequest, DkgCommitResponse, DkgComplaints, DkgCompleteRequest, DkgDealRequest,
+    DkgDealResponse, DkgFinalizeRequest, DkgReconstructRequest, DkgResult, DkgReveals,
+    DkgRespondRequest, DkgVerifyRequest, EncryptedSubShare,
+};
+use proto::{FrostCommitRequest, FrostCommitResponse, FrostCommitment, FrostSignRequest, FrostSignatureShare};
+use proto::{PartialDecryptRequest, PartialDecryption};
+use proto::{
+    EcdsaMtaMessages, EcdsaRound1Message, EcdsaRound1Request, EcdsaRound2Request, EcdsaRound3Message,
+    EcdsaRound3Request, EcdsaRound4Message, EcdsaRound4Request, EcdsaRound5Request, EcdsaRound5Response,
+    EcdsaSetupRequest, EcdsaSetupResponse,
+};
+use proto::{
+    ReshareCommitRequest, ReshareCompleteRequest, ReshareCompleteResponse, ReshareDealRequest,
+    ReshareDealResponse, ReshareStartRequest, ReshareStartResponse,
+};
+use proto::{SharePoint, WeightedShareData};
+use proto::{
+    RepairCombineRequest, RepairCompleteRequest, RepairPiece, RepairSplitRequest, RepairSplitResponse,
+    RepairStartRequest, RepairStartResponse, RepairSum,
+};
+use proto::{RefreshApplyRequest, RefreshApplyResponse, RefreshCommitRequest, RefreshDealRequest, RefreshStartRequest, RefreshStartResponse};
+
+/// key generation state kept between the rpc calls of one ceremony
+struct DkgSession {
+    participant: DkgParticipant,
+    // ephemeral key peers encrypt their sub-shares to
+    encryption_secret: Scalar,
+}
+
+/// refresh state kept between the rpc calls of one refresh
+struct RefreshSession {
+    index: u32,
+    epoch: u64,
+    // ephemeral key peers encrypt their sub-shares to
+    encryption_secret: Scalar,
+    // holders taking part, fixed when this node deals
+    peers: Vec<u32>,
+    // the sub-share this node dealt to itself
+    own_contribution: Option<RefreshContribution>,
+    // the refreshed share and commitments, waiting for the commit
+    staged: Option<(SecretShare, Vec<ProjectivePoint>)>,
+}
+
+/// resharing state kept between the rpc calls of one resharing
+struct ReshareSession {
+    // index in the new committee, 0 if this node is leaving
+    new_index: u32,
+    // ephemeral key old holders encrypt their sub-shares to
+    encryption_secret: Scalar,
+    // the new share and commitments, waiting for the commit
+    staged: Option<(SecretShare, Vec<ProjectivePoint>)>,
+    // epoch and dealt flag of the new share, taken from the dealers' views
+    epoch: u64,
+    dealt: bool,
+}
+
+/// repair state kept between the rpc calls of one share repair
+struct RepairSession {
+    // x coordinate of the share being rebuilt
+    lost_index: u32,
+    // this helper's index, 0 on the node being repaired
+    index: u32,
+    // ephemeral key pieces and sums are encrypted to
+    encryption_secret: Scalar,
+    // helpers taking part, fixed when this node splits its term
+    helpers: Vec<u32>,
+    // the piece of its own term this helper kept
+    own_piece: Option<Scalar>,
+}
 
 /// the custodian service that runs on each node
 /// stores and retrieves secret shares for the client
 pub struct CustodianService {
     store: Arc<ShareStore>,
     node_id: String,
+    // this node's long-term signing key, it signs every encryption key the node hands out
+    identity: NodeIdentity,
+    // identity keys of every node, used to check the encryption keys the client relays
+    roster: Roster,
+    // maps ceremony_id -> in-progress key generation
+    dkg_sessions: Mutex<HashMap<String, DkgSession>>,
+    // maps (ceremony_id, session_id) -> frost nonces waiting for round 2
+    frost_nonces: Mutex<HashMap<(String, String), SigningNonces>>,
+    // paillier key and ring-pedersen parameters, generated on the first ecdsa setup
+    ecdsa_keys: tokio::sync::OnceCell<Arc<SignerKeys>>,
+    // maps (ceremony_id, session_id) -> ecdsa signer between rounds
+    ecdsa_sessions: Mutex<HashMap<(String, String), EcdsaSigner>>,
+    // maps ceremony_id -> in-progress share refresh
+    refresh_sessions: Mutex<HashMap<String, RefreshSession>>,
+    // maps ceremony_id -> in-progress resharing
+    reshare_sessions: Mutex<HashMap<String, ReshareSession>>,
+    // maps ceremony_id -> in-progress share repair
+    repair_sessions: Mutex<HashMap<String, RepairSession>>,
 }
 
 impl CustodianService {
     /// creates a new custodian service with the given store
-    pub fn new(store: Arc<ShareStore>, node_id: String) -> Self {
-        Self { store, node_id }
+    /// the node holds shares at its identity's index, and checks peers against the roster
+    pub fn new(store: Arc<ShareStore>, node_id: String, identity: NodeIdentity, roster: Roster) -> Self {
+        Self {
+            store,
+            node_id,
+            identity,
+            roster,
+            dkg_sessions: Mutex::new(HashMap::new()),
+            frost_nonces: Mutex::new(HashMap::new()),
+            ecdsa_keys: tokio::sync::OnceCell::new(),
+            ecdsa_sessions: Mutex::new(HashMap::new()),
+            refresh_sessions: Mutex::new(HashMap::new()),
+            reshare_sessions: Mutex::new(HashMap::new()),
+            repair_sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// the node's ecdsa signer keys, generated on first use
+    /// key generation takes a few seconds, so it runs off the async executor
+    async fn ecdsa_keys(&self) -> Result<Arc<SignerKeys>, Status> {
+        self.ecdsa_keys
+            .get_or_try_init(|| async {
+                println!("[{}] generating paillier key for threshold ecdsa...", self.node_id);
+                tokio::task::spawn_blocking(|| Arc::new(SignerKeys::generate(PAILLIER_BITS)))
+                    .await
+                    .map_err(|e| Status::internal(e.to_string()))
+            })
+            .await
+            .cloned()
+    }
+
+    /// runs one ecdsa round against a signing session on a blocking thread
+    /// the round hands the signer back to keep the session going; the session is dropped
+    /// after the last round or if a round fails, so a failed session can't be resumed
+    async fn ecdsa_round<T: Send + 'static>(
+        &self,
+        ceremony_id: &str,
+        session_id: &str,
+        f: impl FnOnce(EcdsaSigner) -> Result<(Option<EcdsaSigner>, T), FragmentError> + Send + 'static,
+    ) -> Result<T, Status> {
+        let key = (ceremony_id.to_string(), session_id.to_string());
+        let signer = self.ecdsa_sessions.lock().unwrap().remove(&key).ok_or_else(|| {
+            Status::failed_precondition(format!("no ecdsa session '{}'", session_id))
+        })?;
+
+        let (signer, output) = tokio::task::spawn_blocking(move || f(signer))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(protocol_error)?;
+
+        if let Some(signer) = signer {
+            self.ecdsa_sessions.lock().unwrap().insert(key, signer);
+        }
+        Ok(output)
+    }
+
+    /// decodes the encryption key of a peer, refusing it unless the node at the peer's
+    /// index signed it for this purpose and ceremony; the client relaying it can't swap it
+    fn peer_key(&self, purpose: KeyPurpose, ceremony_id: &str, peer: &PeerKey) -> Result<ProjectivePoint, Status> {
+        if !self
+            .roster
+            .verify_key(purpose, ceremony_id, peer.index, &peer.encryption_key, &peer.signature)
+        {
+            return Err(Status::permission_denied(format!(
+                "encryption key of node {} is not signed by its roster identity",
+                peer.index
+            )));
+        }
+        bytes_to_point(&peer.encryption_key).ok_or_else(|| Status::invalid_argument("malformed peer encryption key"))
+    }
+
+    /// this node's view of the sharing its share of a ceremony belongs to, signed for `scope`
+    fn signed_view(&self, statement: Statement, ceremony_id: &str, share: &SecretShare, scope: &[u8]) -> SharingView {
+        let mut view = convert::sharing_view(
+            self.identity.index(),
+            &self.store.commitments(ceremony_id).unwrap_or_default(),
+            self.store.epoch(ceremony_id),
+            self.store.is_dealt(ceremony_id),
+            share.meta.as_ref(),
+        );
+        view.signature = self
+            .identity
+            .sign_statement(statement, ceremony_id, &sharing_view_message(&view, scope));
+        view
+    }
+
+    /// checks that every view is signed by the holder it names and that they all describe
+    /// the same sharing, then returns that sharing; a node holding no share of its own
+    /// takes its commitments and epoch from here rather than from the client
+    fn agreed_view(
+        &self,
+        statement: Statement,
+        ceremony_id: &str,
+        views: &[SharingView],
+        scope: &[u8],
+    ) -> Result<SharingView, Status> {
+        let first = views
+            .first()
+            .ok_or_else(|| Status::invalid_argument("no holder vouched for the sharing"))?;
+        let unsigned = |view: &SharingView| SharingView {
+            index: 0,
+            signature: Vec::new(),
+            ..view.clone()
+        };
+
+        for view in views {
+            let message = sharing_view_message(view, scope);
+            if !self
+                .roster
+                .verify_statement(statement, ceremony_id, view.index, &message, &view.signature)
+            {
+                return Err(Status::permission_denied(format!(
+                    "sharing view of node {} is not signed by its roster identity",
+                    view.index
+                )));
+            }
+            if unsigned(view) != unsigned(first) {
+                return Err(Status::failed_precondition(format!(
+                    "holders {} and {} disagree about the sharing",
+                    first.index, view.index
+                )));
+            }
+        }
+
+        Ok(first.clone())
+    }
+
+    /// checks a dkg echo is signed by the participant it names and only carries its own complaints
+    fn echo_from_proto(&self, ceremony_id: &str, echo: &DkgComplaints) -> Result<dkg::Echo, Status> {
+        if !self
+            .roster
+            .verify_statement(Statement::DkgEcho, ceremony_id, echo.index, &echo_message(echo), &echo.signature)
+        {
+            return Err(Status::permission_denied(format!(
+                "echo of node {} is not signed by its roster identity",
+                echo.index
+            )));
+        }
+        if echo.complaints.iter().any(|complaint| complaint.accuser != echo.index) {
+            return Err(Status::invalid_argument(format!(
+                "echo of node {} carries another node's complaint",
+                echo.index
+            )));
+        }
+        let digest = echo
+            .commitments_digest
+            .as_slice()
+            .try_into()
+            .map_err(|_| Status::invalid_argument("malformed commitments digest"))?;
+
+        Ok(dkg::Echo {
+            participant: echo.index,
+            digest,
+            accused: echo.complaints.iter().map(|complaint| complaint.accused).collect(),
+        })
+    }
+
+    /// runs a closure against the key generation session of a ceremony
+    fn with_dkg_session<T>(
+        &self,
+        ceremony_id: &str,
+        f: impl FnOnce(&mut DkgSession) -> Result<T, Status>,
+    ) -> Result<T, Status> {
+        let mut sessions = self.dkg_sessions.lock().unwrap();
+        let session = sessions.get_mut(ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no key generation session for ceremony '{}'", ceremony_id))
+        })?;
+        f(session)
+    }
+}
+
+/// binds an encrypted sub-share to its ceremony, dealer and recipient
+fn sub_share_context(ceremony_id: &str, dealer: u32, recipient: u32) -> Vec<u8> {
+    format!("quorum/dkg/{}/{}/{}", ceremony_id, dealer, recipient).into_bytes()
+}
+
+/// binds an encrypted resharing sub-share to its ceremony, old dealer and new recipient
+fn reshare_share_context(ceremony_id: &str, dealer: u32, recipient: u32) -> Vec<u8> {
+    format!("quorum/reshare/{}/{}/{}", ceremony_id, dealer, recipient).into_bytes()
+}
+
+/// binds an encrypted repair piece or sum to its ceremony, lost index, sender and recipient
+fn repair_piece_context(ceremony_id: &str, lost_index: u32, from: u32, to: u32) -> Vec<u8> {
+    format!("quorum/repair/{}/{}/{}/{}", ceremony_id, lost_index, from, to).into_bytes()
+}
+
+/// binds an encrypted refresh sub-share to its ceremony, epoch, dealer and recipient
+fn refresh_share_context(ceremony_id: &str, epoch: u64, dealer: u32, recipient: u32) -> Vec<u8> {
+    format!("quorum/refresh/{}/{}/{}/{}", ceremony_id, epoch, dealer, recipient).into_bytes()
+}
+
+/// the bytes a holder signs for its view of a sharing, bound to the step it is used in
+/// prost encodes fields in order and drops unknown ones, so every node gets the same bytes
+fn sharing_view_message(view: &SharingView, scope: &[u8]) -> Vec<u8> {
+    let mut message = (scope.len() as u32).to_be_bytes().to_vec();
+    message.extend_from_slice(scope);
+    message.extend(SharingView { signature: Vec::new(), ..view.clone() }.encode_to_vec());
+    message
+}
+
+/// what a dkg echo is signed as: the echo itself with the signature left out
+fn echo_message(echo: &DkgComplaints) -> Vec<u8> {
+    DkgComplaints { signature: Vec::new(), ..echo.clone() }.encode_to_vec()
+}
+
+/// what a repair helper's view is signed for: the lost index and the helpers taking part
+fn repair_scope(lost_index: u32, helpers: &[u32]) -> Vec<u8> {
+    let mut helpers = helpers.to_vec();
+    helpers.sort_unstable();
+    std::iter::once(lost_index)
+        .chain(helpers)
+        .flat_map(u32::to_be_bytes)
+        .collect()
+}
+
+/// what a resharing dealer's view is signed for: the dealers and the new committee
+fn reshare_scope(dealers: &[u32], new_threshold: u32, recipients: &[u32]) -> Vec<u8> {
+    let mut dealers = dealers.to_vec();
+    dealers.sort_unstable();
+    let mut recipients = recipients.to_vec();
+    recipients.sort_unstable();
+    std::iter::once(dealers.len() as u32)
+        .chain(dealers)
+        .chain(std::iter::once(new_threshold))
+        .chain(recipients)
+        .flat_map(u32::to_be_bytes)
+        .collect()
+}
+
+/// decodes a list of compressed points, rejecting the request if any is malformed
+fn points_from_bytes(encoded: &[Vec<u8>]) -> Result<Vec<ProjectivePoint>, Status> {
+    encoded
+        .iter()
+        .map(|bytes| bytes_to_point(bytes))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| Status::invalid_argument("malformed commitment point"))
+}
+
+fn reveal_from_proto(reveal: &proto::RevealedShare) -> Result<dkg::RevealedShare, Status> {
+    Ok(dkg::RevealedShare {
+        dealer: reveal.dealer,
+        recipient: reveal.recipient,
+        share: convert::scalar_from(&reveal.share, "revealed share")?,
+        blinding: convert::scalar_from(&reveal.blinding, "revealed blinding")?,
+    })
+}
+
+fn reveals_to_proto(reveals: Vec<dkg::RevealedShare>) -> DkgReveals {
+    DkgReveals {
+        reveals: reveals
+            .into_iter()
+            .map(|reveal| proto::RevealedShare {
+                dealer: reveal.dealer,
+                recipient: reveal.recipient,
+                share: scalar_to_bytes(&reveal.share),
+                blinding: scalar_to_bytes(&reveal.blinding),
+            })
+            .collect(),
+    }
+}
+
+/// the participant index behind a share's x coordinate
+fn share_index(share: &SecretShare) -> Result<u32, Status> {
+    let bytes = share.x.to_bytes();
+    if bytes[..28].iter().any(|b| *b != 0) {
+        return Err(Status::failed_precondition("share index does not fit a participant index"));
     }
+    Ok(u32::from_be_bytes(bytes[28..].try_into().unwrap()))
+}
+
+fn frost_commitment_from_proto(commitment: &FrostCommitment) -> Result<frost::SigningCommitments, Status> {
+    Ok(frost::SigningCommitments {
+        index: commitment.index,
+        hiding: bytes_to_point(&commitment.hiding)
+            .ok_or_else(|| Status::invalid_argument("malformed nonce commitment"))?,
+        binding: bytes_to_point(&commitment.binding)
+            .ok_or_else(|| Status::invalid_argument("malformed nonce commitment"))?,
+    })
+}
+
+fn protocol_error(error: FragmentError) -> Status {
+    Status::failed_precondition(error.to_string())
 }
 
 #[tonic::async_trait]
 impl Custodian for CustodianService {
     /// handles a node joining a ceremony
-    /// for now just acknowledges the join request
+    /// with a ceremony id this starts a key generation session and hands out
+    /// the ephemeral key peers use to encrypt their sub-shares
     async fn join_ceremony(
         &self,
         request: Request<JoinRequest>,
@@ -40,29 +431,127 @@ impl Custodian for CustodianService {
         let req = request.into_inner();
         println!("[{}] node {} joined ceremony", self.node_id, req.node_id);
 
+        if req.ceremony_id.is_empty() {
+            return Ok(Response::new(JoinResponse {
+                success: true,
+                assigned_index: 0, // assigned by client in this simple implementation
+                message: format!("welcome to the ceremony, {}", req.node_id),
+                encryption_key: Vec::new(),
+                key_signature: Vec::new(),
+            }));
+        }
+        // peers check this node's keys against its roster index, so it can't take part as another
+        if req.index != self.identity.index() {
+            return Err(Status::failed_precondition(format!(
+                "this node is participant {} in the roster, not {}",
+                self.identity.index(),
+                req.index
+            )));
+        }
+
+        let participant =
+            DkgParticipant::new(req.index, req.threshold as usize, req.total as usize)
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let encryption_secret = random_scalar();
+        let encryption_key = ProjectivePoint::GENERATOR * encryption_secret;
+
+        self.dkg_sessions.lock().unwrap().insert(
+            req.ceremony_id.clone(),
+            DkgSession {
+                participant,
+                encryption_secret,
+            },
+        );
+
+        println!(
+            "[{}] started key generation for ceremony '{}' as participant {} ({}-of-{})",
+            self.node_id, req.ceremony_id, req.index, req.threshold, req.total
+        );
+
+        let encryption_key = point_to_bytes(&encryption_key);
         Ok(Response::new(JoinResponse {
             success: true,
-            assigned_index: 0, // assigned by client in this simple implementation
-            message: format!("welcome to the ceremony, {}", req.node_id),
+            assigned_index: req.index,
+            message: format!("joined key generation for '{}'", req.ceremony_id),
+            key_signature: self.identity.sign_key(KeyPurpose::Dkg, &req.ceremony_id, &encryption_key),
+            encryption_key,
         }))
     }
 
     /// stores a share sent by the client
-    /// the share is kept in memory for later retrieval
+    /// if the dealer published feldman commitments, the share is verified before storing
     async fn store_share(
         &self,
         request: Request<ShareData>,
     ) -> Result<Response<StoreResponse>, Status> {
         let data = request.into_inner();
         
-        // converting the bytes back to scalars
-        let x = bytes_to_scalar(&data.x);
-        let y = bytes_to_scalar(&data.y);
-        let share = Share { x, y };
+        // converting the bytes back to a share, keeping the metadata of its split
+        let share = convert::share_from_proto(&data)?;
+        if !share.checksum_valid() {
+            return Err(Status::invalid_argument("share failed its checksum"));
+        }
+
+        // checking the share against the dealer's commitments when they are provided
+        let mut commitments = None;
+        if !data.commitments.is_empty() {
+            let points = data
+                .commitments
+                .iter()
+                .map(|bytes| bytes_to_point(bytes))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| Status::invalid_argument("malformed commitment point"))?;
+
+            if !verify_share(&share, &points) {
+                println!(
+                    "[{}] rejected share for ceremony '{}': commitment check failed",
+                    self.node_id, data.ceremony_id
+                );
+                return Err(Status::invalid_argument(
+                    "share does not match the dealer's commitments",
+                ));
+            }
+
+            commitments = Some(points);
+        }
+
+        // a packed share covers a whole batch of secrets at once
+        if data.packed_secrets > 0 {
+            if data.privacy_threshold == 0 {
+                return Err(Status::invalid_argument("a packed share needs a privacy threshold"));
+            }
+            self.store.store_packed(
+                data.ceremony_id.clone(),
+                PackedShare {
+                    secrets: data.packed_secrets,
+                    privacy_threshold: data.privacy_threshold,
+                    share,
+                },
+            );
+            // storing replaces whatever the ceremony held before, so commitments go in after
+            if let Some(commitments) = commitments {
+                self.store.store_commitments(data.ceremony_id.clone(), commitments);
+            }
+            self.store.mark_dealt(&data.ceremony_id);
+
+            println!(
+                "[{}] stored packed share of {} secrets for ceremony '{}'",
+                self.node_id, data.packed_secrets, data.ceremony_id
+            );
+
+            return Ok(Response::new(StoreResponse {
+                success: true,
+                message: format!("packed share of {} secrets stored successfully", data.packed_secrets),
+            }));
+        }
 
         // storing the share
         self.store.store(data.ceremony_id.clone(), share);
-        
+        if let Some(commitments) = commitments {
+            self.store.store_commitments(data.ceremony_id.clone(), commitments);
+        }
+        self.store.mark_dealt(&data.ceremony_id);
+
         println!(
             "[{}] stored share for ceremony '{}'",
             self.node_id, data.ceremony_id
@@ -75,13 +564,25 @@ impl Custodian for CustodianService {
     }
 
     /// retrieves a previously stored share
-    /// called by the client during secret recovery
+    /// called by the client during secret recovery. only a share a dealer stored is handed
+    /// out: a dkg share leaving its node would let the client assemble a key no one was
+    /// supposed to hold
     async fn retrieve_share(
         &self,
         request: Request<RetrieveRequest>,
     ) -> Result<Response<ShareData>, Status> {
         let req = request.into_inner();
-        
+        if self.store.exists(&req.ceremony_id) && !self.store.is_dealt(&req.ceremony_id) {
+            println!(
+                "[{}] refused to hand out the share of ceremony '{}': it was not dealt",
+                self.node_id, req.ceremony_id
+            );
+            return Err(Status::permission_denied(format!(
+                "the share of ceremony '{}' comes from key generation and never leaves this node",
+                req.ceremony_id
+            )));
+        }
+
         // looking up the share
         match self.store.retrieve(&req.ceremony_id) {
             Some(share) => {
@@ -90,10 +591,26 @@ impl Custodian for CustodianService {
                     self.node_id, req.ceremony_id
                 );
 
+                let commitments = self
+                    .store
+                    .commitments(&req.ceremony_id)
+                    .unwrap_or_default();
+                // a tagged share records its own epoch, which refreshes keep in step with the store's
+                let epoch = share
+                    .meta
+                    .as_ref()
+                    .map_or_else(|| self.store.epoch(&req.ceremony_id), |meta| meta.epoch);
+                let (packed_secrets, privacy_threshold) = self
+                    .store
+                    .retrieve_packed(&req.ceremony_id)
+                    .map_or((0, 0), |packed| (packed.secrets, packed.privacy_threshold));
+
                 Ok(Response::new(ShareData {
-                    ceremony_id: req.ceremony_id,
-                    x: scalar_to_bytes(&share.x),
-                    y: scalar_to_bytes(&share.y),
+                    commitments: commitments.iter().map(point_to_bytes).collect(),
+                    epoch,
+                    packed_secrets,
+                    privacy_threshold,
+                    ..convert::share_to_proto(&req.ceremony_id, &share)
                 }))
             }
             None => {
@@ -104,4 +621,1474 @@ impl Custodian for CustodianService {
             }
         }
     }
+
+    /// hands out the public point of a stored share, so the client can derive the
+    /// group public key without any node revealing its share
+    async fn get_public_share(
+        &self,
+        request: Request<RetrieveRequest>,
+    ) -> Result<Response<PublicShare>, Status> {
+        let req = request.into_inner();
+
+        let share = self.store.retrieve(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no share found for ceremony '{}'", req.ceremony_id))
+        })?;
+
+        println!(
+            "[{}] handed out public share for ceremony '{}'",
+            self.node_id, req.ceremony_id
+        );
+
+        Ok(Response::new(PublicShare {
+            x: scalar_to_bytes(&share.x),
+            public_share: point_to_bytes(&pubkey::public_share(&share)),
+            commitments: self
+                .store
+                .commitments(&req.ceremony_id)
+                .unwrap_or_default()
+                .iter()
+                .map(point_to_bytes)
+                .collect(),
+            epoch: self.store.epoch(&req.ceremony_id),
+            chain_code: self.store.chain_code(&req.ceremony_id).map(Vec::from).unwrap_or_default(),
+            ceremony_id: req.ceremony_id,
+        }))
+    }
+
+    /// sets the bip-32 chain code of a ceremony's key
+    /// it can only be set once, setting the same one again is fine
+    async fn set_chain_code(
+        &self,
+        request: Request<ChainCodeRequest>,
+    ) -> Result<Response<StoreResponse>, Status> {
+        let req = request.into_inner();
+
+        if !self.store.exists(&req.ceremony_id) {
+            return Err(Status::not_found(format!("no share found for ceremony '{}'", req.ceremony_id)));
+        }
+        let chain_code: [u8; 32] = req
+            .chain_code
+            .as_slice()
+            .try_into()
+            .map_err(|_| Status::invalid_argument("chain code must be 32 bytes"))?;
+        if self.store.chain_code(&req.ceremony_id).is_some_and(|existing| existing != chain_code) {
+            return Err(Status::failed_precondition(format!(
+                "ceremony '{}' already has a different chain code",
+                req.ceremony_id
+            )));
+        }
+
+        self.store.store_chain_code(req.ceremony_id.clone(), chain_code);
+
+        println!("[{}] set chain code for ceremony '{}'", self.node_id, req.ceremony_id);
+
+        Ok(Response::new(StoreResponse {
+            success: true,
+            message: "chain code set".to_string(),
+        }))
+    }
+
+    /// stores every share of a weighted custodian, one per vote it carries
+    /// each share is verified against the dealer's commitments when they are provided
+    async fn store_weighted_shares(
+        &self,
+        request: Request<WeightedShareData>,
+    ) -> Result<Response<StoreResponse>, Status> {
+        let data = request.into_inner();
+        if data.shares.is_empty() {
+            return Err(Status::invalid_argument("a weighted custodian holds at least one share"));
+        }
+
+        let shares: Vec<SecretShare> = data
+            .shares
+            .iter()
+            .map(|point| {
+                Ok(SecretShare {
+                    x: convert::scalar_from(&point.x, "share x")?,
+                    y: convert::secret_from(&point.y, "share y")?,
+                    meta: None,
+                })
+            })
+            .collect::<Result<_, Status>>()?;
+
+        let commitments = if !data.commitments.is_empty() {
+            let commitments = points_from_bytes(&data.commitments)?;
+            if !shares.iter().all(|share| verify_share(share, &commitments)) {
+                println!(
+                    "[{}] rejected weighted shares for ceremony '{}': commitment check failed",
+                    self.node_id, data.ceremony_id
+                );
+                return Err(Status::invalid_argument(
+                    "a share does not match the dealer's commitments",
+                ));
+            }
+
+            Some(commitments)
+        } else {
+            None
+        };
+
+        let weight = shares.len();
+        self.store.store_weighted(data.ceremony_id.clone(), shares);
+        if let Some(commitments) = commitments {
+            self.store.store_commitments(data.ceremony_id.clone(), commitments);
+        }
+
+        println!(
+            "[{}] stored shares for ceremony '{}' with weight {}",
+            self.node_id, data.ceremony_id, weight
+        );
+
+        Ok(Response::new(StoreResponse {
+            success: true,
+            message: format!("stored {} share(s), one per vote", weight),
+        }))
+    }
+
+    /// retrieves every share of a weighted custodian
+    async fn retrieve_weighted_shares(
+        &self,
+        request: Request<RetrieveRequest>,
+    ) -> Result<Response<WeightedShareData>, Status> {
+        let req = request.into_inner();
+
+        let shares = self.store.retrieve_weighted(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no weighted shares found for ceremony '{}'", req.ceremony_id))
+        })?;
+
+        println!(
+            "[{}] retrieved {} weighted shares for ceremony '{}'",
+            self.node_id,
+            shares.len(),
+            req.ceremony_id
+        );
+
+        Ok(Response::new(WeightedShareData {
+            shares: shares
+                .iter()
+                .map(|share| SharePoint {
+                    x: scalar_to_bytes(&share.x),
+                    y: secret_to_bytes(&share.y),
+                })
+                .collect(),
+            commitments: self
+                .store
+                .commitments(&req.ceremony_id)
+                .unwrap_or_default()
+                .iter()
+                .map(point_to_bytes)
+                .collect(),
+            ceremony_id: req.ceremony_id,
+        }))
+    }
+
+    /// dkg round 1: deals this node's polynomial
+    /// each sub-share is encrypted to its recipient so the client can't read it
+    async fn dkg_deal(
+        &self,
+        request: Request<DkgDealRequest>,
+    ) -> Result<Response<DkgDealResponse>, Status> {
+        let req = request.into_inner();
+
+        let response = self.with_dkg_session(&req.ceremony_id, |session| {
+            let package = session.participant.deal();
+
+            let mut sub_shares = Vec::new();
+            for peer in &req.peers {
+                if peer.index == package.dealer {
+                    continue;
+                }
+                let recipient_key = self.peer_key(KeyPurpose::Dkg, &req.ceremony_id, peer)?;
+                let sub_share = package.sub_shares.get(&peer.index).ok_or_else(|| {
+                    Status::invalid_argument(format!("unknown participant {}", peer.index))
+                })?;
+
+                let mut plaintext = secret_to_bytes(&sub_share.share.y);
+                plaintext.extend(scalar_to_bytes(&sub_share.blinding));
+                let context = sub_share_context(&req.ceremony_id, package.dealer, peer.index);
+
+                sub_shares.push(EncryptedSubShare {
+                    recipient: peer.index,
+                    ciphertext: ecies::encrypt(&recipient_key, &plaintext, &context),
+                });
+            }
+
+            Ok(DkgDealResponse {
+                dealer: package.dealer,
+                commitments: package.commitments.iter().map(point_to_bytes).collect(),
+                sub_shares,
+            })
+        })?;
+
+        println!("[{}] dealt sub-shares for ceremony '{}'", self.node_id, req.ceremony_id);
+        Ok(Response::new(response))
+    }
+
+    /// dkg round 2: decrypts and verifies the sub-shares dealt to this node
+    /// returns a signed echo of the commitments it received, with a complaint
+    /// against every dealer whose sub-share is missing or invalid
+    async fn dkg_verify(
+        &self,
+        request: Request<DkgVerifyRequest>,
+    ) -> Result<Response<DkgComplaints>, Status> {
+        let req = request.into_inner();
+
+        let echo = self.with_dkg_session(&req.ceremony_id, |session| {
+            let index = session.participant.index();
+
+            for message in &req.dealers {
+                // a malformed broadcast is treated like an invalid sub-share
+                let commitments = points_from_bytes(&message.commitments).unwrap_or_default();
+
+                let context = sub_share_context(&req.ceremony_id, message.dealer, index);
+                let sub_share = ecies::decrypt(&session.encryption_secret, &message.ciphertext, &context)
+                    .ok()
+                    .filter(|plaintext| plaintext.len() == 64)
+                    .and_then(|plaintext| {
+                        Some(PedersenShare {
+                            share: SecretShare::new(Scalar::from(index as u64), bytes_to_scalar(&plaintext[..32])?),
+                            blinding: bytes_to_scalar(&plaintext[32..])?,
+                        })
+                    });
+
+                session
+                    .participant
+                    .receive_deal(message.dealer, commitments, sub_share);
+            }
+
+            let echo = session.participant.echo();
+            Ok(DkgComplaints {
+                complaints: echo
+                    .accused
+                    .into_iter()
+                    .map(|accused| proto::Complaint {
+                        accuser: index,
+                        accused,
+                    })
+                    .collect(),
+                index,
+                commitments_digest: echo.digest.to_vec(),
+                signature: Vec::new(),
+            })
+        })?;
+        let echo = DkgComplaints {
+            signature: self
+                .identity
+                .sign_statement(Statement::DkgEcho, &req.ceremony_id, &echo_message(&echo)),
+            ..echo
+        };
+
+        println!(
+            "[{}] verified sub-shares for ceremony '{}' ({} complaints)",
+            self.node_id,
+            req.ceremony_id,
+            echo.complaints.len()
+        );
+        Ok(Response::new(echo))
+    }
+
+    /// dkg round 3: checks that every participant echoed the same commitments, then
+    /// answers the echoed complaints against this node by revealing the disputed sub-shares
+    async fn dkg_respond(
+        &self,
+        request: Request<DkgRespondRequest>,
+    ) -> Result<Response<DkgReveals>, Status> {
+        let req = request.into_inner();
+        let echoes = req
+            .echoes
+            .iter()
+            .map(|echo| self.echo_from_proto(&req.ceremony_id, echo))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let reveals = self.with_dkg_session(&req.ceremony_id, |session| {
+            session.participant.check_echoes(&echoes).map_err(protocol_error)?;
+            session.participant.respond().map_err(protocol_error)
+        })?;
+
+        Ok(Response::new(reveals_to_proto(reveals)))
+    }
+
+    /// dkg round 4: settles complaints and publishes feldman commitments if qualified
+    async fn dkg_commit(
+        &self,
+        request: Request<DkgCommitRequest>,
+    ) -> Result<Response<DkgCommitResponse>, Status> {
+        let req = request.into_inner();
+        let reveals: Vec<dkg::RevealedShare> = req.reveals.iter().map(reveal_from_proto).collect::<Result<_, _>>()?;
+
+        let response = self.with_dkg_session(&req.ceremony_id, |session| {
+            let qualified = session
+                .participant
+                .resolve_complaints(&reveals)
+                .map_err(protocol_error)?;
+
+            let commitments = if qualified.contains(&session.participant.index()) {
+                session.participant.feldman_commitments()
+            } else {
+                Vec::new()
+            };
+
+            Ok(DkgCommitResponse {
+                qualified,
+                commitments: commitments.iter().map(point_to_bytes).collect(),
+            })
+        })?;
+
+        println!(
+            "[{}] qualified dealers for ceremony '{}': {:?}",
+            self.node_id, req.ceremony_id, response.qualified
+        );
+        Ok(Response::new(response))
+    }
+
+    /// dkg round 5: checks every qualified dealer's feldman commitments
+    /// returns this node's sub-share as evidence against dealers that fail
+    async fn dkg_finalize(
+        &self,
+        request: Request<DkgFinalizeRequest>,
+    ) -> Result<Response<DkgReveals>, Status> {
+        let req = request.into_inner();
+
+        let mut commitments = BTreeMap::new();
+        for dealer in &req.commitments {
+            // a malformed broadcast is treated like an inconsistent one
+            commitments.insert(dealer.dealer, points_from_bytes(&dealer.commitments).unwrap_or_default());
+        }
+
+        let evidence = self.with_dkg_session(&req.ceremony_id, |session| {
+            Ok(session.participant.verify_feldman(commitments))
+        })?;
+
+        Ok(Response::new(reveals_to_proto(evidence)))
+    }
+
+    /// dkg round 6 (only on misbehaviour): reveals sub-shares from the given dealers
+    async fn dkg_reconstruct(
+        &self,
+        request: Request<DkgReconstructRequest>,
+    ) -> Result<Response<DkgReveals>, Status> {
+        let req = request.into_inner();
+
+        let reveals = self.with_dkg_session(&req.ceremony_id, |session| {
+            Ok(session.participant.reveal_shares(&req.dealers))
+        })?;
+
+        Ok(Response::new(reveals_to_proto(reveals)))
+    }
+
+    /// dkg round 6: computes this node's final share and stores it under the ceremony id
+    async fn dkg_complete(
+        &self,
+        request: Request<DkgCompleteRequest>,
+    ) -> Result<Response<DkgResult>, Status> {
+        let req = request.into_inner();
+        let evidence: Vec<dkg::RevealedShare> = req.evidence.iter().map(reveal_from_proto).collect::<Result<_, _>>()?;
+        let reveals: Vec<dkg::RevealedShare> = req.reveals.iter().map(reveal_from_proto).collect::<Result<_, _>>()?;
+
+        let output = self.with_dkg_session(&req.ceremony_id, |session| {
+            session
+                .participant
+                .complete(&evidence, &reveals)
+                .map_err(protocol_error)
+        })?;
+
+        // the session is no longer needed once the share is stored
+        self.dkg_sessions.lock().unwrap().remove(&req.ceremony_id);
+        self.store.store(req.ceremony_id.clone(), output.share.clone());
+        self.store
+            .store_commitments(req.ceremony_id.clone(), output.commitments.clone());
+
+        println!(
+            "[{}] completed key generation for ceremony '{}'",
+            self.node_id, req.ceremony_id
+        );
+
+        Ok(Response::new(DkgResult {
+            group_public_key: point_to_bytes(&output.group_public_key),
+            commitments: output.commitments.iter().map(point_to_bytes).collect(),
+            qualified: output.qualified,
+        }))
+    }
+
+    /// frost round 1: samples nonces for a signing session and returns their commitments
+    /// the nonces stay on this node until the matching sign request consumes them
+    async fn frost_commit(
+        &self,
+        request: Request<FrostCommitRequest>,
+    ) -> Result<Response<FrostCommitResponse>, Status> {
+        let req = request.into_inner();
+
+        let share = self.store.retrieve(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no share found for ceremony '{}'", req.ceremony_id))
+        })?;
+        let group_commitments = self.store.commitments(&req.ceremony_id).ok_or_else(|| {
+            Status::failed_precondition(format!("no group commitments for ceremony '{}'", req.ceremony_id))
+        })?;
+
+        let (nonces, commitments) = frost::commit(share_index(&share)?);
+        self.frost_nonces
+            .lock()
+            .unwrap()
+            .insert((req.ceremony_id.clone(), req.session_id.clone()), nonces);
+
+        println!(
+            "[{}] committed to nonces for signing session '{}' on ceremony '{}'",
+            self.node_id, req.session_id, req.ceremony_id
+        );
+
+        Ok(Response::new(FrostCommitResponse {
+            commitment: Some(FrostCommitment {
+                index: commitments.index,
+                hiding: point_to_bytes(&commitments.hiding),
+                binding: point_to_bytes(&commitments.binding),
+            }),
+            group_commitments: group_commitments.iter().map(point_to_bytes).collect(),
+            chain_code: self.store.chain_code(&req.ceremony_id).map(Vec::from).unwrap_or_default(),
+        }))
+    }
+
+    /// frost round 2: signs the message with this node's share
+    /// the session's nonces are deleted whether or not signing succeeds
+    async fn frost_sign(
+        &self,
+        request: Request<FrostSignRequest>,
+    ) -> Result<Response<FrostSignatureShare>, Status> {
+        let req = request.into_inner();
+
+        let nonces = self
+            .frost_nonces
+            .lock()
+            .unwrap()
+            .remove(&(req.ceremony_id.clone(), req.session_id.clone()))
+            .ok_or_else(|| {
+                Status::failed_precondition(format!("no nonces for signing session '{}'", req.session_id))
+            })?;
+
+        let share = self.store.retrieve(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no share found for ceremony '{}'", req.ceremony_id))
+        })?;
+        let group_public_key = self
+            .store
+            .commitments(&req.ceremony_id)
+            .and_then(|commitments| commitments.first().copied())
+            .ok_or_else(|| {
+                Status::failed_precondition(format!("no group public key for ceremony '{}'", req.ceremony_id))
+            })?;
+
+        let commitments = req
+            .commitments
+            .iter()
+            .map(frost_commitment_from_proto)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // signing for a child key: the share and the group key both move by the path's tweak
+        let (share, group_public_key) = if req.derivation_path.is_empty() {
+            (share, group_public_key)
+        } else {
+            let chain_code = self.store.chain_code(&req.ceremony_id).ok_or_else(|| {
+                Status::failed_precondition(format!("no chain code for ceremony '{}'", req.ceremony_id))
+            })?;
+            let path = bip32::parse_path(&req.derivation_path).map_err(protocol_error)?;
+            let (child, tweak) = ExtendedPublicKey::root(group_public_key, chain_code)
+                .derive_path(&path)
+                .map_err(protocol_error)?;
+            (bip32::tweak_share(&share, &tweak), child.public_key)
+        };
+
+        let signature_share = frost::sign(&share, nonces, &group_public_key, &req.message, &commitments)
+            .map_err(protocol_error)?;
+
+        println!(
+            "[{}] produced signature share for session '{}' on ceremony '{}'",
+            self.node_id, req.session_id, req.ceremony_id
+        );
+
+        Ok(Response::new(FrostSignatureShare {
+            index: signature_share.index,
+            z: scalar_to_bytes(&signature_share.z),
+        }))
+    }
+
+    /// threshold decryption: returns share * R for a ciphertext's ephemeral point R,
+    /// proving it was computed with the share behind this node's public share
+    async fn partial_decrypt(
+        &self,
+        request: Request<PartialDecryptRequest>,
+    ) -> Result<Response<PartialDecryption>, Status> {
+        let req = request.into_inner();
+
+        let share = self.store.retrieve(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no share found for ceremony '{}'", req.ceremony_id))
+        })?;
+        let ephemeral = bytes_to_point(&req.ephemeral_point)
+            .filter(|point| *point != ProjectivePoint::IDENTITY)
+            .ok_or_else(|| Status::invalid_argument("malformed ephemeral point"))?;
+
+        let partial = decryption::partial_decrypt(share_index(&share)?, &share, &ephemeral);
+
+        println!(
+            "[{}] produced partial decryption for ceremony '{}'",
+            self.node_id, req.ceremony_id
+        );
+
+        Ok(Response::new(convert::partial_decryption_to_proto(&partial)))
+    }
+
+    /// ecdsa setup: returns this node's signer keys and the ceremony's group commitments
+    /// given the other signers' setups, it proves this node's modulus to each of them
+    async fn ecdsa_setup(
+        &self,
+        request: Request<EcdsaSetupRequest>,
+    ) -> Result<Response<EcdsaSetupResponse>, Status> {
+        let req = request.into_inner();
+
+        let share = self.store.retrieve(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no share found for ceremony '{}'", req.ceremony_id))
+        })?;
+        let group_commitments = self.store.commitments(&req.ceremony_id).ok_or_else(|| {
+            Status::failed_precondition(format!("no group commitments for ceremony '{}'", req.ceremony_id))
+        })?;
+        let peers = req
+            .peers
+            .iter()
+            .map(convert::setup_from_proto)
+            .collect::<Result<Vec<_>, _>>()?;
+        let index = share_index(&share)?;
+        let keys = self.ecdsa_keys().await?;
+
+        let setup = tokio::task::spawn_blocking(move || keys.setup(index, &peers))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(protocol_error)?;
+
+        Ok(Response::new(EcdsaSetupResponse {
+            setup: Some(convert::setup_to_proto(&setup)),
+            group_commitments: group_commitments.iter().map(point_to_bytes).collect(),
+        }))
+    }
+
+    /// ecdsa round 1: starts a signing session with the given signers
+    async fn ecdsa_round1(
+        &self,
+        request: Request<EcdsaRound1Request>,
+    ) -> Result<Response<EcdsaRound1Message>, Status> {
+        let req = request.into_inner();
+
+        let share = self.store.retrieve(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no share found for ceremony '{}'", req.ceremony_id))
+        })?;
+        let group_commitments = self.store.commitments(&req.ceremony_id).ok_or_else(|| {
+            Status::failed_precondition(format!("no group commitments for ceremony '{}'", req.ceremony_id))
+        })?;
+        let setups = req
+            .setups
+            .iter()
+            .map(convert::setup_from_proto)
+            .collect::<Result<Vec<_>, _>>()?;
+        let keys = self.ecdsa_keys().await?;
+
+        let (signer, message) = tokio::task::spawn_blocking(move || {
+            let set = SignerSet::new(setups, &group_commitments)?;
+            let signer = EcdsaSigner::new(&share, keys, set)?;
+            let message = signer.round1();
+            Ok((signer, message))
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(protocol_error)?;
+
+        self.ecdsa_sessions
+            .lock()
+            .unwrap()
+            .insert((req.ceremony_id.clone(), req.session_id.clone()), signer);
+
+        println!(
+            "[{}] started ecdsa session '{}' on ceremony '{}'",
+            self.node_id, req.session_id, req.ceremony_id
+        );
+
+        Ok(Response::new(convert::round1_to_proto(&message)))
+    }
+
+    /// ecdsa round 2: answers every peer's encrypted nonce with mta responses
+    async fn ecdsa_round2(
+        &self,
+        request: Request<EcdsaRound2Request>,
+    ) -> Result<Response<EcdsaMtaMessages>, Status> {
+        let req = request.into_inner();
+        let round1 = req
+            .messages
+            .iter()
+            .map(convert::round1_from_proto)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let messages = self
+            .ecdsa_round(&req.ceremony_id, &req.session_id, move |mut signer| {
+                let messages = signer.round2(&round1)?;
+                Ok((Some(signer), messages))
+            })
+            .await?;
+
+        Ok(Response::new(EcdsaMtaMessages {
+            messages: messages.iter().map(convert::mta_to_proto).collect(),
+        }))
+    }
+
+    /// ecdsa round 3: decrypts the mta responses and opens the gamma commitment
+    async fn ecdsa_round3(
+        &self,
+        request: Request<EcdsaRound3Request>,
+    ) -> Result<Response<EcdsaRound3Message>, Status> {
+        let req = request.into_inner();
+        let mta = req
+            .messages
+            .iter()
+            .map(convert::mta_from_proto)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let message = self
+            .ecdsa_round(&req.ceremony_id, &req.session_id, move |mut signer| {
+                let message = signer.round3(&mta)?;
+                Ok((Some(signer), message))
+            })
+            .await?;
+
+        Ok(Response::new(convert::round3_to_proto(&message)))
+    }
+
+    /// ecdsa round 4: computes the nonce point and returns this node's shares of it
+    async fn ecdsa_round4(
+        &self,
+        request: Request<EcdsaRound4Request>,
+    ) -> Result<Response<EcdsaRound4Message>, Status> {
+        let req = request.into_inner();
+        let round3 = req
+            .messages
+            .iter()
+            .map(convert::round3_from_proto)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let message = self
+            .ecdsa_round(&req.ceremony_id, &req.session_id, move |mut signer| {
+                let message = signer.round4(&round3)?;
+                Ok((Some(signer), message))
+            })
+            .await?;
+
+        Ok(Response::new(convert::round4_to_proto(&message)))
+    }
+
+    /// ecdsa round 5: returns a signature share, or this session's secrets if the checks fail
+    /// the session ends here either way; checking the round 4 proofs is paillier work,
+    /// so it runs on a blocking thread like the other rounds
+    async fn ecdsa_round5(
+        &self,
+        request: Request<EcdsaRound5Request>,
+    ) -> Result<Response<EcdsaRound5Response>, Status> {
+        let req = request.into_inner();
+        let prehash: [u8; 32] = req
+            .message_hash
+            .as_slice()
+            .try_into()
+            .map_err(|_| Status::invalid_argument("message hash must be 32 bytes"))?;
+        let round4 = req
+            .messages
+            .iter()
+            .map(convert::round4_from_proto)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let output = self
+            .ecdsa_round(&req.ceremony_id, &req.session_id, move |signer| {
+                Ok((None, signer.round5(&prehash, &round4)?))
+            })
+            .await?;
+
+        println!(
+            "[{}] finished ecdsa session '{}' on ceremony '{}'",
+            self.node_id, req.session_id, req.ceremony_id
+        );
+
+        Ok(Response::new(convert::round5_to_proto(&output)))
+    }
+
+    /// refresh round 1: opens a refresh session for this node's share
+    /// hands out a fresh ephemeral key for the sub-shares dealt to this node
+    async fn refresh_start(
+        &self,
+        request: Request<RefreshStartRequest>,
+    ) -> Result<Response<RefreshStartResponse>, Status> {
+        let req = request.into_inner();
+
+        let share = self.store.retrieve(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no share found for ceremony '{}'", req.ceremony_id))
+        })?;
+        if self.store.commitments(&req.ceremony_id).is_none() {
+            return Err(Status::failed_precondition(format!(
+                "ceremony '{}' has no feldman commitments to refresh against",
+                req.ceremony_id
+            )));
+        }
+        // a sharing of zero only preserves the secret at x = 0, not a packed batch
+        if self.store.retrieve_packed(&req.ceremony_id).is_some() {
+            return Err(Status::failed_precondition(format!(
+                "ceremony '{}' holds a packed share, which can't be refreshed",
+                req.ceremony_id
+            )));
+        }
+
+        let index = share_index(&share)?;
+        let epoch = self.store.epoch(&req.ceremony_id);
+        let encryption_secret = random_scalar();
+        let encryption_key = ProjectivePoint::GENERATOR * encryption_secret;
+
+        self.refresh_sessions.lock().unwrap().insert(
+            req.ceremony_id.clone(),
+            RefreshSession {
+                index,
+                epoch,
+                encryption_secret,
+                peers: Vec::new(),
+                own_contribution: None,
+                staged: None,
+            },
+        );
+
+        println!(
+            "[{}] started refresh of ceremony '{}' at epoch {}",
+            self.node_id, req.ceremony_id, epoch
+        );
+
+        let encryption_key = point_to_bytes(&encryption_key);
+        Ok(Response::new(RefreshStartResponse {
+            index,
+            epoch,
+            key_signature: self.identity.sign_key(KeyPurpose::Refresh, &req.ceremony_id, &encryption_key),
+            encryption_key,
+        }))
+    }
+
+    /// refresh round 2: deals a zero-constant polynomial to every holder
+    /// each sub-share is encrypted to its recipient so the client can't read it
+    async fn refresh_deal(
+        &self,
+        request: Request<RefreshDealRequest>,
+    ) -> Result<Response<DkgDealResponse>, Status> {
+        let req = request.into_inner();
+
+        let threshold = self
+            .store
+            .commitments(&req.ceremony_id)
+            .map(|commitments| commitments.len())
+            .unwrap_or_default();
+
+        let mut sessions = self.refresh_sessions.lock().unwrap();
+        let session = sessions.get_mut(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no refresh session for ceremony '{}'", req.ceremony_id))
+        })?;
+
+        let peers: Vec<u32> = req.peers.iter().map(|peer| peer.index).collect();
+        if !peers.contains(&session.index) {
+            return Err(Status::invalid_argument("this node is missing from the refresh peers"));
+        }
+        let deal = refresh::deal_refresh(threshold, &peers).map_err(protocol_error)?;
+
+        let mut sub_shares = Vec::new();
+        for (peer, sub_share) in req.peers.iter().zip(deal.sub_shares) {
+            if peer.index == session.index {
+                session.own_contribution = Some(RefreshContribution {
+                    dealer: session.index,
+                    commitments: deal.commitments.clone(),
+                    sub_share,
+                });
+                continue;
+            }
+
+            let recipient_key = self.peer_key(KeyPurpose::Refresh, &req.ceremony_id, peer)?;
+            let context = refresh_share_context(&req.ceremony_id, session.epoch, session.index, peer.index);
+            sub_shares.push(EncryptedSubShare {
+                recipient: peer.index,
+                ciphertext: ecies::encrypt(&recipient_key, &secret_to_bytes(&sub_share.y), &context),
+            });
+        }
+        session.peers = peers;
+
+        println!("[{}] dealt refresh sub-shares for ceremony '{}'", self.node_id, req.ceremony_id);
+
+        Ok(Response::new(DkgDealResponse {
+            dealer: session.index,
+            commitments: deal.commitments.iter().map(point_to_bytes).collect(),
+            sub_shares,
+        }))
+    }
+
+    /// refresh round 3: checks every holder's sub-share and stages the refreshed share
+    /// the stored share stays untouched until the commit
+    async fn refresh_apply(
+        &self,
+        request: Request<RefreshApplyRequest>,
+    ) -> Result<Response<RefreshApplyResponse>, Status> {
+        let req = request.into_inner();
+
+        let share = self.store.retrieve(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no share found for ceremony '{}'", req.ceremony_id))
+        })?;
+        let group_commitments = self.store.commitments(&req.ceremony_id).unwrap_or_default();
+
+        let mut sessions = self.refresh_sessions.lock().unwrap();
+        let session = sessions.get_mut(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no refresh session for ceremony '{}'", req.ceremony_id))
+        })?;
+        let own = session
+            .own_contribution
+            .clone()
+            .ok_or_else(|| Status::failed_precondition("this node has not dealt yet"))?;
+
+        let mut contributions = vec![own];
+        for message in &req.dealers {
+            let context = refresh_share_context(&req.ceremony_id, session.epoch, message.dealer, session.index);
+            let y = ecies::decrypt(&session.encryption_secret, &message.ciphertext, &context)
+                .ok()
+                .and_then(|plaintext| bytes_to_scalar(&plaintext))
+                .ok_or_else(|| {
+                    Status::failed_precondition(format!("undecryptable sub-share from dealer {}", message.dealer))
+                })?;
+
+            contributions.push(RefreshContribution {
+                dealer: message.dealer,
+                commitments: points_from_bytes(&message.commitments)?,
+                sub_share: SecretShare::new(share.x, y),
+            });
+        }
+
+        // every holder has to contribute, or a missing dealer could be one that is compromised
+        let mut dealers: Vec<u32> = contributions.iter().map(|c| c.dealer).collect();
+        dealers.sort_unstable();
+        let mut peers = session.peers.clone();
+        peers.sort_unstable();
+        if dealers != peers {
+            return Err(Status::failed_precondition("refresh needs one contribution from every holder"));
+        }
+
+        let (refreshed, commitments) =
+            refresh::apply_refresh(&share, &group_commitments, &contributions).map_err(protocol_error)?;
+        let response = RefreshApplyResponse {
+            index: session.index,
+            epoch: session.epoch + 1,
+            commitments: commitments.iter().map(point_to_bytes).collect(),
+        };
+        session.staged = Some((refreshed, commitments));
+
+        println!(
+            "[{}] staged refreshed share for ceremony '{}' (epoch {})",
+            self.node_id, req.ceremony_id, response.epoch
+        );
+        Ok(Response::new(response))
+    }
+
+    /// refresh round 4: replaces the stored share with the staged one
+    /// the old share is overwritten, so it no longer exists on this node
+    async fn refresh_commit(
+        &self,
+        request: Request<RefreshCommitRequest>,
+    ) -> Result<Response<StoreResponse>, Status> {
+        let req = request.into_inner();
+
+        let session = self
+            .refresh_sessions
+            .lock()
+            .unwrap()
+            .remove(&req.ceremony_id)
+            .ok_or_else(|| {
+                Status::not_found(format!("no refresh session for ceremony '{}'", req.ceremony_id))
+            })?;
+        let (share, commitments) = session
+            .staged
+            .ok_or_else(|| Status::failed_precondition("no refreshed share staged"))?;
+        if req.epoch != session.epoch + 1 || self.store.epoch(&req.ceremony_id) != session.epoch {
+            return Err(Status::failed_precondition("refresh epoch does not match"));
+        }
+
+        self.store
+            .store_refreshed(req.ceremony_id.clone(), share, commitments, req.epoch);
+
+        println!(
+            "[{}] refreshed share for ceremony '{}', now at epoch {}",
+            self.node_id, req.ceremony_id, req.epoch
+        );
+
+        Ok(Response::new(StoreResponse {
+            success: true,
+            message: format!("share refreshed to epoch {}", req.epoch),
+        }))
+    }
+
+    /// reshare round 1: opens a resharing session
+    /// reports the share this node holds today and, if it joins the new committee,
+    /// a fresh ephemeral key for the sub-shares dealt to it
+    async fn reshare_start(
+        &self,
+        request: Request<ReshareStartRequest>,
+    ) -> Result<Response<ReshareStartResponse>, Status> {
+        let req = request.into_inner();
+
+        if self.store.retrieve_packed(&req.ceremony_id).is_some() {
+            return Err(Status::failed_precondition(format!(
+                "ceremony '{}' holds a packed share, which can't be reshared",
+                req.ceremony_id
+            )));
+        }
+        let old_index = match self.store.retrieve(&req.ceremony_id) {
+            Some(share) => share_index(&share)?,
+            None => 0,
+        };
+        if old_index == 0 && req.new_index == 0 {
+            return Err(Status::invalid_argument(format!(
+                "node holds no share of '{}' and is not in the new committee",
+                req.ceremony_id
+            )));
+        }
+        // dealers check this node's key against its roster index, so it can't join as another
+        if req.new_index != 0 && req.new_index != self.identity.index() {
+            return Err(Status::failed_precondition(format!(
+                "this node is participant {} in the roster, not {}",
+                self.identity.index(),
+                req.new_index
+            )));
+        }
+
+        let encryption_secret = random_scalar();
+        let (encryption_key, key_signature) = if req.new_index == 0 {
+            (Vec::new(), Vec::new())
+        } else {
+            let key = point_to_bytes(&(ProjectivePoint::GENERATOR * encryption_secret));
+            let signature = self.identity.sign_key(KeyPurpose::Reshare, &req.ceremony_id, &key);
+            (key, signature)
+        };
+
+        self.reshare_sessions.lock().unwrap().insert(
+            req.ceremony_id.clone(),
+            ReshareSession {
+                new_index: req.new_index,
+                encryption_secret,
+                staged: None,
+                epoch: 0,
+                dealt: false,
+            },
+        );
+
+        println!(
+            "[{}] started resharing of ceremony '{}' (old index {}, new index {})",
+            self.node_id, req.ceremony_id, old_index, req.new_index
+        );
+
+        Ok(Response::new(ReshareStartResponse {
+            old_index,
+            epoch: self.store.epoch(&req.ceremony_id),
+            encryption_key,
+            commitments: self
+                .store
+                .commitments(&req.ceremony_id)
+                .unwrap_or_default()
+                .iter()
+                .map(point_to_bytes)
+                .collect(),
+            key_signature,
+        }))
+    }
+
+    /// reshare round 2: deals this node's share to the new committee
+    /// each sub-share is encrypted to its recipient so the client can't read it, and the
+    /// dealer signs its view of the old sharing for the new holders to agree on
+    async fn reshare_deal(
+        &self,
+        request: Request<ReshareDealRequest>,
+    ) -> Result<Response<ReshareDealResponse>, Status> {
+        let req = request.into_inner();
+
+        if !self.reshare_sessions.lock().unwrap().contains_key(&req.ceremony_id) {
+            return Err(Status::not_found(format!(
+                "no resharing session for ceremony '{}'",
+                req.ceremony_id
+            )));
+        }
+        let share = self.store.retrieve(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no share found for ceremony '{}'", req.ceremony_id))
+        })?;
+        let dealer = share_index(&share)?;
+        let old_threshold = self
+            .store
+            .commitments(&req.ceremony_id)
+            .map(|commitments| commitments.len())
+            .ok_or_else(|| Status::failed_precondition("the share has no group commitments to reshare from"))?;
+
+        // a dealer set below the old threshold or a repeated index would deal the wrong polynomial
+        let new_indices: Vec<u32> = req.recipients.iter().map(|peer| peer.index).collect();
+        let deal = reshare::deal_reshare(
+            dealer,
+            &share,
+            &req.dealers,
+            old_threshold,
+            req.new_threshold as usize,
+            &new_indices,
+        )
+        .map_err(protocol_error)?;
+
+        let mut sub_shares = Vec::new();
+        for (peer, sub_share) in req.recipients.iter().zip(deal.sub_shares) {
+            let recipient_key = self.peer_key(KeyPurpose::Reshare, &req.ceremony_id, peer)?;
+            let context = reshare_share_context(&req.ceremony_id, dealer, peer.index);
+            sub_shares.push(EncryptedSubShare {
+                recipient: peer.index,
+                ciphertext: ecies::encrypt(&recipient_key, &secret_to_bytes(&sub_share.y), &context),
+            });
+        }
+
+        println!(
+            "[{}] dealt its share of '{}' to {} new holders",
+            self.node_id,
+            req.ceremony_id,
+            sub_shares.len()
+        );
+
+        let view = self.signed_view(
+            Statement::ReshareView,
+            &req.ceremony_id,
+            &share,
+            &reshare_scope(&req.dealers, req.new_threshold, &new_indices),
+        );
+
+        Ok(Response::new(ReshareDealResponse {
+            dealer,
+            commitments: deal.commitments.iter().map(point_to_bytes).collect(),
+            sub_shares,
+            view: Some(view),
+        }))
+    }
+
+    /// reshare round 3: checks every dealer's sub-share and stages this node's new share
+    /// nothing is stored until the commit
+    ///
+    /// the old commitments, epoch, split metadata and dealt flag come from the dealers'
+    /// signed views, which must agree, so the client can't pick what a new holder stores
+    async fn reshare_complete(
+        &self,
+        request: Request<ReshareCompleteRequest>,
+    ) -> Result<Response<ReshareCompleteResponse>, Status> {
+        let req = request.into_inner();
+
+        let mut signers: Vec<u32> = req.views.iter().map(|view| view.index).collect();
+        signers.sort_unstable();
+        let mut dealers = req.dealers.clone();
+        dealers.sort_unstable();
+        if signers != dealers {
+            return Err(Status::invalid_argument("expected one sharing view from every dealer"));
+        }
+        let view = self.agreed_view(
+            Statement::ReshareView,
+            &req.ceremony_id,
+            &req.views,
+            &reshare_scope(&req.dealers, req.new_threshold, &req.recipients),
+        )?;
+        let old_commitments = points_from_bytes(&view.commitments)?;
+
+        // a node that held the old share knows the old sharing and won't take another
+        if let Some(stored) = self.store.commitments(&req.ceremony_id) {
+            if stored != old_commitments || self.store.epoch(&req.ceremony_id) != view.epoch {
+                return Err(Status::failed_precondition(
+                    "old sharing does not match the one this node holds",
+                ));
+            }
+        }
+
+        let mut sessions = self.reshare_sessions.lock().unwrap();
+        let session = sessions.get_mut(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no resharing session for ceremony '{}'", req.ceremony_id))
+        })?;
+        if session.new_index == 0 || !req.recipients.contains(&session.new_index) {
+            return Err(Status::failed_precondition("this node is not in the new committee"));
+        }
+
+        let mut contributions = Vec::new();
+        for message in &req.messages {
+            let context = reshare_share_context(&req.ceremony_id, message.dealer, session.new_index);
+            let y = ecies::decrypt(&session.encryption_secret, &message.ciphertext, &context)
+                .ok()
+                .and_then(|plaintext| bytes_to_scalar(&plaintext))
+                .ok_or_else(|| {
+                    Status::failed_precondition(format!("undecryptable sub-share from dealer {}", message.dealer))
+                })?;
+
+            contributions.push(ReshareContribution {
+                dealer: message.dealer,
+                commitments: points_from_bytes(&message.commitments)?,
+                sub_share: SecretShare::new(Scalar::from(session.new_index as u64), y),
+            });
+        }
+
+        let (share, commitments) =
+            reshare::complete_reshare(session.new_index, &old_commitments, &req.dealers, &contributions)
+                .map_err(protocol_error)?;
+        if commitments.len() != req.new_threshold as usize {
+            return Err(Status::failed_precondition("dealers reshared to a different threshold"));
+        }
+
+        // a tagged sharing stays tagged, as a new split of the same set at the next epoch
+        let epoch = view.epoch + 1;
+        let share = match convert::view_metadata(&view)? {
+            Some(meta) => {
+                let meta = ShareMetadata {
+                    threshold: req.new_threshold,
+                    total: req.recipients.len() as u32,
+                    epoch,
+                    ..meta
+                };
+                SecretShare::tagged(share.x, *share.y.expose_secret(), meta)
+            }
+            None => share,
+        };
+        let response = ReshareCompleteResponse {
+            index: session.new_index,
+            commitments: commitments.iter().map(point_to_bytes).collect(),
+        };
+        session.staged = Some((share, commitments));
+        session.epoch = epoch;
+        session.dealt = view.dealt;
+
+        println!(
+            "[{}] staged new share of '{}' as holder {}",
+            self.node_id, req.ceremony_id, session.new_index
+        );
+        Ok(Response::new(response))
+    }
+
+    /// reshare round 4: new holders store their staged share, leaving holders delete theirs
+    async fn reshare_commit(
+        &self,
+        request: Request<ReshareCommitRequest>,
+    ) -> Result<Response<StoreResponse>, Status> {
+        let req = request.into_inner();
+
+        let session = self
+            .reshare_sessions
+            .lock()
+            .unwrap()
+            .remove(&req.ceremony_id)
+            .ok_or_else(|| {
+                Status::not_found(format!("no resharing session for ceremony '{}'", req.ceremony_id))
+            })?;
+
+        let message = match session.staged {
+            Some(_) if req.epoch != session.epoch => {
+                return Err(Status::failed_precondition("resharing epoch does not match"));
+            }
+            Some((share, commitments)) => {
+                self.store.store_rebuilt(
+                    req.ceremony_id.clone(),
+                    share,
+                    commitments,
+                    session.epoch,
+                    session.dealt,
+                );
+                format!("stored new share as holder {} (epoch {})", session.new_index, session.epoch)
+            }
+            None if session.new_index == 0 => {
+                self.store.remove(&req.ceremony_id);
+                "left the committee and deleted the old share".to_string()
+            }
+            None => return Err(Status::failed_precondition("no new share staged")),
+        };
+
+        println!("[{}] resharing of '{}': {}", self.node_id, req.ceremony_id, message);
+        Ok(Response::new(StoreResponse { success: true, message }))
+    }
+
+    /// repair round 1: opens a repair session for the share at the lost index
+    /// a node holding a share joins as a helper, a node without one is the one being repaired
+    async fn repair_start(
+        &self,
+        request: Request<RepairStartRequest>,
+    ) -> Result<Response<RepairStartResponse>, Status> {
+        let req = request.into_inner();
+
+        let index = match self.store.retrieve(&req.ceremony_id) {
+            Some(share) => share_index(&share)?,
+            None => 0,
+        };
+        if req.lost_index == 0 || index == req.lost_index {
+            return Err(Status::invalid_argument(format!(
+                "share {} of '{}' is not lost",
+                req.lost_index, req.ceremony_id
+            )));
+        }
+        // only a committee member can be repaired, and only on the node holding its identity
+        if self.roster.identity(req.lost_index).is_none() {
+            return Err(Status::invalid_argument(format!(
+                "index {} is not in the roster",
+                req.lost_index
+            )));
+        }
+        let purpose = if index == 0 {
+            if req.lost_index != self.identity.index() {
+                return Err(Status::failed_precondition(format!(
+                    "this node is participant {} in the roster, not {}",
+                    self.identity.index(),
+                    req.lost_index
+                )));
+            }
+            KeyPurpose::RepairRecipient
+        } else {
+            KeyPurpose::RepairHelper
+        };
+
+        let encryption_secret = random_scalar();
+        let encryption_key = point_to_bytes(&(ProjectivePoint::GENERATOR * encryption_secret));
+        // the recipient signature is what helpers send their sums to, and a node only
+        // signs one while it holds no share, so a holder that still answers can't be repaired
+        let key_signature = self.identity.sign_key(purpose, &req.ceremony_id, &encryption_key);
+
+        self.repair_sessions.lock().unwrap().insert(
+            req.ceremony_id.clone(),
+            RepairSession {
+                lost_index: req.lost_index,
+                index,
+                encryption_secret,
+                helpers: Vec::new(),
+                own_piece: None,
+            },
+        );
+
+        if index == 0 {
+            println!(
+                "[{}] waiting for share {} of ceremony '{}' to be repaired",
+                self.node_id, req.lost_index, req.ceremony_id
+            );
+        } else {
+            println!(
+                "[{}] helping repair share {} of ceremony '{}'",
+                self.node_id, req.lost_index, req.ceremony_id
+            );
+        }
+
+        Ok(Response::new(RepairStartResponse {
+            index,
+            epoch: self.store.epoch(&req.ceremony_id),
+            encryption_key,
+            commitments: self
+                .store
+                .commitments(&req.ceremony_id)
+                .unwrap_or_default()
+                .iter()
+                .map(point_to_bytes)
+                .collect(),
+            key_signature,
+        }))
+    }
+
+    /// repair round 2: splits this helper's lagrange term into one masked piece per helper
+    /// each piece is encrypted to the helper it is meant for
+    async fn repair_split(
+        &self,
+        request: Request<RepairSplitRequest>,
+    ) -> Result<Response<RepairSplitResponse>, Status> {
+        let req = request.into_inner();
+
+        let share = self.store.retrieve(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no share found for ceremony '{}'", req.ceremony_id))
+        })?;
+
+        let mut sessions = self.repair_sessions.lock().unwrap();
+        let session = sessions.get_mut(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no repair session for ceremony '{}'", req.ceremony_id))
+        })?;
+
+        // fewer helpers than the threshold would leak combinations of their shares
+        let threshold = self
+            .store
+            .commitments(&req.ceremony_id)
+            .map(|commitments| commitments.len())
+            .ok_or_else(|| Status::failed_precondition("the share has no group commitments to repair against"))?;
+        if req.helpers.len() < threshold {
+            return Err(Status::failed_precondition(format!(
+                "repair needs at least {} helpers, got {}",
+                threshold,
+                req.helpers.len()
+            )));
+        }
+
+        let helpers: Vec<u32> = req.helpers.iter().map(|peer| peer.index).collect();
+        let mut pieces =
+            repair::repair_pieces(&share, &helpers, session.lost_index, threshold).map_err(protocol_error)?;
+        session.own_piece = pieces.remove(&session.index);
+
+        let mut encrypted = Vec::new();
+        for peer in req.helpers.iter().filter(|peer| peer.index != session.index) {
+            let helper_key = self.peer_key(KeyPurpose::RepairHelper, &req.ceremony_id, peer)?;
+            let context = repair_piece_context(&req.ceremony_id, session.lost_index, session.index, peer.index);
+            encrypted.push(RepairPiece {
+                from: session.index,
+                to: peer.index,
+                ciphertext: ecies::encrypt(&helper_key, &scalar_to_bytes(&pieces[&peer.index]), &context),
+            });
+        }
+        session.helpers = helpers;
+
+        println!("[{}] split its repair term for ceremony '{}'", self.node_id, req.ceremony_id);
+        Ok(Response::new(RepairSplitResponse { pieces: encrypted }))
+    }
+
+    /// repair round 3: sums the pieces this helper received and encrypts the sum
+    /// to the node being repaired, along with this helper's signed view of the sharing;
+    /// the helper's part of the repair ends here
+    async fn repair_combine(
+        &self,
+        request: Request<RepairCombineRequest>,
+    ) -> Result<Response<RepairSum>, Status> {
+        let req = request.into_inner();
+
+        let share = self.store.retrieve(&req.ceremony_id).ok_or_else(|| {
+            Status::not_found(format!("no share found for ceremony '{}'", req.ceremony_id))
+        })?;
+
+        let session = self
+            .repair_sessions
+            .lock()
+            .unwrap()
+            .remove(&req.ceremony_id)
+            .ok_or_else(|| {
+                Status::not_found(format!("no repair session for ceremony '{}'", req.ceremony_id))
+            })?;
+        let own_piece = session
+            .own_piece
+            .ok_or_else(|| Status::failed_precondition("this helper has not split its term yet"))?;
+
+        let mut pieces = vec![own_piece];
+        for from in session.helpers.iter().filter(|helper| **helper != session.index) {
+            let piece = req
+                .pieces
+                .iter()
+                .find(|piece| piece.from == *from && piece.to == session.index)
+                .ok_or_else(|| Status::failed_precondition(format!("missing repair piece from helper {}", from)))?;
+            let context = repair_piece_context(&req.ceremony_id, session.lost_index, *from, session.index);
+            let piece = ecies::decrypt(&session.encryption_secret, &piece.ciphertext, &context)
+                .ok()
+                .and_then(|plaintext| bytes_to_scalar(&plaintext))
+                .ok_or_else(|| Status::failed_precondition(format!("undecryptable repair piece from helper {}", from)))?;
+            pieces.push(piece);
+        }
+
+        let recipient_key = self.peer_key(
+            KeyPurpose::RepairRecipient,
+            &req.ceremony_id,
+            &PeerKey {
+                index: session.lost_index,
+                encryption_key: req.recipient_key,
+                signature: req.recipient_key_signature,
+            },
+        )?;
+        let context = repair_piece_context(&req.ceremony_id, session.lost_index, session.index, session.lost_index);
+        let sum = repair::sum_pieces(&pieces);
+        let view = self.signed_view(
+            Statement::RepairView,
+            &req.ceremony_id,
+            &share,
+            &repair_scope(session.lost_index, &session.helpers),
+        );
+
+        println!(
+            "[{}] sent its repair sum for share {} of ceremony '{}'",
+            self.node_id, session.lost_index, req.ceremony_id
+        );
+
+        Ok(Response::new(RepairSum {
+            sum: Some(RepairPiece {
+                from: session.index,
+                to: session.lost_index,
+                ciphertext: ecies::encrypt(&recipient_key, &scalar_to_bytes(&sum), &context),
+            }),
+            view: Some(view),
+        }))
+    }
+
+    /// repair round 4: adds up the helpers' sums and stores the rebuilt share
+    /// once it checks out against the group commitments
+    ///
+    /// the commitments, epoch, split metadata and dealt flag all come from the helpers'
+    /// signed views, which must agree, so the client can't pick the sharing being rebuilt
+    async fn repair_complete(
+        &self,
+        request: Request<RepairCompleteRequest>,
+    ) -> Result<Response<StoreResponse>, Status> {
+        let req = request.into_inner();
+
+        let session = self
+            .repair_sessions
+            .lock()
+            .unwrap()
+            .remove(&req.ceremony_id)
+            .ok_or_else(|| {
+                Status::not_found(format!("no repair session for ceremony '{}'", req.ceremony_id))
+            })?;
+        if session.index != 0 {
+            return Err(Status::failed_precondition("this node is a helper, not the one being repaired"));
+        }
+
+        // one sum per helper, each with the view of the helper that sent it, so a sum
+        // can't be replayed to make up the count
+        let mut pieces = Vec::new();
+        let mut views = Vec::new();
+        for sum in &req.sums {
+            let (Some(piece), Some(view)) = (&sum.sum, &sum.view) else {
+                return Err(Status::invalid_argument("repair sum without its sharing view"));
+            };
+            if piece.from != view.index || piece.to != session.lost_index {
+                return Err(Status::invalid_argument(format!(
+                    "repair sum from helper {} is not addressed to share {}",
+                    piece.from, session.lost_index
+                )));
+            }
+            pieces.push(piece);
+            views.push(view.clone());
+        }
+        let mut helpers: Vec<u32> = pieces.iter().map(|piece| piece.from).collect();
+        helpers.sort_unstable();
+        if let Some(pair) = helpers.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(Status::invalid_argument(format!("more than one repair sum from helper {}", pair[0])));
+        }
+        let view = self.agreed_view(
+            Statement::RepairView,
+            &req.ceremony_id,
+            &views,
+            &repair_scope(session.lost_index, &helpers),
+        )?;
+
+        let mut sums = Vec::new();
+        for piece in pieces {
+            let context = repair_piece_context(&req.ceremony_id, session.lost_index, piece.from, session.lost_index);
+            let sum = ecies::decrypt(&session.encryption_secret, &piece.ciphertext, &context)
+                .ok()
+                .and_then(|plaintext| bytes_to_scalar(&plaintext))
+                .ok_or_else(|| Status::failed_precondition(format!("undecryptable repair sum from helper {}", piece.from)))?;
+            sums.push(sum);
+        }
+
+        let commitments = points_from_bytes(&view.commitments)?;
+        let share = repair::complete_repair(session.lost_index, &sums, &commitments).map_err(protocol_error)?;
+        // the rebuilt share is tagged like the helpers' shares, it sits at the same epoch
+        let share = match convert::view_metadata(&view)? {
+            Some(meta) => SecretShare::tagged(share.x, *share.y.expose_secret(), meta),
+            None => share,
+        };
+        self.store
+            .store_rebuilt(req.ceremony_id.clone(), share, commitments, view.epoch, view.dealt);
+
+        println!(
+            "[{}] repaired share {} of ceremony '{}'",
+            self.node_id, session.lost_index, req.ceremony_id
+        );
+
+        Ok(Response::new(StoreResponse {
+            success: true,
+            message: format!("share {} repaired", session.lost_index),
+        }))
+    }
 }
@@ -1,17 +1,32 @@
 // in-memory storage for secret shares
 // uses thread-safe concurrent access for the grpc server
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::RwLock;
+use k256::elliptic_curve::group::GroupEncoding;
 use k256::elliptic_curve::scalar::ScalarPrimitive;
-use k256::{Scalar, Secp256k1};
-use crate::core::scheme::Share;
+use k256::{ProjectivePoint, Scalar, Secp256k1};
+use crate::core::packed::PackedShare;
+use crate::core::scheme::SecretShare;
+use crate::core::secret::SecretScalar;
 
 /// stores shares keyed by ceremony id
 /// thread-safe for concurrent grpc requests
 pub struct ShareStore {
-    // maps ceremony_id -> Share
-    shares: RwLock<HashMap<String, Share>>,
+    // maps ceremony_id -> SecretShare
+    shares: RwLock<HashMap<String, SecretShare>>,
+    // maps ceremony_id -> feldman commitments published by the dealer
+    commitments: RwLock<HashMap<String, Vec<ProjectivePoint>>>,
+    // maps ceremony_id -> number of refreshes applied to the share
+    epochs: RwLock<HashMap<String, u64>>,
+    // maps ceremony_id -> the shares of a weighted custodian, one per vote
+    weighted: RwLock<HashMap<String, Vec<SecretShare>>>,
+    // maps ceremony_id -> (secrets, privacy threshold) of a packed share
+    packing: RwLock<HashMap<String, (u32, u32)>>,
+    // maps ceremony_id -> bip-32 chain code of the shared key
+    chain_codes: RwLock<HashMap<String, [u8; 32]>>,
+    // ceremonies whose share a dealer stored, the only ones that may be handed out
+    dealt: RwLock<HashSet<String>>,
 }
 
 impl ShareStore {
@@ -19,23 +34,176 @@ impl ShareStore {
     pub fn new() -> Self {
         Self {
             shares: RwLock::new(HashMap::new()),
+            commitments: RwLock::new(HashMap::new()),
+            epochs: RwLock::new(HashMap::new()),
+            weighted: RwLock::new(HashMap::new()),
+            packing: RwLock::new(HashMap::new()),
+            chain_codes: RwLock::new(HashMap::new()),
+            dealt: RwLock::new(HashSet::new()),
         }
     }
 
     /// stores a share for a given ceremony
-    /// overwrites if the ceremony_id already exists
-    pub fn store(&self, ceremony_id: String, share: Share) {
+    /// overwrites if the ceremony_id already exists, starting again at epoch 0 and
+    /// dropping everything stored alongside the old share, commitments included
+    pub fn store(&self, ceremony_id: String, share: SecretShare) {
+        self.dealt.write().unwrap().remove(&ceremony_id);
+        self.commitments.write().unwrap().remove(&ceremony_id);
+        self.weighted.write().unwrap().remove(&ceremony_id);
+        self.epochs.write().unwrap().remove(&ceremony_id);
+        self.packing.write().unwrap().remove(&ceremony_id);
+        self.chain_codes.write().unwrap().remove(&ceremony_id);
         let mut store = self.shares.write().unwrap();
         store.insert(ceremony_id, share);
     }
 
+    /// stores a packed share covering a batch of secrets for a given ceremony
+    /// overwrites if the ceremony_id already exists, like `store`
+    pub fn store_packed(&self, ceremony_id: String, packed: PackedShare) {
+        let mut shares = self.shares.write().unwrap();
+        self.dealt.write().unwrap().remove(&ceremony_id);
+        self.commitments.write().unwrap().remove(&ceremony_id);
+        self.weighted.write().unwrap().remove(&ceremony_id);
+        self.epochs.write().unwrap().remove(&ceremony_id);
+        self.chain_codes.write().unwrap().remove(&ceremony_id);
+        self.packing
+            .write()
+            .unwrap()
+            .insert(ceremony_id.clone(), (packed.secrets, packed.privacy_threshold));
+        shares.insert(ceremony_id, packed.share);
+    }
+
+    /// retrieves a ceremony's share as a packed share
+    /// returns none if the ceremony_id doesn't exist or holds a plain share
+    pub fn retrieve_packed(&self, ceremony_id: &str) -> Option<PackedShare> {
+        let shares = self.shares.read().unwrap();
+        let (secrets, privacy_threshold) = *self.packing.read().unwrap().get(ceremony_id)?;
+        Some(PackedShare {
+            secrets,
+            privacy_threshold,
+            share: shares.get(ceremony_id)?.clone(),
+        })
+    }
+
+    /// replaces a share and its commitments with their refreshed versions
+    pub fn store_refreshed(&self, ceremony_id: String, share: SecretShare, commitments: Vec<ProjectivePoint>, epoch: u64) {
+        // holding the share lock keeps readers from seeing a new share with old commitments
+        let mut shares = self.shares.write().unwrap();
+        self.commitments.write().unwrap().insert(ceremony_id.clone(), commitments);
+        self.epochs.write().unwrap().insert(ceremony_id.clone(), epoch);
+        shares.insert(ceremony_id, share);
+    }
+
+    /// stores a share rebuilt from other holders by repair or resharing
+    /// the dealt flag comes from those holders, whatever this node stored before is dropped
+    /// except the chain code, which belongs to the key and not to the sharing
+    pub fn store_rebuilt(
+        &self,
+        ceremony_id: String,
+        share: SecretShare,
+        commitments: Vec<ProjectivePoint>,
+        epoch: u64,
+        dealt: bool,
+    ) {
+        let mut shares = self.shares.write().unwrap();
+        self.weighted.write().unwrap().remove(&ceremony_id);
+        self.packing.write().unwrap().remove(&ceremony_id);
+        if dealt {
+            self.dealt.write().unwrap().insert(ceremony_id.clone());
+        } else {
+            self.dealt.write().unwrap().remove(&ceremony_id);
+        }
+        self.commitments.write().unwrap().insert(ceremony_id.clone(), commitments);
+        self.epochs.write().unwrap().insert(ceremony_id.clone(), epoch);
+        shares.insert(ceremony_id, share);
+    }
+
+    /// deletes a ceremony's share along with its commitments and epoch
+    pub fn remove(&self, ceremony_id: &str) {
+        let mut shares = self.shares.write().unwrap();
+        self.commitments.write().unwrap().remove(ceremony_id);
+        self.epochs.write().unwrap().remove(ceremony_id);
+        self.weighted.write().unwrap().remove(ceremony_id);
+        self.packing.write().unwrap().remove(ceremony_id);
+        self.chain_codes.write().unwrap().remove(ceremony_id);
+        self.dealt.write().unwrap().remove(ceremony_id);
+        shares.remove(ceremony_id);
+    }
+
+    /// stores every share of a weighted custodian for a given ceremony
+    /// overwrites if the ceremony_id already exists, like `store`, so a plain share stored
+    /// under the same ceremony before is dropped and can no longer be handed out or used
+    pub fn store_weighted(&self, ceremony_id: String, shares: Vec<SecretShare>) {
+        let mut store = self.weighted.write().unwrap();
+        self.shares.write().unwrap().remove(&ceremony_id);
+        self.dealt.write().unwrap().remove(&ceremony_id);
+        self.commitments.write().unwrap().remove(&ceremony_id);
+        self.epochs.write().unwrap().remove(&ceremony_id);
+        self.packing.write().unwrap().remove(&ceremony_id);
+        self.chain_codes.write().unwrap().remove(&ceremony_id);
+        store.insert(ceremony_id, shares);
+    }
+
+    /// retrieves a weighted custodian's shares for a given ceremony
+    /// returns none if the ceremony_id doesn't exist
+    pub fn retrieve_weighted(&self, ceremony_id: &str) -> Option<Vec<SecretShare>> {
+        let store = self.weighted.read().unwrap();
+        store.get(ceremony_id).cloned()
+    }
+
+    /// the refresh epoch of a ceremony's share, 0 if it was never refreshed
+    pub fn epoch(&self, ceremony_id: &str) -> u64 {
+        let epochs = self.epochs.read().unwrap();
+        epochs.get(ceremony_id).copied().unwrap_or(0)
+    }
+
     /// retrieves a share for a given ceremony
     /// returns none if the ceremony_id doesn't exist
-    pub fn retrieve(&self, ceremony_id: &str) -> Option<Share> {
+    pub fn retrieve(&self, ceremony_id: &str) -> Option<SecretShare> {
         let store = self.shares.read().unwrap();
         store.get(ceremony_id).cloned()
     }
 
+    /// stores the feldman commitments for a given ceremony
+    /// overwrites if the ceremony_id already exists
+    pub fn store_commitments(&self, ceremony_id: String, commitments: Vec<ProjectivePoint>) {
+        let mut store = self.commitments.write().unwrap();
+        store.insert(ceremony_id, commitments);
+    }
+
+    /// retrieves the feldman commitments for a given ceremony
+    /// returns none if the share was stored without commitments
+    pub fn commitments(&self, ceremony_id: &str) -> Option<Vec<ProjectivePoint>> {
+        let store = self.commitments.read().unwrap();
+        store.get(ceremony_id).cloned()
+    }
+
+    /// stores the bip-32 chain code of a ceremony's key
+    /// overwrites if the ceremony_id already has one
+    pub fn store_chain_code(&self, ceremony_id: String, chain_code: [u8; 32]) {
+        let mut store = self.chain_codes.write().unwrap();
+        store.insert(ceremony_id, chain_code);
+    }
+
+    /// retrieves the bip-32 chain code of a ceremony's key
+    /// returns none if it was never set
+    pub fn chain_code(&self, ceremony_id: &str) -> Option<[u8; 32]> {
+        let store = self.chain_codes.read().unwrap();
+        store.get(ceremony_id).copied()
+    }
+
+    /// records that a dealer stored the ceremony's share, so it may be retrieved
+    /// storing the ceremony again clears the mark, refreshing keeps it
+    pub fn mark_dealt(&self, ceremony_id: &str) {
+        self.dealt.write().unwrap().insert(ceremony_id.to_string());
+    }
+
+    /// whether the ceremony's share came from a dealer, who knew the secret anyway
+    /// shares out of key generation never are, so the client can't collect them
+    pub fn is_dealt(&self, ceremony_id: &str) -> bool {
+        self.dealt.read().unwrap().contains(ceremony_id)
+    }
+
     /// checks if a share exists for a given ceremony
     pub fn exists(&self, ceremony_id: &str) -> bool {
         let store = self.shares.read().unwrap();
@@ -55,11 +223,57 @@ pub fn scalar_to_bytes(scalar: &Scalar) -> Vec<u8> {
 }
 
 /// converts bytes back to a k256 scalar
-/// uses modular reduction to ensure the result is valid
-pub fn bytes_to_scalar(bytes: &[u8]) -> Scalar {
-    let arr: [u8; 32] = bytes.try_into().expect("invalid scalar bytes length");
-    // using scalar primitive for safe conversion with modular reduction
-    let primitive = ScalarPrimitive::<Secp256k1>::from_bytes((&arr).into()).unwrap();
-    Scalar::from(primitive)
+/// returns none unless the bytes are a 32-byte big-endian value below the group order,
+/// they come off the network and a malformed one must not bring the process down
+pub fn bytes_to_scalar(bytes: &[u8]) -> Option<Scalar> {
+    let arr: [u8; 32] = bytes.try_into().ok()?;
+    let primitive: Option<ScalarPrimitive<Secp256k1>> = ScalarPrimitive::from_bytes((&arr).into()).into();
+    primitive.map(Scalar::from)
+}
+
+/// converts a secret share value to bytes for grpc transmission
+pub fn secret_to_bytes(secret: &SecretScalar) -> Vec<u8> {
+    scalar_to_bytes(secret.expose_secret())
 }
 
+/// converts bytes back to a secret share value, see `bytes_to_scalar`
+pub fn bytes_to_secret(bytes: &[u8]) -> Option<SecretScalar> {
+    bytes_to_scalar(bytes).map(SecretScalar::new)
+}
+
+/// converts a curve point to 33-byte compressed sec1 encoding for grpc transmission
+pub fn point_to_bytes(point: &ProjectivePoint) -> Vec<u8> {
+    point.to_bytes().to_vec()
+}
+
+/// converts compressed sec1 bytes back to a curve point
+/// returns none if the bytes are not a valid point on secp256k1
+pub fn bytes_to_point(bytes: &[u8]) -> Option<ProjectivePoint> {
+    let arr: [u8; 33] = bytes.try_into().ok()?;
+    ProjectivePoint::from_bytes((&arr).into()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_store_drops_plain_share() {
+        let store = ShareStore::new();
+        let ceremony_id = "ceremony-001".to_string();
+        store.store(ceremony_id.clone(), SecretShare::new(Scalar::ONE, Scalar::from(7u64)));
+        store.store_commitments(ceremony_id.clone(), vec![ProjectivePoint::GENERATOR]);
+        store.store_chain_code(ceremony_id.clone(), [1; 32]);
+        store.mark_dealt(&ceremony_id);
+
+        let weighted = vec![SecretShare::new(Scalar::from(2u64), Scalar::from(9u64))];
+        store.store_weighted(ceremony_id.clone(), weighted);
+
+        assert!(store.retrieve(&ceremony_id).is_none());
+        assert!(!store.exists(&ceremony_id));
+        assert!(!store.is_dealt(&ceremony_id));
+        assert!(store.commitments(&ceremony_id).is_none());
+        assert!(store.chain_code(&ceremony_id).is_none());
+        assert_eq!(store.retrieve_weighted(&ceremony_id).unwrap().len(), 1);
+    }
+}